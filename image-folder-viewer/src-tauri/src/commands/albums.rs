@@ -0,0 +1,256 @@
+// アルバム
+//
+// 複数のフォルダ（アーカイブを含む）から選んだ画像を手動で並べたアルバムを編集し、
+// フォルダのカードと同じ ImageFile の一覧として返す。アルバムは画像のパスを直接保持するため、
+// 画像の移動・削除で見つからなくなった画像は一覧から除き、検証コマンドで報告する。
+// 編集コマンドは編集中のプロファイルを受け取り、更新後のプロファイルを返す（保存はしない）。
+
+use super::blocking::run_blocking;
+use super::images::{relative_to, ImageChecker, ImageFile};
+use crate::models::{Album, AlbumItem, Card, ProfileData};
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// アルバムの検証結果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlbumStatus {
+    pub album_id: String,
+    /// 画像数（見つからない画像を含む）
+    pub item_count: u32,
+    /// 見つからない画像のパス（アルバムの表示順）
+    pub missing_paths: Vec<String>,
+}
+
+/// アルバムを取得
+fn find_album<'a>(profile: &'a mut ProfileData, album_id: &str) -> Result<&'a mut Album, String> {
+    profile
+        .albums
+        .iter_mut()
+        .find(|album| album.id == album_id)
+        .ok_or_else(|| format!("アルバムが見つかりません: {}", album_id))
+}
+
+/// アルバム名を検証して前後の空白を除く
+fn validate_title(title: &str) -> Result<String, String> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err("アルバム名を入力してください".to_string());
+    }
+    Ok(title.to_string())
+}
+
+/// 画像を含むカードを取得（フォルダが入れ子になっている場合は最も深いカード）
+fn owner_card<'a>(cards: &'a [Card], path: &str) -> Option<(&'a Card, String)> {
+    cards
+        .iter()
+        .filter_map(|card| {
+            relative_to(path, &card.folder_path)
+                .filter(|relative| !relative.is_empty())
+                .map(|relative| (card, relative.replace('\\', "/")))
+        })
+        .max_by_key(|(card, _)| card.folder_path.len())
+}
+
+/// アルバムの画像を ImageFile に変換
+fn to_image_file(cards: &[Card], path: &str) -> ImageFile {
+    let filename = path
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let (card_id, relative_path) = match owner_card(cards, path) {
+        Some((card, relative)) => (Some(card.id.clone()), relative),
+        None => (None, filename.clone()),
+    };
+    ImageFile {
+        path: path.to_string(),
+        filename,
+        relative_path,
+        card_id,
+    }
+}
+
+/// 更新日時を現在時刻にする
+fn touch(mut profile: ProfileData, album_id: &str) -> ProfileData {
+    let now = chrono::Utc::now().to_rfc3339();
+    if let Some(album) = profile.albums.iter_mut().find(|album| album.id == album_id) {
+        album.updated_at = now.clone();
+    }
+    profile.updated_at = now;
+    profile
+}
+
+/// アルバムを作成し、更新後のプロファイルを返す（作成したアルバムは末尾に追加される）
+#[tauri::command]
+pub fn create_album(mut profile: ProfileData, title: String) -> Result<ProfileData, String> {
+    let title = validate_title(&title)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let sort_order = profile
+        .albums
+        .iter()
+        .map(|album| album.sort_order + 1)
+        .max()
+        .unwrap_or(0);
+    profile.albums.push(Album {
+        id: uuid::Uuid::new_v4().to_string(),
+        title,
+        items: Vec::new(),
+        sort_order,
+        created_at: now.clone(),
+        updated_at: now.clone(),
+    });
+    profile.updated_at = now;
+    Ok(profile)
+}
+
+/// アルバム名を変更し、更新後のプロファイルを返す
+#[tauri::command]
+pub fn rename_album(
+    mut profile: ProfileData,
+    album_id: String,
+    title: String,
+) -> Result<ProfileData, String> {
+    let title = validate_title(&title)?;
+    find_album(&mut profile, &album_id)?.title = title;
+    Ok(touch(profile, &album_id))
+}
+
+/// アルバムを削除し、更新後のプロファイルを返す（画像ファイルは削除しない）
+#[tauri::command]
+pub fn delete_album(mut profile: ProfileData, album_id: String) -> Result<ProfileData, String> {
+    find_album(&mut profile, &album_id)?;
+    profile.albums.retain(|album| album.id != album_id);
+    profile.updated_at = chrono::Utc::now().to_rfc3339();
+    Ok(profile)
+}
+
+/// アルバムに画像を追加し、更新後のプロファイルを返す
+///
+/// index を指定した場合はその位置に、省略した場合は末尾に追加する。
+/// アルバムにすでにある画像は追加しない。存在しない画像を指定した場合はエラーとする。
+#[tauri::command]
+pub fn add_album_images(
+    mut profile: ProfileData,
+    album_id: String,
+    paths: Vec<String>,
+    index: Option<u32>,
+) -> Result<ProfileData, String> {
+    let mut checker = ImageChecker::default();
+    if let Some(path) = paths.iter().find(|path| !checker.exists(path)) {
+        return Err(format!("画像が見つかりません: {}", path));
+    }
+
+    let album = find_album(&mut profile, &album_id)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut existing: HashSet<String> = album.items.iter().map(|item| item.path.clone()).collect();
+    let new_items: Vec<AlbumItem> = paths
+        .into_iter()
+        .filter(|path| existing.insert(path.clone()))
+        .map(|path| AlbumItem {
+            path,
+            added_at: now.clone(),
+        })
+        .collect();
+
+    let index = index.map_or(album.items.len(), |index| {
+        (index as usize).min(album.items.len())
+    });
+    album.items.splice(index..index, new_items);
+    Ok(touch(profile, &album_id))
+}
+
+/// アルバムから画像を外し、更新後のプロファイルを返す（画像ファイルは削除しない）
+#[tauri::command]
+pub fn remove_album_images(
+    mut profile: ProfileData,
+    album_id: String,
+    paths: Vec<String>,
+) -> Result<ProfileData, String> {
+    let paths: HashSet<&str> = paths.iter().map(String::as_str).collect();
+    find_album(&mut profile, &album_id)?
+        .items
+        .retain(|item| !paths.contains(item.path.as_str()));
+    Ok(touch(profile, &album_id))
+}
+
+/// アルバムの画像を並べ替え、更新後のプロファイルを返す
+///
+/// paths にはアルバムのすべての画像のパスを新しい順序で指定する。
+#[tauri::command]
+pub fn reorder_album_images(
+    mut profile: ProfileData,
+    album_id: String,
+    paths: Vec<String>,
+) -> Result<ProfileData, String> {
+    let album = find_album(&mut profile, &album_id)?;
+    let unique: HashSet<&str> = paths.iter().map(String::as_str).collect();
+    let is_permutation = paths.len() == album.items.len()
+        && unique.len() == paths.len()
+        && album
+            .items
+            .iter()
+            .all(|item| unique.contains(item.path.as_str()));
+    if !is_permutation {
+        return Err("並べ替え後の画像がアルバムの画像と一致しません".to_string());
+    }
+
+    let mut items = std::mem::take(&mut album.items);
+    album.items = paths
+        .iter()
+        .filter_map(|path| {
+            let position = items.iter().position(|item| item.path == *path)?;
+            Some(items.swap_remove(position))
+        })
+        .collect();
+    Ok(touch(profile, &album_id))
+}
+
+/// アルバムの画像一覧を取得（見つからない画像は除く）
+///
+/// カードのフォルダ内の画像は ImageFile.card_id にカードを、relative_path にカードの
+/// ルートフォルダからの相対パスを設定する。それ以外の画像の relative_path はファイル名とする。
+#[tauri::command]
+pub async fn list_album_images(
+    profile: ProfileData,
+    album_id: String,
+) -> Result<Vec<ImageFile>, String> {
+    run_blocking(move || {
+        let album = profile
+            .albums
+            .iter()
+            .find(|album| album.id == album_id)
+            .ok_or_else(|| format!("アルバムが見つかりません: {}", album_id))?;
+        let mut checker = ImageChecker::default();
+        Ok(album
+            .items
+            .iter()
+            .filter(|item| checker.exists(&item.path))
+            .map(|item| to_image_file(&profile.cards, &item.path))
+            .collect())
+    })
+    .await
+}
+
+/// すべてのアルバムについて、見つからない画像を検証
+#[tauri::command]
+pub async fn validate_albums(profile: ProfileData) -> Result<Vec<AlbumStatus>, String> {
+    run_blocking(move || {
+        let mut checker = ImageChecker::default();
+        Ok(profile
+            .albums
+            .iter()
+            .map(|album| AlbumStatus {
+                album_id: album.id.clone(),
+                item_count: album.items.len() as u32,
+                missing_paths: album
+                    .items
+                    .iter()
+                    .filter(|item| !checker.exists(&item.path))
+                    .map(|item| item.path.clone())
+                    .collect(),
+            })
+            .collect())
+    })
+    .await
+}
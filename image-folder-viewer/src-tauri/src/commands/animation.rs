@@ -0,0 +1,275 @@
+// アニメーション画像（GIF/WebP/APNG）のフレーム操作
+//
+// フレームは image クレートの AnimationDecoder で、前のフレームと合成済みの
+// キャンバス全体の RGBA 画像として取り出す。静止画は1フレームの画像として扱う。
+// フレーム数・表示時間の取得ではフレームを展開せず、フレームのヘッダーのみを読む。
+
+use super::archive;
+use super::blocking::run_blocking;
+use super::formats;
+use super::image_info::count_frames;
+use super::images::load_image;
+use image::codecs::gif::{GifDecoder, GifEncoder, Repeat};
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::{AnimationDecoder, DynamicImage, Frame, Frames, ImageFormat};
+use serde::Serialize;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+
+/// アニメーションサムネイルに含める最大フレーム数
+const MAX_THUMBNAIL_FRAMES: usize = 100;
+
+/// アニメーションサムネイルに含める最大再生時間（ミリ秒、超えた分は切り捨て）
+const MAX_THUMBNAIL_DURATION_MS: u32 = 5000;
+
+/// GIFエンコード時の減色速度（1〜30、大きいほど高速・低画質）
+const THUMBNAIL_GIF_SPEED: i32 = 10;
+
+/// アニメーション情報
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimationInfo {
+    /// アニメーション画像かどうか（静止画は false）
+    pub animated: bool,
+    /// フレーム数（静止画は 1）
+    pub frame_count: u32,
+    /// 各フレームの表示時間（ミリ秒、静止画は空）
+    pub frame_delays: Vec<u32>,
+    /// 1周の再生時間（ミリ秒）
+    pub total_duration: u32,
+}
+
+/// 画像ファイルのバイト列と形式を読み込む（アーカイブ内の画像にも対応）
+fn read_source(image_path: &str) -> Result<(Vec<u8>, Option<ImageFormat>), String> {
+    let (bytes, name) = match archive::split_member_path(image_path) {
+        Some((archive_path, member)) => (archive::read_member(&archive_path, &member)?, member),
+        None => (
+            fs::read(image_path).map_err(|e| format!("画像の読み込みに失敗しました: {}", e))?,
+            image_path.to_string(),
+        ),
+    };
+    let format = image::guess_format(&bytes)
+        .ok()
+        .or_else(|| formats::format_from_path(Path::new(&name)));
+    Ok((bytes, format))
+}
+
+/// アニメーション画像ならフレームのイテレータを返す（静止画・非対応形式は None）
+fn animation_frames(
+    bytes: &[u8],
+    format: Option<ImageFormat>,
+) -> Result<Option<Frames<'_>>, String> {
+    let Some(format) = format else {
+        return Ok(None);
+    };
+    if count_frames(Cursor::new(bytes), format).is_none() {
+        return Ok(None);
+    }
+
+    let cursor = Cursor::new(bytes);
+    let frames = match format {
+        ImageFormat::Gif => GifDecoder::new(cursor).map(|decoder| decoder.into_frames()),
+        ImageFormat::WebP => WebPDecoder::new(cursor).map(|decoder| decoder.into_frames()),
+        ImageFormat::Png => PngDecoder::new(cursor)
+            .and_then(|decoder| decoder.apng())
+            .map(|decoder| decoder.into_frames()),
+        _ => return Ok(None),
+    }
+    .map_err(|e| format!("アニメーションのデコードに失敗しました: {}", e))?;
+
+    Ok(Some(frames))
+}
+
+/// フレームの表示時間をミリ秒で取得
+fn frame_delay_ms(frame: &Frame) -> u32 {
+    let (numer, denom) = frame.delay().numer_denom_ms();
+    numer.checked_div(denom).unwrap_or(0)
+}
+
+/// APNGのフレームの表示時間をミリ秒で取得（分母が 0 の場合は 1/100 秒単位とみなす）
+fn png_delay_ms(frame_control: &png::FrameControl) -> u32 {
+    let denom = match frame_control.delay_den {
+        0 => 100,
+        denom => u32::from(denom),
+    };
+    u32::from(frame_control.delay_num) * 1000 / denom
+}
+
+/// アニメーションWebPの各フレーム（ANMFチャンク）の表示時間をミリ秒で取得
+fn webp_frame_delays(bytes: &[u8]) -> Option<Vec<u32>> {
+    if bytes.get(0..4)? != b"RIFF" || bytes.get(8..12)? != b"WEBP" {
+        return None;
+    }
+    let mut delays = Vec::new();
+    let mut pos = 12;
+    while let Some(header) = bytes.get(pos..pos + 8) {
+        let size = u32::from_le_bytes(header[4..8].try_into().ok()?) as usize;
+        let data = pos + 8;
+        if &header[0..4] == b"ANMF" {
+            // フレームの位置とサイズ（3バイト×4）に続く3バイトが表示時間
+            let duration = bytes.get(data + 12..data + 15)?;
+            delays.push(
+                u32::from(duration[0]) | u32::from(duration[1]) << 8 | u32::from(duration[2]) << 16,
+            );
+        }
+        // チャンクは偶数バイト境界に揃えられる
+        pos = data.checked_add(size)?.checked_add(size & 1)?;
+    }
+    Some(delays)
+}
+
+/// アニメーション画像の各フレームの表示時間をミリ秒で取得（静止画・非対応形式は None）
+///
+/// count_frames と同様にフレームの画素データは展開せず、フレームのヘッダーのみを読む。
+fn frame_delays(bytes: &[u8], format: Option<ImageFormat>) -> Result<Option<Vec<u32>>, String> {
+    let Some(format) = format else {
+        return Ok(None);
+    };
+    if count_frames(Cursor::new(bytes), format).is_none() {
+        return Ok(None);
+    }
+
+    let error = |e: &dyn std::fmt::Display| format!("フレーム情報の読み込みに失敗しました: {}", e);
+    let delays = match format {
+        ImageFormat::Gif => {
+            let mut options = gif::DecodeOptions::new();
+            options.skip_frame_decoding(true);
+            let mut decoder = options
+                .read_info(Cursor::new(bytes))
+                .map_err(|e| error(&e))?;
+            let mut delays = Vec::new();
+            // GIFの表示時間は 1/100 秒単位
+            while let Some(frame) = decoder.next_frame_info().map_err(|e| error(&e))? {
+                delays.push(u32::from(frame.delay) * 10);
+            }
+            delays
+        }
+        ImageFormat::WebP => {
+            webp_frame_delays(bytes).ok_or_else(|| error(&"WebPのチャンクが不正です"))?
+        }
+        ImageFormat::Png => {
+            let mut reader = png::Decoder::new(Cursor::new(bytes))
+                .read_info()
+                .map_err(|e| error(&e))?;
+            let num_frames = reader
+                .info()
+                .animation_control
+                .map_or(0, |control| control.num_frames) as usize;
+            let mut delays = Vec::new();
+            // IDAT の前に fcTL がある場合は IDAT が最初のフレーム
+            if let Some(frame_control) = reader.info().frame_control() {
+                delays.push(png_delay_ms(frame_control));
+            }
+            while delays.len() < num_frames {
+                let frame_control = reader.next_frame_info().map_err(|e| error(&e))?;
+                delays.push(png_delay_ms(frame_control));
+            }
+            delays
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(delays))
+}
+
+/// 指定したフレームを画像として取り出す（0始まり、静止画は 0 のみ有効）
+pub(crate) fn load_frame(image_path: &str, frame_index: u32) -> Result<DynamicImage, String> {
+    let (bytes, format) = read_source(image_path)?;
+    let Some(mut frames) = animation_frames(&bytes, format)? else {
+        if frame_index != 0 {
+            return Err(format!("フレーム番号が範囲外です: {}", frame_index));
+        }
+        return load_image(image_path);
+    };
+
+    let frame = frames
+        .nth(frame_index as usize)
+        .ok_or_else(|| format!("フレーム番号が範囲外です: {}", frame_index))?
+        .map_err(|e| format!("フレームのデコードに失敗しました: {}", e))?;
+    Ok(DynamicImage::ImageRgba8(frame.into_buffer()))
+}
+
+/// アニメーションサムネイル（アニメーションGIF）を生成（静止画は None）
+///
+/// 長いアニメーションは先頭から MAX_THUMBNAIL_FRAMES フレーム・
+/// MAX_THUMBNAIL_DURATION_MS ミリ秒までに切り詰める。
+pub(crate) fn encode_animated_thumbnail(
+    image_path: &str,
+    size: u32,
+) -> Result<Option<Vec<u8>>, String> {
+    let (bytes, format) = read_source(image_path)?;
+    let Some(frames) = animation_frames(&bytes, format)? else {
+        return Ok(None);
+    };
+
+    let mut thumbnail_frames = Vec::new();
+    let mut duration = 0;
+    for frame in frames.take(MAX_THUMBNAIL_FRAMES) {
+        let frame = frame.map_err(|e| format!("フレームのデコードに失敗しました: {}", e))?;
+        if !thumbnail_frames.is_empty() && duration >= MAX_THUMBNAIL_DURATION_MS {
+            break;
+        }
+        duration += frame_delay_ms(&frame);
+        let delay = frame.delay();
+        let resized = DynamicImage::ImageRgba8(frame.into_buffer())
+            .thumbnail(size, size)
+            .to_rgba8();
+        thumbnail_frames.push(Frame::from_parts(resized, 0, 0, delay));
+    }
+
+    let mut output = Vec::new();
+    {
+        let mut encoder = GifEncoder::new_with_speed(&mut output, THUMBNAIL_GIF_SPEED);
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .and_then(|_| encoder.encode_frames(thumbnail_frames))
+            .map_err(|e| format!("サムネイルのエンコードに失敗しました: {}", e))?;
+    }
+
+    Ok(Some(output))
+}
+
+/// アニメーション情報（フレーム数と各フレームの表示時間）を取得
+#[tauri::command]
+pub async fn get_animation_info(image_path: String) -> Result<AnimationInfo, String> {
+    run_blocking(move || {
+        let (bytes, format) = read_source(&image_path)?;
+        let Some(frame_delays) = frame_delays(&bytes, format)? else {
+            return Ok(AnimationInfo {
+                animated: false,
+                frame_count: 1,
+                frame_delays: Vec::new(),
+                total_duration: 0,
+            });
+        };
+
+        Ok(AnimationInfo {
+            animated: true,
+            frame_count: frame_delays.len() as u32,
+            total_duration: frame_delays.iter().sum(),
+            frame_delays,
+        })
+    })
+    .await
+}
+
+/// 指定したフレームを画像ファイルとして保存（形式は保存先の拡張子から決定）
+#[tauri::command]
+pub async fn export_animation_frame(
+    image_path: String,
+    frame_index: u32,
+    output_path: String,
+) -> Result<(), String> {
+    run_blocking(move || {
+        let mut frame = load_frame(&image_path, frame_index)?;
+        // JPEGはアルファチャンネルを保存できないためRGBに変換
+        if ImageFormat::from_path(&output_path).is_ok_and(|format| format == ImageFormat::Jpeg) {
+            frame = DynamicImage::ImageRgb8(frame.to_rgb8());
+        }
+        frame
+            .save(&output_path)
+            .map_err(|e| format!("フレームの保存に失敗しました: {}", e))
+    })
+    .await
+}
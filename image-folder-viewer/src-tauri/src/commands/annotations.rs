@@ -0,0 +1,196 @@
+// 画像ごとの注釈
+//
+// カード内の画像に評価・お気に入り・色ラベル・タグを設定し、それらの条件で
+// カードの画像一覧を絞り込む。注釈はカードIDと、カードのルートフォルダからの
+// 相対パス（ImageFile.relative_path）で画像を識別するため、フォルダを付け替えても保たれる。
+// 編集コマンドは編集中のプロファイルを受け取り、更新後のプロファイルを返す（保存はしない）。
+
+use super::blocking::run_blocking;
+use super::images::{list_images, ImageFile};
+use crate::models::{ColorLabel, ImageAnnotation, ImageAnnotationFilter, ProfileData};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// 評価の最大値
+pub(crate) const MAX_RATING: u8 = 5;
+
+/// 注釈の変更内容（省略した項目は変更しない）
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageAnnotationUpdate {
+    /// 評価（0〜5、0 で未評価）
+    pub rating: Option<u8>,
+    pub favorite: Option<bool>,
+    pub color_label: Option<ColorLabel>,
+    /// 色ラベルを解除する（color_label より優先）
+    #[serde(default)]
+    pub clear_color_label: bool,
+    /// 付けるタグ
+    #[serde(default)]
+    pub add_tag_ids: Vec<String>,
+    /// 外すタグ
+    #[serde(default)]
+    pub remove_tag_ids: Vec<String>,
+}
+
+/// 注釈付きの画像
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotatedImage {
+    pub image: ImageFile,
+    /// 注釈（未設定の場合は None）
+    pub annotation: Option<ImageAnnotation>,
+}
+
+/// 注釈が存在するカード・タグのみを参照しているかを検証（読み込み時に使用）
+pub(crate) fn validate_annotations(profile: &ProfileData) -> Result<(), String> {
+    let card_ids: HashSet<&str> = profile.cards.iter().map(|c| c.id.as_str()).collect();
+    let tag_ids: HashSet<&str> = profile.tags.iter().map(|t| t.id.as_str()).collect();
+
+    for annotation in &profile.image_annotations {
+        if !card_ids.contains(annotation.card_id.as_str()) {
+            return Err(format!(
+                "画像の注釈が存在しないカードを参照しています: {}",
+                annotation.card_id
+            ));
+        }
+        if let Some(tag_id) = annotation
+            .tag_ids
+            .iter()
+            .find(|id| !tag_ids.contains(id.as_str()))
+        {
+            return Err(format!(
+                "画像の注釈が存在しないタグを参照しています: {}",
+                tag_id
+            ));
+        }
+        if annotation.rating > MAX_RATING {
+            return Err(format!(
+                "画像の評価が範囲外です（0〜{}）: {}",
+                MAX_RATING, annotation.relative_path
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// 画像の注釈を一括で変更し、更新後のプロファイルを返す
+///
+/// relative_paths にはカードのルートフォルダからの相対パス（ImageFile.relative_path）を指定する。
+/// 変更によって何も設定されていない状態になった注釈は削除する。
+#[tauri::command]
+pub fn set_image_annotations(
+    mut profile: ProfileData,
+    card_id: String,
+    relative_paths: Vec<String>,
+    update: ImageAnnotationUpdate,
+) -> Result<ProfileData, String> {
+    if !profile.cards.iter().any(|card| card.id == card_id) {
+        return Err(format!("カードが見つかりません: {}", card_id));
+    }
+    if update.rating.is_some_and(|rating| rating > MAX_RATING) {
+        return Err(format!("評価は0〜{}で指定してください", MAX_RATING));
+    }
+    if let Some(tag_id) = update
+        .add_tag_ids
+        .iter()
+        .find(|id| !profile.tags.iter().any(|tag| tag.id == **id))
+    {
+        return Err(format!("タグが見つかりません: {}", tag_id));
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut index: HashMap<String, usize> = profile
+        .image_annotations
+        .iter()
+        .enumerate()
+        .filter(|(_, a)| a.card_id == card_id)
+        .map(|(i, a)| (a.relative_path.clone(), i))
+        .collect();
+
+    for relative_path in relative_paths {
+        let i = *index.entry(relative_path.clone()).or_insert_with(|| {
+            profile.image_annotations.push(ImageAnnotation {
+                card_id: card_id.clone(),
+                relative_path,
+                rating: 0,
+                favorite: false,
+                color_label: None,
+                tag_ids: Vec::new(),
+                updated_at: now.clone(),
+            });
+            profile.image_annotations.len() - 1
+        });
+
+        let annotation = &mut profile.image_annotations[i];
+        if let Some(rating) = update.rating {
+            annotation.rating = rating;
+        }
+        if let Some(favorite) = update.favorite {
+            annotation.favorite = favorite;
+        }
+        if update.clear_color_label {
+            annotation.color_label = None;
+        } else if let Some(color_label) = update.color_label {
+            annotation.color_label = Some(color_label);
+        }
+        annotation
+            .tag_ids
+            .retain(|id| !update.remove_tag_ids.contains(id));
+        for tag_id in &update.add_tag_ids {
+            if !annotation.tag_ids.contains(tag_id) {
+                annotation.tag_ids.push(tag_id.clone());
+            }
+        }
+        annotation.updated_at = now.clone();
+    }
+
+    profile.image_annotations.retain(|a| !a.is_empty());
+    profile.updated_at = now;
+    Ok(profile)
+}
+
+/// カードの画像一覧を注釈付きで取得（カードの走査オプション・ソート方法に従う）
+///
+/// filter を指定した場合は条件を満たす画像のみを返す。
+#[tauri::command]
+pub async fn list_annotated_images(
+    profile: ProfileData,
+    card_id: String,
+    filter: Option<ImageAnnotationFilter>,
+) -> Result<Vec<AnnotatedImage>, String> {
+    let filter = filter.unwrap_or_default();
+    run_blocking(move || annotated_images(&profile, &card_id, &filter)).await
+}
+
+/// カードの画像を走査し、注釈の条件を満たす画像を注釈付きで集める
+fn annotated_images(
+    profile: &ProfileData,
+    card_id: &str,
+    filter: &ImageAnnotationFilter,
+) -> Result<Vec<AnnotatedImage>, String> {
+    let card = profile
+        .cards
+        .iter()
+        .find(|card| card.id == card_id)
+        .ok_or_else(|| format!("カードが見つかりません: {}", card_id))?;
+
+    let annotations: HashMap<&str, &ImageAnnotation> = profile
+        .image_annotations
+        .iter()
+        .filter(|a| a.card_id == card_id)
+        .map(|a| (a.relative_path.as_str(), a))
+        .collect();
+
+    let images = list_images(&card.folder_path, &card.scan_options, card.sort_mode)?;
+    Ok(images
+        .into_iter()
+        .filter_map(|image| {
+            let annotation = annotations.get(image.relative_path.as_str()).copied();
+            filter.matches(annotation).then(|| AnnotatedImage {
+                annotation: annotation.cloned(),
+                image,
+            })
+        })
+        .collect())
+}
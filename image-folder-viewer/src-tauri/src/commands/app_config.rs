@@ -1,6 +1,11 @@
 // アプリ共通設定管理コマンド
 
-use crate::models::{AppConfig, RecentProfile};
+use super::atomic_write::write_atomic;
+use super::thumbnail::apply_thumbnail_config;
+use crate::models::{
+    migrate_app_config, schema_version, version_string, AppConfig, RecentProfile,
+    APP_CONFIG_VERSION,
+};
 use std::fs;
 use std::path::PathBuf;
 use tauri::{AppHandle, Manager};
@@ -24,29 +29,46 @@ fn get_config_path(app: &AppHandle) -> Result<PathBuf, String> {
     Ok(app_data_dir.join(CONFIG_FILE_NAME))
 }
 
-/// アプリ共通設定をファイルから読み込む（内部処理用・setup フックから直接呼び出し可能）
-pub fn load_app_config(app: &AppHandle) -> AppConfig {
+/// アプリ共通設定をファイルから読み込む（古いバージョンは現在のバージョンに移行）
+///
+/// ファイルがない・読み込めない場合は既定の設定を返す。
+/// 新しいバージョンのアプリで保存された設定ファイルの場合のみエラーとする。
+fn read_app_config(app: &AppHandle) -> Result<AppConfig, String> {
     let config_path = match get_config_path(app) {
         Ok(p) => p,
-        Err(_) => return AppConfig::default(),
+        Err(_) => return Ok(AppConfig::default()),
     };
 
     if !config_path.exists() {
-        return AppConfig::default();
+        return Ok(AppConfig::default());
     }
 
     let content = match fs::read_to_string(&config_path) {
         Ok(c) => c,
-        Err(_) => return AppConfig::default(),
+        Err(_) => return Ok(AppConfig::default()),
     };
 
-    serde_json::from_str(&content).unwrap_or_else(|_| AppConfig::default())
+    let value = match serde_json::from_str(&content) {
+        Ok(v) => v,
+        Err(_) => return Ok(AppConfig::default()),
+    };
+    let is_newer = schema_version(&value).is_ok_and(|version| version > APP_CONFIG_VERSION);
+    match migrate_app_config(value) {
+        Ok(value) => Ok(serde_json::from_value(value).unwrap_or_default()),
+        Err(e) if is_newer => Err(e),
+        Err(_) => Ok(AppConfig::default()),
+    }
+}
+
+/// アプリ共通設定をファイルから読み込む（内部処理用・setup フックから直接呼び出し可能）
+pub fn load_app_config(app: &AppHandle) -> AppConfig {
+    read_app_config(app).unwrap_or_default()
 }
 
 /// アプリ共通設定を取得（起動時に呼び出し）
 #[tauri::command]
 pub fn get_app_config(app: AppHandle) -> Result<AppConfig, String> {
-    Ok(load_app_config(&app))
+    read_app_config(&app)
 }
 
 /// アプリ共通設定を保存
@@ -54,14 +76,21 @@ pub fn get_app_config(app: AppHandle) -> Result<AppConfig, String> {
 pub fn save_app_config(app: AppHandle, config: AppConfig) -> Result<(), String> {
     let config_path = get_config_path(&app)?;
 
+    // 新しいバージョンのアプリで保存された設定ファイルは上書きしない
+    read_app_config(&app)?;
+    let mut config = config;
+    config.version = version_string(APP_CONFIG_VERSION);
+
     // JSONに変換（整形あり）
     let content = serde_json::to_string_pretty(&config)
         .map_err(|e| format!("設定の変換に失敗しました: {}", e))?;
 
-    // ファイルに書き込み
-    fs::write(&config_path, content)
+    // 一時ファイル経由で書き込み
+    write_atomic(&config_path, content.as_bytes())
         .map_err(|e| format!("設定ファイルの保存に失敗しました: {}", e))?;
 
+    apply_thumbnail_config(&config);
+
     Ok(())
 }
 
@@ -0,0 +1,561 @@
+// アーカイブ（ZIP/CBZ/TAR/TAR.GZ）内の画像アクセス
+//
+// アーカイブ内の画像は "<アーカイブのパス>!/<エントリ名>" 形式の文字列で表し、
+// 通常のファイルパスと同じように ImageFile.path やサムネイル取得に渡せるようにする。
+// エントリは必要な分だけ読み込み、アーカイブ全体をディスクに展開することはない。
+//
+// TAR はランダムアクセス用の目次を持たないため、初回走査時にエントリの
+// データ位置とサイズをインデックスとしてディスクに保存し、再オープン時に再利用する。
+// TAR.GZ は展開しないとシークできないため、初回走査時に展開したTARもディスクに保存し、
+// エントリごとに先頭から展開し直さずに済むようにする（上限を超えるアーカイブは保存せず、
+// エントリを読むたびに先頭から展開する）。
+
+use flate2::read::GzDecoder;
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager};
+use zip::ZipArchive;
+
+/// アーカイブのパスとエントリ名の区切り文字列
+pub(crate) const MEMBER_SEPARATOR: &str = "!/";
+
+/// TARインデックスのディスクキャッシュのサブディレクトリ名（サムネイルキャッシュと同じ階層）
+const ARCHIVE_INDEX_DIR: &str = "archive_index";
+
+/// アーカイブ内のエントリの最大サイズ（ヘッダーのサイズはアーカイブ側で自由に書けるため、読み込み量を制限する）
+const MAX_MEMBER_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// エントリの読み込み前に確保するバッファの上限
+const MAX_PREALLOC_BYTES: u64 = 64 * 1024 * 1024;
+
+/// TARインデックスの形式のバージョン（項目を変えたら上げ、古いインデックスを作り直させる）
+const TAR_INDEX_VERSION: u32 = 3;
+
+/// 展開したTARをディスクに保存するアーカイブの展開後サイズの上限
+const MAX_UNPACKED_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// 展開したTARのディスクキャッシュ全体の上限（超えた分は古いものから削除する）
+const MAX_UNPACKED_TOTAL_BYTES: u64 = 8 * 1024 * 1024 * 1024;
+
+/// TARインデックスの保存先ディレクトリ（起動時に設定）
+static INDEX_DIR: OnceCell<PathBuf> = OnceCell::new();
+
+/// TARインデックスのメモリキャッシュ（アーカイブのパス → インデックス）
+static TAR_INDEX_CACHE: Lazy<Mutex<HashMap<PathBuf, Arc<TarIndex>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// TARインデックスの作成中のロック（同じアーカイブを並行して走査・展開しないようにする）
+static TAR_INDEX_BUILD: Mutex<()> = Mutex::new(());
+
+/// 対応するアーカイブ形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+/// 拡張子からアーカイブ形式を判定
+pub(crate) fn archive_kind(path: &Path) -> Option<ArchiveKind> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    match ext.as_str() {
+        "zip" | "cbz" => Some(ArchiveKind::Zip),
+        "tar" => Some(ArchiveKind::Tar),
+        "tgz" => Some(ArchiveKind::TarGz),
+        "gz" if name.ends_with(".tar.gz") => Some(ArchiveKind::TarGz),
+        _ => None,
+    }
+}
+
+/// TARインデックスの保存先ディレクトリを設定（setup フックから呼び出す）
+pub fn init_archive_index_dir(app: &AppHandle) {
+    if let Ok(dir) = app.path().app_cache_dir() {
+        let _ = INDEX_DIR.set(dir.join(ARCHIVE_INDEX_DIR));
+    }
+}
+
+/// アーカイブファイルかどうかを判定（存在するファイルのみ true）
+pub(crate) fn is_archive_file(path: &Path) -> bool {
+    archive_kind(path).is_some() && path.is_file()
+}
+
+/// アーカイブ内エントリを表すパス文字列を生成
+pub(crate) fn member_path(archive: &Path, member: &str) -> String {
+    format!(
+        "{}{}{}",
+        archive.to_string_lossy(),
+        MEMBER_SEPARATOR,
+        member
+    )
+}
+
+/// パス文字列をアーカイブのパスとエントリ名に分割（アーカイブ内でなければ None）
+pub(crate) fn split_member_path(path: &str) -> Option<(PathBuf, String)> {
+    path.match_indices(MEMBER_SEPARATOR).find_map(|(idx, _)| {
+        let archive = Path::new(&path[..idx]);
+        if is_archive_file(archive) {
+            let member = &path[idx + MEMBER_SEPARATOR.len()..];
+            Some((archive.to_path_buf(), member.to_string()))
+        } else {
+            None
+        }
+    })
+}
+
+/// ZIPアーカイブを開く
+fn open_zip(archive: &Path) -> Result<ZipArchive<BufReader<File>>, String> {
+    let file = File::open(archive).map_err(|e| format!("アーカイブを開けませんでした: {}", e))?;
+    ZipArchive::new(BufReader::new(file))
+        .map_err(|e| format!("アーカイブの読み込みに失敗しました: {}", e))
+}
+
+/// アーカイブ内のファイルエントリ名を列挙（ディレクトリとmacOSのメタデータは除外）
+pub(crate) fn list_members(archive: &Path) -> Result<Vec<String>, String> {
+    match archive_kind(archive) {
+        Some(ArchiveKind::Zip) => {
+            let zip = open_zip(archive)?;
+            Ok(zip
+                .file_names()
+                .filter(|name| !name.ends_with('/') && !name.starts_with("__MACOSX/"))
+                .map(|name| name.to_string())
+                .collect())
+        }
+        Some(kind @ (ArchiveKind::Tar | ArchiveKind::TarGz)) => {
+            let index = load_tar_index(archive, kind)?;
+            Ok(index
+                .entries
+                .iter()
+                .map(|entry| entry.name.clone())
+                .collect())
+        }
+        None => Err(format!(
+            "対応していないアーカイブ形式です: {}",
+            archive.display()
+        )),
+    }
+}
+
+/// アーカイブ内のエントリの情報（並べ替えに使用）
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MemberInfo {
+    /// 展開後のサイズ
+    pub size: u64,
+    /// 更新日時（アーカイブに記録されていない場合は None）
+    pub modified: Option<SystemTime>,
+}
+
+/// ZIPの日時を SystemTime に変換
+///
+/// ZIPの日時はタイムゾーンを持たないため UTC とみなす（同じアーカイブ内の比較にのみ使う）。
+fn zip_time(time: zip::DateTime) -> Option<SystemTime> {
+    chrono::NaiveDate::from_ymd_opt(time.year() as i32, time.month() as u32, time.day() as u32)?
+        .and_hms_opt(
+            time.hour() as u32,
+            time.minute() as u32,
+            time.second() as u32,
+        )
+        .map(|time| SystemTime::from(time.and_utc()))
+}
+
+/// アーカイブ内のファイルエントリの情報を取得（エントリ名 → 情報）
+///
+/// ZIPはセントラルディレクトリの、TARはインデックスの値を使い、エントリは展開しない。
+pub(crate) fn member_infos(archive: &Path) -> Result<HashMap<String, MemberInfo>, String> {
+    match archive_kind(archive) {
+        Some(ArchiveKind::Zip) => {
+            let mut zip = open_zip(archive)?;
+            let mut infos = HashMap::new();
+            for i in 0..zip.len() {
+                let Ok(entry) = zip.by_index_raw(i) else {
+                    continue;
+                };
+                if entry.is_dir() {
+                    continue;
+                }
+                infos.insert(
+                    entry.name().to_string(),
+                    MemberInfo {
+                        size: entry.size(),
+                        modified: entry.last_modified().and_then(zip_time),
+                    },
+                );
+            }
+            Ok(infos)
+        }
+        Some(kind @ (ArchiveKind::Tar | ArchiveKind::TarGz)) => {
+            let index = load_tar_index(archive, kind)?;
+            Ok(index
+                .entries
+                .iter()
+                .map(|entry| {
+                    let info = MemberInfo {
+                        size: entry.size,
+                        modified: entry
+                            .modified
+                            .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+                    };
+                    (entry.name.clone(), info)
+                })
+                .collect())
+        }
+        None => Err(format!(
+            "対応していないアーカイブ形式です: {}",
+            archive.display()
+        )),
+    }
+}
+
+/// エントリのデータを読み込む（size はヘッダーのサイズで、バッファの確保量の目安にのみ使う）
+///
+/// MAX_MEMBER_BYTES を超えるエントリはエラーとする。
+fn read_limited(reader: impl Read, size: u64) -> io::Result<Vec<u8>> {
+    let too_large = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "ファイルが大きすぎます（上限 {} MB）",
+                MAX_MEMBER_BYTES / 1024 / 1024
+            ),
+        )
+    };
+    if size > MAX_MEMBER_BYTES {
+        return Err(too_large());
+    }
+
+    let mut bytes = Vec::with_capacity(size.min(MAX_PREALLOC_BYTES) as usize);
+    reader.take(MAX_MEMBER_BYTES + 1).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > MAX_MEMBER_BYTES {
+        return Err(too_large());
+    }
+    Ok(bytes)
+}
+
+/// アーカイブ内のエントリを読み込む
+pub(crate) fn read_member(archive: &Path, member: &str) -> Result<Vec<u8>, String> {
+    match archive_kind(archive) {
+        Some(ArchiveKind::Zip) => {
+            let mut zip = open_zip(archive)?;
+            let mut entry = zip.by_name(member).map_err(|e| {
+                format!("アーカイブ内のファイルが見つかりません: {} ({})", member, e)
+            })?;
+            let size = entry.size();
+            read_limited(&mut entry, size)
+                .map_err(|e| format!("アーカイブ内のファイルの読み込みに失敗しました: {}", e))
+        }
+        Some(kind @ (ArchiveKind::Tar | ArchiveKind::TarGz)) => {
+            let index = load_tar_index(archive, kind)?;
+            let entry = index
+                .entry(member)
+                .ok_or_else(|| format!("アーカイブ内のファイルが見つかりません: {}", member))?;
+            read_tar_entry(archive, &index, entry)
+                .map_err(|e| format!("アーカイブ内のファイルの読み込みに失敗しました: {}", e))
+        }
+        None => Err(format!(
+            "対応していないアーカイブ形式です: {}",
+            archive.display()
+        )),
+    }
+}
+
+/// TARエントリの位置情報
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TarIndexEntry {
+    /// エントリ名（先頭の "./" は除去済み）
+    name: String,
+    /// データ部の開始位置（TAR.GZ の場合は展開後のストリーム上の位置）
+    offset: u64,
+    /// データ部のサイズ
+    size: u64,
+    /// 更新日時（UNIX秒、ヘッダーに記録されていない場合は None）
+    modified: Option<u64>,
+}
+
+/// TARアーカイブのインデックス（形式のバージョンと、作成時のアーカイブのサイズと更新日時で有効性を判定）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TarIndex {
+    version: u32,
+    archive_size: u64,
+    archive_modified: u64,
+    /// 展開したTARのディスクキャッシュのサイズ（TAR.GZ で保存した場合のみ）
+    unpacked_size: Option<u64>,
+    /// エントリ（アーカイブ内の順序）
+    entries: Vec<TarIndexEntry>,
+    /// エントリ名 → entries の位置（読み込み時に作成）
+    #[serde(skip)]
+    positions: HashMap<String, usize>,
+}
+
+impl TarIndex {
+    /// エントリ名の検索表を作成
+    fn with_positions(mut self) -> Self {
+        self.positions = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (entry.name.clone(), i))
+            .collect();
+        self
+    }
+
+    /// エントリ名からエントリを取得
+    fn entry(&self, name: &str) -> Option<&TarIndexEntry> {
+        self.positions.get(name).map(|&i| &self.entries[i])
+    }
+}
+
+/// アーカイブのサイズと更新日時（UNIX秒）を取得
+fn archive_stamp(archive: &Path) -> Result<(u64, u64), String> {
+    let meta = fs::metadata(archive).map_err(|e| format!("アーカイブを開けませんでした: {}", e))?;
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    Ok((meta.len(), modified))
+}
+
+/// インデックスのディスクキャッシュのパスを計算（アーカイブパスのSHA-256ハッシュ）
+fn index_cache_path(archive: &Path) -> Option<PathBuf> {
+    let dir = INDEX_DIR.get()?;
+    let mut hasher = Sha256::new();
+    hasher.update(archive.to_string_lossy().as_bytes());
+    Some(dir.join(format!("{:x}.json", hasher.finalize())))
+}
+
+/// TAR.GZ を展開したTARのディスクキャッシュのパスを計算
+fn unpacked_cache_path(archive: &Path) -> Option<PathBuf> {
+    index_cache_path(archive).map(|path| path.with_extension("tar"))
+}
+
+/// TARインデックスを取得（メモリ → ディスク → アーカイブ走査の順に探す）
+fn load_tar_index(archive: &Path, kind: ArchiveKind) -> Result<Arc<TarIndex>, String> {
+    let (size, modified) = archive_stamp(archive)?;
+    let is_current = |index: &TarIndex| {
+        index.version == TAR_INDEX_VERSION
+            && index.archive_size == size
+            && index.archive_modified == modified
+    };
+    let cached = || {
+        TAR_INDEX_CACHE.lock().ok().and_then(|cache| {
+            cache
+                .get(archive)
+                .filter(|index| is_current(index))
+                .cloned()
+        })
+    };
+
+    if let Some(index) = cached() {
+        return Ok(index);
+    }
+    let _build = TAR_INDEX_BUILD.lock();
+    // 待っている間に他のスレッドが作成した場合はそれを使う
+    if let Some(index) = cached() {
+        return Ok(index);
+    }
+
+    let cache_path = index_cache_path(archive);
+    let from_disk = cache_path
+        .as_ref()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<TarIndex>(&content).ok())
+        .filter(|index| is_current(index));
+
+    let index = match from_disk {
+        Some(index) => index,
+        None => {
+            let (entries, unpacked_size) = build_tar_entries(archive, kind)
+                .map_err(|e| format!("アーカイブの読み込みに失敗しました: {}", e))?;
+            let index = TarIndex {
+                version: TAR_INDEX_VERSION,
+                archive_size: size,
+                archive_modified: modified,
+                unpacked_size,
+                entries,
+                positions: HashMap::new(),
+            };
+            // ディスクへの保存失敗は無視（次回は再走査になるだけ）
+            if let Some(path) = &cache_path {
+                if let Some(dir) = path.parent() {
+                    let _ = fs::create_dir_all(dir);
+                }
+                if let Ok(content) = serde_json::to_string(&index) {
+                    let _ = fs::write(path, content);
+                }
+            }
+            index
+        }
+    };
+
+    let index = Arc::new(index.with_positions());
+    if let Ok(mut cache) = TAR_INDEX_CACHE.lock() {
+        cache.insert(archive.to_path_buf(), Arc::clone(&index));
+    }
+
+    Ok(index)
+}
+
+/// TARアーカイブを先頭から走査してエントリの位置情報を収集
+///
+/// TAR.GZ は展開したTARをディスクキャッシュに保存できた場合、そのサイズも返す。
+fn build_tar_entries(
+    archive: &Path,
+    kind: ArchiveKind,
+) -> io::Result<(Vec<TarIndexEntry>, Option<u64>)> {
+    let file = BufReader::new(File::open(archive)?);
+    if kind != ArchiveKind::TarGz {
+        return Ok((collect_tar_entries(file)?, None));
+    }
+
+    // 展開したTARを保存できなかった場合は、展開しながら走査する
+    if let Some(unpacked_path) = unpacked_cache_path(archive) {
+        if let Some(unpacked_size) = unpack_tar_gz(file, &unpacked_path)? {
+            let unpacked = BufReader::new(File::open(&unpacked_path)?);
+            return Ok((collect_tar_entries(unpacked)?, Some(unpacked_size)));
+        }
+    }
+    let file = BufReader::new(File::open(archive)?);
+    Ok((collect_tar_entries(GzDecoder::new(file))?, None))
+}
+
+/// TAR.GZ を展開してディスクキャッシュに保存し、展開後のサイズを返す
+///
+/// 展開後のサイズが MAX_UNPACKED_BYTES を超える場合は保存せず None を返す。
+fn unpack_tar_gz(reader: impl Read, dest: &Path) -> io::Result<Option<u64>> {
+    // 古いキャッシュが残らないよう、先に削除しておく
+    let _ = fs::remove_file(dest);
+    let Some(dir) = dest.parent() else {
+        return Ok(None);
+    };
+    fs::create_dir_all(dir)?;
+
+    let temp_path = dest.with_extension("tar.tmp");
+    let mut output = BufWriter::new(File::create(&temp_path)?);
+    let mut decoder = GzDecoder::new(reader).take(MAX_UNPACKED_BYTES + 1);
+    let written = io::copy(&mut decoder, &mut output).and_then(|written| {
+        output.flush()?;
+        Ok(written)
+    });
+    drop(output);
+    match written {
+        Ok(written) if written <= MAX_UNPACKED_BYTES => {
+            prune_unpacked_cache(dir, written);
+            fs::rename(&temp_path, dest)?;
+            Ok(Some(written))
+        }
+        Ok(_) => {
+            let _ = fs::remove_file(&temp_path);
+            Ok(None)
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&temp_path);
+            Err(e)
+        }
+    }
+}
+
+/// 展開したTARのディスクキャッシュを、追加する分を含めて上限に収まるよう古いものから削除
+fn prune_unpacked_cache(dir: &Path, incoming: u64) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut files: Vec<(SystemTime, u64, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "tar"))
+        .filter_map(|path| {
+            let meta = fs::metadata(&path).ok()?;
+            let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            Some((modified, meta.len(), path))
+        })
+        .collect();
+    files.sort();
+
+    let mut total = files.iter().map(|(_, size, _)| size).sum::<u64>() + incoming;
+    for (_, size, path) in files {
+        if total <= MAX_UNPACKED_TOTAL_BYTES {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            total -= size;
+        }
+    }
+}
+
+/// TARストリームから通常ファイルのエントリを収集
+fn collect_tar_entries<R: Read>(reader: R) -> io::Result<Vec<TarIndexEntry>> {
+    let mut tar = tar::Archive::new(reader);
+    let mut entries = Vec::new();
+
+    for entry in tar.entries()? {
+        let entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?;
+        let name = path.to_string_lossy();
+        let name = name.strip_prefix("./").unwrap_or(&name).to_string();
+        if name.starts_with("__MACOSX/") {
+            continue;
+        }
+        entries.push(TarIndexEntry {
+            name,
+            offset: entry.raw_file_position(),
+            size: entry.size(),
+            modified: entry.header().mtime().ok().filter(|mtime| *mtime > 0),
+        });
+    }
+
+    Ok(entries)
+}
+
+/// インデックスの位置情報を使ってTARエントリのデータを読み込む
+///
+/// 非圧縮TARと、展開したTARを保存済みの TAR.GZ はシークで直接読み込む。
+/// 保存していない TAR.GZ はエントリの位置まで先頭から展開しながら読み飛ばすため、
+/// エントリ数に比例して時間がかかる。
+fn read_tar_entry(archive: &Path, index: &TarIndex, entry: &TarIndexEntry) -> io::Result<Vec<u8>> {
+    let unpacked = index.unpacked_size.and_then(|unpacked_size| {
+        let path = unpacked_cache_path(archive)?;
+        let file = File::open(path).ok()?;
+        let is_current = file
+            .metadata()
+            .is_ok_and(|meta| meta.len() == unpacked_size);
+        is_current.then_some(file)
+    });
+
+    let bytes = match unpacked {
+        Some(mut file) => {
+            file.seek(SeekFrom::Start(entry.offset))?;
+            read_limited(file.take(entry.size), entry.size)?
+        }
+        None if archive_kind(archive) == Some(ArchiveKind::TarGz) => {
+            let file = File::open(archive)?;
+            let mut decoder = GzDecoder::new(BufReader::new(file));
+            io::copy(&mut (&mut decoder).take(entry.offset), &mut io::sink())?;
+            read_limited(decoder.take(entry.size), entry.size)?
+        }
+        None => {
+            let mut file = File::open(archive)?;
+            file.seek(SeekFrom::Start(entry.offset))?;
+            read_limited(file.take(entry.size), entry.size)?
+        }
+    };
+
+    if (bytes.len() as u64) < entry.size {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "アーカイブが途中で終了しています",
+        ));
+    }
+
+    Ok(bytes)
+}
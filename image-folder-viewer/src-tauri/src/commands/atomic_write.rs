@@ -0,0 +1,49 @@
+// ファイルの安全な書き込み
+//
+// 同じフォルダの一時ファイルに書き込んでディスクに同期してから置き換えるため、
+// 書き込み中にクラッシュしたりディスクが一杯になったりしても元のファイルは壊れない。
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 一時ファイル名の連番（同じファイルへの同時書き込みで一時ファイルが衝突しないようにする）
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// 書き込み先と同じフォルダの一時ファイルのパスを生成
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(
+        ".{}.{}-{}.tmp",
+        name,
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// ファイルを一時ファイル経由で書き込み、置き換える
+pub(crate) fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+    let tmp_path = temp_path(path);
+    let result = (|| {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result?;
+
+    // 置き換え（ディレクトリエントリの変更）をディスクに同期
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        let _ = File::open(dir).and_then(|dir| dir.sync_all());
+    }
+
+    Ok(())
+}
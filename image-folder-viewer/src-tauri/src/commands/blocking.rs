@@ -0,0 +1,15 @@
+// 非同期コマンドからのブロッキング処理の実行
+//
+// フォルダの走査やアーカイブの読み込みは時間がかかるため、async コマンドでは
+// 非同期ランタイムのスレッドを塞がないよう、ブロッキング処理用のスレッドで実行する。
+
+/// ブロッキング処理を専用のスレッドで実行し、結果を待つ
+pub(crate) async fn run_blocking<T, F>(f: F) -> Result<T, String>
+where
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+    T: Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("バックグラウンド処理に失敗しました: {}", e))?
+}
@@ -0,0 +1,148 @@
+// カード検証コマンド
+//
+// カードのフォルダの状態（存在・種類・権限・画像数）とサムネイル画像の有無を調べ、
+// 無効なカードにはその理由を返す。
+
+use super::archive;
+use super::blocking::run_blocking;
+use super::images::{count_images, ImageChecker};
+use crate::models::{Card, CardStatus, CardWithStatus};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::thread;
+
+/// 一括検証のワーカースレッド数の上限
+const MAX_WORKERS: usize = 8;
+
+/// 状態ごとのエラーメッセージ
+fn status_message(status: CardStatus) -> Option<&'static str> {
+    match status {
+        CardStatus::Ok => None,
+        CardStatus::Missing => Some("フォルダが見つかりません"),
+        CardStatus::NotADirectory => Some("フォルダではありません"),
+        CardStatus::PermissionDenied => Some("フォルダへのアクセス権限がありません"),
+        CardStatus::NoImages => Some("フォルダ内に画像がありません"),
+        CardStatus::Unreadable => Some("フォルダの読み込みに失敗しました"),
+    }
+}
+
+/// I/Oエラーを状態に変換
+fn status_from_error(error: &std::io::Error) -> CardStatus {
+    match error.kind() {
+        ErrorKind::NotFound => CardStatus::Missing,
+        ErrorKind::PermissionDenied => CardStatus::PermissionDenied,
+        _ => CardStatus::Unreadable,
+    }
+}
+
+/// フォルダ（またはアーカイブファイル）を開けるかを確認（画像の走査は行わない）
+fn folder_status(folder_path: &Path) -> CardStatus {
+    let meta = match fs::metadata(folder_path) {
+        Ok(meta) => meta,
+        Err(e) => return status_from_error(&e),
+    };
+
+    if meta.is_dir() {
+        return match fs::read_dir(folder_path) {
+            Ok(_) => CardStatus::Ok,
+            Err(e) => status_from_error(&e),
+        };
+    }
+
+    if !archive::is_archive_file(folder_path) {
+        return CardStatus::NotADirectory;
+    }
+    match fs::File::open(folder_path) {
+        Ok(_) => CardStatus::Ok,
+        Err(e) => status_from_error(&e),
+    }
+}
+
+/// 検証結果を組み立てる
+fn with_status(
+    card: &Card,
+    status: CardStatus,
+    detail: Option<String>,
+    image_count: Option<u32>,
+    checker: &mut ImageChecker,
+) -> CardWithStatus {
+    let error_message = status_message(status).map(|message| match detail {
+        Some(detail) => format!("{}: {}", message, detail),
+        None => message.to_string(),
+    });
+    CardWithStatus {
+        card: card.clone(),
+        is_valid: status == CardStatus::Ok,
+        error_message,
+        status,
+        image_count,
+        thumbnail_missing: card
+            .thumbnail
+            .as_ref()
+            .is_some_and(|thumbnail| !checker.exists(thumbnail)),
+    }
+}
+
+/// カードのフォルダを開けるかを簡易検証（画像数は数えない、フォルダ監視用）
+pub(crate) fn card_status(card: &Card, checker: &mut ImageChecker) -> CardWithStatus {
+    let status = folder_status(Path::new(&card.folder_path));
+    with_status(card, status, None, None, checker)
+}
+
+/// カードを詳細に検証（画像数を数え、画像がない場合も無効とする）
+fn inspect_card(card: &Card, checker: &mut ImageChecker) -> CardWithStatus {
+    let status = folder_status(Path::new(&card.folder_path));
+    if status != CardStatus::Ok {
+        return with_status(card, status, None, None, checker);
+    }
+
+    match count_images(&card.folder_path, &card.scan_options) {
+        Ok(0) => with_status(card, CardStatus::NoImages, None, Some(0), checker),
+        Ok(count) => with_status(card, CardStatus::Ok, None, Some(count as u32), checker),
+        Err(e) => with_status(card, CardStatus::Unreadable, Some(e), None, checker),
+    }
+}
+
+/// プロファイルの全カードを並列に検証（結果は cards と同じ順序）
+#[tauri::command]
+pub async fn validate_profile_cards(cards: Vec<Card>) -> Result<Vec<CardWithStatus>, String> {
+    run_blocking(move || inspect_cards(&cards)).await
+}
+
+/// カードを複数のスレッドで分担して検証
+fn inspect_cards(cards: &[Card]) -> Result<Vec<CardWithStatus>, String> {
+    if cards.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let workers = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .clamp(1, MAX_WORKERS);
+    let chunk_size = cards.len().div_ceil(workers);
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = cards
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let mut checker = ImageChecker::default();
+                    chunk
+                        .iter()
+                        .map(|card| inspect_card(card, &mut checker))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .map_err(|_| "カードの検証中にエラーが発生しました".to_string())
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(|chunks| chunks.into_iter().flatten().collect())
+    })
+}
@@ -3,10 +3,11 @@
 // Linux/X11ではClipboardインスタンスがドロップされるとクリップボードの内容が
 // 失われるため、グローバルなインスタンスをアプリケーションのライフタイム全体で保持する。
 
+use super::animation::load_frame;
+use super::images::{load_image, source_file_path};
 use arboard::Clipboard;
-use image::ImageReader;
+use image::DynamicImage;
 use once_cell::sync::Lazy;
-use std::path::Path;
 use std::sync::Mutex;
 
 /// グローバルClipboardインスタンス（Linux/X11対策）
@@ -17,18 +18,30 @@ static CLIPBOARD: Lazy<Mutex<Clipboard>> = Lazy::new(|| {
 /// 画像をクリップボードにコピー
 #[tauri::command]
 pub fn copy_image_to_clipboard(image_path: String) -> Result<(), String> {
-    let path = Path::new(&image_path);
+    if !source_file_path(&image_path).exists() {
+        return Err(format!("画像ファイルが見つかりません: {}", image_path));
+    }
+
+    // 画像を読み込み（アーカイブ内の画像にも対応、EXIFの向きを適用）
+    let img = load_image(&image_path)?;
+
+    set_clipboard_image(&img)
+}
 
-    if !path.exists() {
+/// アニメーション画像の指定したフレームをクリップボードにコピー
+#[tauri::command]
+pub fn copy_animation_frame_to_clipboard(image_path: String, frame_index: u32) -> Result<(), String> {
+    if !source_file_path(&image_path).exists() {
         return Err(format!("画像ファイルが見つかりません: {}", image_path));
     }
 
-    // 画像を読み込み
-    let img = ImageReader::open(path)
-        .map_err(|e| format!("画像の読み込みに失敗しました: {}", e))?
-        .decode()
-        .map_err(|e| format!("画像のデコードに失敗しました: {}", e))?;
+    let img = load_frame(&image_path, frame_index)?;
+
+    set_clipboard_image(&img)
+}
 
+/// 画像をクリップボードに設定
+fn set_clipboard_image(img: &DynamicImage) -> Result<(), String> {
     // RGBAに変換
     let rgba = img.to_rgba8();
     let (width, height) = rgba.dimensions();
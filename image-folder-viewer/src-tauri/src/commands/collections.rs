@@ -0,0 +1,279 @@
+// スマートコレクション
+//
+// 保存済みの検索条件に一致する画像を全カードから集め、フォルダのカードと同じ
+// ImageFile の一覧として返す（ImageFile.card_id に画像のカードを設定する）。
+// 見つからないカード・読み込めないカードは飛ばし、存在しないカード・タグを
+// 参照する条件は一致しないものとして扱う。
+// 編集コマンドは検索条件を検証したうえで編集中のプロファイルを受け取り、
+// 更新後のプロファイルを返す（保存はしない）。
+
+use super::annotations::MAX_RATING;
+use super::blocking::run_blocking;
+use super::images::{list_images, sort_image_files, source_file_path, ImageFile};
+use super::tags::format_ids;
+use crate::models::{ImageAnnotation, ProfileData, SmartCollection, SmartQuery};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::time::SystemTime;
+
+/// 画像の更新日時の下限を取得（両方指定された場合は新しい方）
+fn modified_since(query: &SmartQuery) -> Result<Option<SystemTime>, String> {
+    let after = query
+        .modified_after
+        .as_deref()
+        .map(|after| {
+            DateTime::parse_from_rfc3339(after)
+                .map(|time| time.with_timezone(&Utc))
+                .map_err(|e| format!("更新日時の形式が不正です: {}", e))
+        })
+        .transpose()?;
+    let within = query
+        .modified_within_days
+        .map(|days| Utc::now() - Duration::days(days as i64));
+    Ok(after.into_iter().chain(within).max().map(SystemTime::from))
+}
+
+/// コレクションを取得
+fn find_collection<'a>(
+    profile: &'a mut ProfileData,
+    collection_id: &str,
+) -> Result<&'a mut SmartCollection, String> {
+    profile
+        .smart_collections
+        .iter_mut()
+        .find(|collection| collection.id == collection_id)
+        .ok_or_else(|| format!("コレクションが見つかりません: {}", collection_id))
+}
+
+/// コレクション名を検証して前後の空白を除く
+fn validate_title(title: &str) -> Result<String, String> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err("コレクション名を入力してください".to_string());
+    }
+    Ok(title.to_string())
+}
+
+/// 検索条件が存在するカード・タグのみを参照し、値が範囲内であるかを検証
+fn validate_query(profile: &ProfileData, query: &SmartQuery) -> Result<(), String> {
+    let card_ids: HashSet<&str> = profile.cards.iter().map(|c| c.id.as_str()).collect();
+    let missing_cards: Vec<&str> = query
+        .card_ids
+        .iter()
+        .flatten()
+        .map(String::as_str)
+        .filter(|id| !card_ids.contains(id))
+        .collect();
+    if !missing_cards.is_empty() {
+        return Err(format!(
+            "存在しないカードが指定されています: {}",
+            format_ids(missing_cards)
+        ));
+    }
+
+    let tag_ids: HashSet<&str> = profile.tags.iter().map(|t| t.id.as_str()).collect();
+    let mut referenced: Vec<&str> = Vec::new();
+    for expression in [&query.card_tags, &query.annotations.tags]
+        .into_iter()
+        .flatten()
+    {
+        expression.collect_tag_ids(&mut referenced);
+    }
+    let mut missing_tags: Vec<&str> = Vec::new();
+    for id in referenced {
+        if !tag_ids.contains(id) && !missing_tags.contains(&id) {
+            missing_tags.push(id);
+        }
+    }
+    if !missing_tags.is_empty() {
+        return Err(format!(
+            "存在しないタグが指定されています: {}",
+            format_ids(missing_tags)
+        ));
+    }
+
+    if query
+        .annotations
+        .min_rating
+        .is_some_and(|rating| rating > MAX_RATING)
+    {
+        return Err(format!("評価は0〜{}で指定してください", MAX_RATING));
+    }
+    if query.limit == Some(0) {
+        return Err("最大件数は1以上で指定してください".to_string());
+    }
+    modified_since(query)?;
+    Ok(())
+}
+
+/// 更新日時を現在時刻にする
+fn touch(mut profile: ProfileData, collection_id: &str) -> ProfileData {
+    let now = chrono::Utc::now().to_rfc3339();
+    if let Some(collection) = profile
+        .smart_collections
+        .iter_mut()
+        .find(|collection| collection.id == collection_id)
+    {
+        collection.updated_at = now.clone();
+    }
+    profile.updated_at = now;
+    profile
+}
+
+/// 検索条件に一致する画像を全カードから集める
+fn query_images(profile: &ProfileData, query: &SmartQuery) -> Result<Vec<ImageFile>, String> {
+    let since = modified_since(query)?;
+
+    let mut cards: Vec<_> = profile
+        .cards
+        .iter()
+        .filter(|card| {
+            query
+                .card_ids
+                .as_ref()
+                .is_none_or(|ids| ids.contains(&card.id))
+        })
+        .filter(|card| {
+            query.card_tags.as_ref().is_none_or(|expression| {
+                let tags: HashSet<&str> = profile
+                    .card_tags
+                    .iter()
+                    .filter(|ct| ct.card_id == card.id)
+                    .map(|ct| ct.tag_id.as_str())
+                    .collect();
+                expression.matches(&tags)
+            })
+        })
+        .collect();
+    cards.sort_by_key(|card| card.sort_order);
+
+    let annotations: HashMap<(&str, &str), &ImageAnnotation> = profile
+        .image_annotations
+        .iter()
+        .map(|a| ((a.card_id.as_str(), a.relative_path.as_str()), a))
+        .collect();
+
+    let mut images = Vec::new();
+    for card in cards {
+        let Ok(card_images) = list_images(&card.folder_path, &card.scan_options, card.sort_mode)
+        else {
+            continue;
+        };
+        for mut image in card_images {
+            let annotation = annotations
+                .get(&(card.id.as_str(), image.relative_path.as_str()))
+                .copied();
+            if !query.annotations.matches(annotation) {
+                continue;
+            }
+            if let Some(since) = since {
+                // アーカイブ内の画像はアーカイブファイルの更新日時で判定
+                let modified = fs::metadata(source_file_path(&image.path))
+                    .and_then(|m| m.modified())
+                    .ok();
+                if modified.is_none_or(|modified| modified < since) {
+                    continue;
+                }
+            }
+            image.card_id = Some(card.id.clone());
+            images.push(image);
+        }
+    }
+
+    if let Some(sort) = query.sort {
+        images = sort_image_files(images, sort);
+    }
+    if let Some(limit) = query.limit {
+        images.truncate(limit as usize);
+    }
+    Ok(images)
+}
+
+/// スマートコレクションを作成し、更新後のプロファイルを返す（作成したコレクションは末尾に追加される）
+#[tauri::command]
+pub fn create_smart_collection(
+    mut profile: ProfileData,
+    title: String,
+    query: SmartQuery,
+) -> Result<ProfileData, String> {
+    let title = validate_title(&title)?;
+    validate_query(&profile, &query)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let sort_order = profile
+        .smart_collections
+        .iter()
+        .map(|collection| collection.sort_order + 1)
+        .max()
+        .unwrap_or(0);
+    profile.smart_collections.push(SmartCollection {
+        id: uuid::Uuid::new_v4().to_string(),
+        title,
+        query,
+        sort_order,
+        created_at: now.clone(),
+        updated_at: now.clone(),
+    });
+    profile.updated_at = now;
+    Ok(profile)
+}
+
+/// スマートコレクションの名前・検索条件を変更し、更新後のプロファイルを返す（省略した項目は変更しない）
+#[tauri::command]
+pub fn update_smart_collection(
+    mut profile: ProfileData,
+    collection_id: String,
+    title: Option<String>,
+    query: Option<SmartQuery>,
+) -> Result<ProfileData, String> {
+    let title = title.as_deref().map(validate_title).transpose()?;
+    if let Some(query) = &query {
+        validate_query(&profile, query)?;
+    }
+    let collection = find_collection(&mut profile, &collection_id)?;
+    if let Some(title) = title {
+        collection.title = title;
+    }
+    if let Some(query) = query {
+        collection.query = query;
+    }
+    Ok(touch(profile, &collection_id))
+}
+
+/// スマートコレクションを削除し、更新後のプロファイルを返す
+#[tauri::command]
+pub fn delete_smart_collection(
+    mut profile: ProfileData,
+    collection_id: String,
+) -> Result<ProfileData, String> {
+    find_collection(&mut profile, &collection_id)?;
+    profile
+        .smart_collections
+        .retain(|collection| collection.id != collection_id);
+    profile.updated_at = chrono::Utc::now().to_rfc3339();
+    Ok(profile)
+}
+
+/// スマートコレクションの画像一覧を取得
+#[tauri::command]
+pub async fn list_smart_collection_images(
+    profile: ProfileData,
+    collection_id: String,
+) -> Result<Vec<ImageFile>, String> {
+    let query = profile
+        .smart_collections
+        .iter()
+        .find(|collection| collection.id == collection_id)
+        .map(|collection| collection.query.clone())
+        .ok_or_else(|| format!("コレクションが見つかりません: {}", collection_id))?;
+    run_blocking(move || query_images(&profile, &query)).await
+}
+
+/// 保存前の検索条件に一致する画像一覧を取得（コレクションの編集中のプレビュー用）
+#[tauri::command]
+pub async fn preview_smart_query(
+    profile: ProfileData,
+    query: SmartQuery,
+) -> Result<Vec<ImageFile>, String> {
+    run_blocking(move || query_images(&profile, &query)).await
+}
@@ -0,0 +1,252 @@
+// 画像ファイルの内容キー（パス → 内容キーのインデックス）
+//
+// 内容キーはファイルサイズと先頭・末尾の一部のハッシュから計算し、パスには依存しない。
+// フォルダの移動・リネーム後も同じ内容キーになるため、サムネイルキャッシュを再利用できる。
+// 計算結果はパス・サイズ・更新日時とともにインデックスに記録し、サイズと更新日時が
+// 変わらない限り再計算しない（更新日時だけが変わった場合は再計算するが、キーは変わらない）。
+//
+// インデックスは1行1エントリの JSON Lines 形式で追記し、行数が増えたら書き直す。
+
+use super::archive;
+use super::images::source_file_path;
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::{AppHandle, Manager};
+
+/// インデックスのファイル名（app_cache_dir 直下）
+const CONTENT_INDEX_FILE: &str = "content_index.jsonl";
+
+/// 内容キーの計算に使う先頭・末尾のバイト数
+const SAMPLE_BYTES: u64 = 64 * 1024;
+
+/// 書き直しを行う重複行数の下限
+const COMPACT_THRESHOLD: usize = 1000;
+
+/// インデックスファイルのパス（起動時に設定）
+static INDEX_FILE: OnceCell<PathBuf> = OnceCell::new();
+
+/// インデックス（None は未読み込み）
+static CONTENT_INDEX: Lazy<Mutex<Option<ContentIndex>>> = Lazy::new(|| Mutex::new(None));
+
+/// インデックスのエントリ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    path: String,
+    size: u64,
+    /// 更新日時（UNIXエポックからのナノ秒）
+    modified: u64,
+    key: String,
+}
+
+/// パス → 内容キーのインデックス
+struct ContentIndex {
+    entries: HashMap<String, IndexEntry>,
+    /// ファイルの行数（書き直しの判定用）
+    lines: usize,
+}
+
+/// インデックスの保存先を設定（setup フックから呼び出す）
+pub fn init_content_index(app: &AppHandle) {
+    if let Ok(dir) = app.path().app_cache_dir() {
+        let _ = INDEX_FILE.set(dir.join(CONTENT_INDEX_FILE));
+    }
+}
+
+/// インデックスファイルを読み込む（後の行が前の行を上書きする）
+fn read_index() -> ContentIndex {
+    let mut index = ContentIndex {
+        entries: HashMap::new(),
+        lines: 0,
+    };
+    let Some(file) = INDEX_FILE.get().and_then(|path| File::open(path).ok()) else {
+        return index;
+    };
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        index.lines += 1;
+        if let Ok(entry) = serde_json::from_str::<IndexEntry>(&line) {
+            index.entries.insert(entry.path.clone(), entry);
+        }
+    }
+    index
+}
+
+/// インデックスファイルに1行追記
+fn append_entry(entry: &IndexEntry) -> io::Result<()> {
+    let Some(path) = INDEX_FILE.get() else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)
+}
+
+/// インデックスファイルを現在のエントリで書き直す
+fn rewrite_index(index: &mut ContentIndex) -> io::Result<()> {
+    let Some(path) = INDEX_FILE.get() else {
+        return Ok(());
+    };
+    let mut content = String::new();
+    for entry in index.entries.values() {
+        content.push_str(&serde_json::to_string(entry)?);
+        content.push('\n');
+    }
+    let tmp_path = path.with_extension("jsonl.tmp");
+    fs::write(&tmp_path, content)?;
+    fs::rename(&tmp_path, path)?;
+    index.lines = index.entries.len();
+    Ok(())
+}
+
+/// ファイルのサイズと更新日時（ナノ秒）を取得
+fn file_stamp(path: &Path) -> Option<(u64, u64)> {
+    let meta = fs::metadata(path).ok()?;
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    Some((meta.len(), modified))
+}
+
+/// ファイルサイズと先頭・末尾の一部から内容キーを計算
+fn hash_file(path: &Path, size: u64) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    hasher.update(size.to_le_bytes());
+
+    let mut buffer = Vec::with_capacity(SAMPLE_BYTES as usize);
+    (&mut file).take(SAMPLE_BYTES).read_to_end(&mut buffer)?;
+    hasher.update(&buffer);
+
+    // 先頭と重ならない範囲の末尾を読む
+    if size > SAMPLE_BYTES {
+        buffer.clear();
+        file.seek(SeekFrom::Start(
+            size.saturating_sub(SAMPLE_BYTES).max(SAMPLE_BYTES),
+        ))?;
+        file.take(SAMPLE_BYTES).read_to_end(&mut buffer)?;
+        hasher.update(&buffer);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// インデックスを参照して内容キーを取得し、サイズか更新日時が変わっていれば再計算
+///
+/// stamp_path はサイズと更新日時を確認するファイル（アーカイブ内の画像はアーカイブ自体）。
+fn indexed_key(
+    image_path: &str,
+    stamp_path: &Path,
+    compute: impl FnOnce(u64) -> Option<String>,
+) -> Option<String> {
+    let (size, modified) = file_stamp(stamp_path)?;
+
+    {
+        let mut guard = CONTENT_INDEX.lock().ok()?;
+        let index = guard.get_or_insert_with(read_index);
+        if let Some(entry) = index.entries.get(image_path) {
+            if entry.size == size && entry.modified == modified {
+                return Some(entry.key.clone());
+            }
+        }
+    }
+
+    // 計算中はロックを保持しない
+    let key = compute(size)?;
+    let entry = IndexEntry {
+        path: image_path.to_string(),
+        size,
+        modified,
+        key: key.clone(),
+    };
+
+    let mut guard = CONTENT_INDEX.lock().ok()?;
+    let index = guard.get_or_insert_with(read_index);
+    // 記録の失敗は無視（次回は再計算になるだけ）
+    if append_entry(&entry).is_ok() {
+        index.lines += 1;
+    }
+    index.entries.insert(entry.path.clone(), entry);
+    if index.lines > index.entries.len() * 2 + COMPACT_THRESHOLD {
+        let _ = rewrite_index(index);
+    }
+
+    Some(key)
+}
+
+/// 画像の内容キーを取得
+///
+/// アーカイブ内の画像はアーカイブ自体の内容キーとエントリ名から計算する。
+/// ファイルが存在しない場合は None。
+pub(crate) fn content_key(image_path: &str) -> Option<String> {
+    match archive::split_member_path(image_path) {
+        Some((archive_path, member)) => indexed_key(image_path, &archive_path, |_| {
+            let archive_key = content_key(&archive_path.to_string_lossy())?;
+            let mut hasher = Sha256::new();
+            hasher.update(format!(
+                "{}{}{}",
+                archive_key,
+                archive::MEMBER_SEPARATOR,
+                member
+            ));
+            Some(format!("{:x}", hasher.finalize()))
+        }),
+        None => {
+            let path = Path::new(image_path);
+            indexed_key(image_path, path, |size| hash_file(path, size).ok())
+        }
+    }
+}
+
+/// フォルダ（またはアーカイブ）以下の記録済みのパスを、フォルダからの相対パスで取得
+///
+/// フォルダが移動・削除された後も、ガベージコレクションを行うまでは記録が残る。
+pub(crate) fn indexed_relative_paths(folder_path: &str) -> Vec<String> {
+    let folder_path = folder_path.trim_end_matches(['/', '\\']);
+    let Ok(mut guard) = CONTENT_INDEX.lock() else {
+        return Vec::new();
+    };
+    let index = guard.get_or_insert_with(read_index);
+    index
+        .entries
+        .keys()
+        .filter_map(|path| {
+            let rest = path.strip_prefix(folder_path)?;
+            rest.strip_prefix(archive::MEMBER_SEPARATOR)
+                .or_else(|| rest.strip_prefix(['/', '\\']))
+                .map(str::to_string)
+        })
+        .collect()
+}
+
+/// 存在しなくなったパスをインデックスから削除し、残った内容キーの一覧を返す
+///
+/// アーカイブ内の画像はアーカイブの存在のみを確認する。
+pub(crate) fn prune_content_index() -> HashSet<String> {
+    let Ok(mut guard) = CONTENT_INDEX.lock() else {
+        return HashSet::new();
+    };
+    let index = guard.get_or_insert_with(read_index);
+    let before = index.entries.len();
+    index
+        .entries
+        .retain(|path, _| source_file_path(path).exists());
+    if index.entries.len() != before || index.lines > index.entries.len() {
+        let _ = rewrite_index(index);
+    }
+    index
+        .entries
+        .values()
+        .map(|entry| entry.key.clone())
+        .collect()
+}
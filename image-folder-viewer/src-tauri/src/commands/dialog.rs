@@ -1,5 +1,6 @@
 // ダイアログ関連コマンド
 
+use super::formats;
 use tauri_plugin_dialog::DialogExt;
 
 /// プロファイルファイル選択ダイアログ（開く用）
@@ -43,16 +44,29 @@ pub async fn select_folder(app: tauri::AppHandle) -> Result<Option<String>, Stri
     Ok(folder.map(|f| f.to_string()))
 }
 
+/// アーカイブファイル選択ダイアログ（ZIP/CBZ/TAR/TAR.GZをカードとして追加する用）
+#[tauri::command]
+pub async fn select_archive_file(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    let file = app
+        .dialog()
+        .file()
+        .add_filter("アーカイブ", &["zip", "cbz", "tar", "gz", "tgz"])
+        .blocking_pick_file();
+
+    Ok(file.map(|f| f.to_string()))
+}
+
 /// 画像ファイル選択ダイアログ（サムネイル選択用）
 #[tauri::command]
 pub async fn select_image_file(
     app: tauri::AppHandle,
     initial_dir: Option<String>,
 ) -> Result<Option<String>, String> {
+    let extensions = formats::supported_extensions();
     let mut dialog = app
         .dialog()
         .file()
-        .add_filter("画像ファイル", &["jpg", "jpeg", "png", "gif", "webp", "bmp"]);
+        .add_filter("画像ファイル", &extensions);
 
     // 初期ディレクトリが指定されている場合は設定
     if let Some(dir) = initial_dir {
@@ -0,0 +1,117 @@
+// 対応画像形式の管理
+//
+// 一覧表示・ダイアログのフィルタ・デコードはすべてこのレジストリを参照する。
+// 各形式が実際に読み込めるかは image クレートの有効な機能に依存する。
+
+use image::ImageFormat;
+use serde::Serialize;
+use std::path::Path;
+
+/// 対応画像形式の一覧（表示名, 形式）
+const FORMATS: &[(&str, ImageFormat)] = &[
+    ("JPEG", ImageFormat::Jpeg),
+    ("PNG", ImageFormat::Png),
+    ("GIF", ImageFormat::Gif),
+    ("WebP", ImageFormat::WebP),
+    ("BMP", ImageFormat::Bmp),
+    ("TIFF", ImageFormat::Tiff),
+    ("AVIF", ImageFormat::Avif),
+    ("QOI", ImageFormat::Qoi),
+    ("TGA", ImageFormat::Tga),
+    ("ICO", ImageFormat::Ico),
+    ("PNM", ImageFormat::Pnm),
+    ("HDR", ImageFormat::Hdr),
+    ("OpenEXR", ImageFormat::OpenExr),
+    ("Farbfeld", ImageFormat::Farbfeld),
+];
+
+/// このビルドで読み込み可能な形式かどうかを判定
+///
+/// AVIF は image クレートの "avif" 機能がエンコーダのみのため、
+/// デコーダ（"avif" 機能 → image/avif-native）を有効にした場合のみ対象とする。
+fn is_format_enabled(format: ImageFormat) -> bool {
+    match format {
+        ImageFormat::Avif => cfg!(feature = "avif"),
+        _ => format.reading_enabled(),
+    }
+}
+
+/// WebView がそのまま表示できる形式（それ以外は表示時にデコードしてPNG/JPEGに変換する）
+const WEBVIEW_FORMATS: &[ImageFormat] = &[
+    ImageFormat::Jpeg,
+    ImageFormat::Png,
+    ImageFormat::Gif,
+    ImageFormat::WebP,
+    ImageFormat::Bmp,
+    ImageFormat::Ico,
+    ImageFormat::Avif,
+];
+
+/// WebView がそのまま表示できる形式かどうかを判定
+pub(crate) fn is_webview_format(format: ImageFormat) -> bool {
+    WEBVIEW_FORMATS.contains(&format)
+}
+
+/// 読み込み可能な形式の一覧
+fn enabled_formats() -> impl Iterator<Item = (&'static str, ImageFormat)> {
+    FORMATS
+        .iter()
+        .copied()
+        .filter(|(_, format)| is_format_enabled(*format))
+}
+
+/// 読み込み可能な形式の拡張子一覧（ダイアログのフィルタ用）
+pub(crate) fn supported_extensions() -> Vec<&'static str> {
+    enabled_formats()
+        .flat_map(|(_, format)| format.extensions_str().iter().copied())
+        .collect()
+}
+
+/// 拡張子から画像形式を判定（読み込み不可の形式は None）
+pub(crate) fn format_from_path(path: &Path) -> Option<ImageFormat> {
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    enabled_formats()
+        .map(|(_, format)| format)
+        .find(|format| format.extensions_str().contains(&ext.as_str()))
+}
+
+/// 画像形式の表示名を取得
+pub(crate) fn format_name(format: ImageFormat) -> Option<&'static str> {
+    FORMATS
+        .iter()
+        .find(|(_, f)| *f == format)
+        .map(|(name, _)| *name)
+}
+
+/// 読み込み可能な画像ファイルかどうかを拡張子で判定
+pub(crate) fn is_supported_image(path: &Path) -> bool {
+    format_from_path(path).is_some()
+}
+
+/// 対応画像形式の情報
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupportedFormat {
+    /// 表示名（"JPEG" など）
+    pub name: String,
+    /// MIMEタイプ
+    pub mime_type: String,
+    /// 拡張子一覧（小文字、ドットなし）
+    pub extensions: Vec<String>,
+}
+
+/// このビルドで対応している画像形式の一覧を取得
+#[tauri::command]
+pub fn get_supported_formats() -> Vec<SupportedFormat> {
+    enabled_formats()
+        .map(|(name, format)| SupportedFormat {
+            name: name.to_string(),
+            mime_type: format.to_mime_type().to_string(),
+            extensions: format
+                .extensions_str()
+                .iter()
+                .map(|ext| ext.to_string())
+                .collect(),
+        })
+        .collect()
+}
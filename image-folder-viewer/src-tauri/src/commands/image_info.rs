@@ -0,0 +1,296 @@
+// 画像情報取得コマンド
+//
+// ファイルサイズ・解像度・形式・EXIFの概要を返す。
+// 解像度と色形式はヘッダのみから取得し、画素データ全体のデコードは行わない。
+
+use super::archive;
+use super::blocking::run_blocking;
+use super::formats;
+use super::images::list_images;
+use crate::models::{ScanOptions, SortMode};
+use exif::{In, Reader as ExifReader, Tag, Value};
+use image::metadata::Orientation;
+use image::{ImageDecoder, ImageFormat, ImageReader};
+use serde::Serialize;
+use std::fs;
+use std::io::{BufRead, BufReader, Cursor, Seek, SeekFrom};
+use std::path::Path;
+use std::time::SystemTime;
+
+/// EXIF情報の概要
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExifSummary {
+    /// カメラメーカー
+    pub make: Option<String>,
+    /// カメラ機種
+    pub model: Option<String>,
+    /// レンズ
+    pub lens_model: Option<String>,
+    /// 露出時間（"1/125 s" など）
+    pub exposure_time: Option<String>,
+    /// F値（"f/2.8" など）
+    pub f_number: Option<String>,
+    /// ISO感度
+    pub iso: Option<u32>,
+    /// 焦点距離（"50 mm" など）
+    pub focal_length: Option<String>,
+    /// 撮影日時（EXIF形式 "YYYY:MM:DD HH:MM:SS"）
+    pub date_taken: Option<String>,
+    /// 緯度（十進度、南緯は負）
+    pub gps_latitude: Option<f64>,
+    /// 経度（十進度、西経は負）
+    pub gps_longitude: Option<f64>,
+}
+
+/// 画像詳細情報
+///
+/// 読み取れなかった項目は None になる（ファイル自体が存在しない場合のみエラー）。
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageInfo {
+    pub path: String,
+    pub filename: String,
+    /// ファイルサイズ（バイト）
+    pub size: u64,
+    /// 更新日時（ISO 8601形式、アーカイブ内の画像はアーカイブの更新日時）
+    pub modified_at: Option<String>,
+    /// 幅（EXIFの向きを適用した表示上のピクセル数）
+    pub width: Option<u32>,
+    /// 高さ（EXIFの向きを適用した表示上のピクセル数）
+    pub height: Option<u32>,
+    /// 画像形式の表示名（"JPEG" など）
+    pub format: Option<String>,
+    /// 色形式（"Rgb8", "Rgba8" など）
+    pub color_type: Option<String>,
+    /// アニメーションのフレーム数（静止画は None）
+    pub frame_count: Option<u32>,
+    /// EXIF情報（EXIFがない場合は None）
+    pub exif: Option<ExifSummary>,
+}
+
+/// ヘッダから読み取った画像の基本情報
+struct HeaderInfo {
+    width: u32,
+    height: u32,
+    format: Option<ImageFormat>,
+    color_type: String,
+}
+
+/// 画像ヘッダを読み取る（画素データはデコードしない）
+fn read_header<R: BufRead + Seek>(reader: R, path: &Path) -> Option<HeaderInfo> {
+    let mut reader = ImageReader::new(reader).with_guessed_format().ok()?;
+    if reader.format().is_none() {
+        reader.set_format(formats::format_from_path(path)?);
+    }
+    let format = reader.format();
+    let mut decoder = reader.into_decoder().ok()?;
+    let (width, height) = decoder.dimensions();
+    let color_type = format!("{:?}", decoder.color_type());
+
+    // 90度回転を含む向きの場合は縦横を入れ替える
+    let rotated = matches!(
+        decoder.orientation().unwrap_or(Orientation::NoTransforms),
+        Orientation::Rotate90
+            | Orientation::Rotate270
+            | Orientation::Rotate90FlipH
+            | Orientation::Rotate270FlipH
+    );
+    let (width, height) = if rotated {
+        (height, width)
+    } else {
+        (width, height)
+    };
+
+    Some(HeaderInfo {
+        width,
+        height,
+        format,
+        color_type,
+    })
+}
+
+/// アニメーション画像のフレーム数を取得（静止画・非対応形式は None）
+///
+/// フレームの画素データは展開せず、チャンク/ブロックの情報のみを数える。
+pub(crate) fn count_frames<R: BufRead + Seek>(reader: R, format: ImageFormat) -> Option<u32> {
+    let frames = match format {
+        ImageFormat::Gif => {
+            let mut options = gif::DecodeOptions::new();
+            options.skip_frame_decoding(true);
+            let mut decoder = options.read_info(reader).ok()?;
+            let mut count = 0;
+            while let Ok(Some(_)) = decoder.next_frame_info() {
+                count += 1;
+            }
+            count
+        }
+        ImageFormat::WebP => {
+            let decoder = image_webp::WebPDecoder::new(reader).ok()?;
+            if !decoder.is_animated() {
+                return None;
+            }
+            decoder.num_frames()
+        }
+        ImageFormat::Png => {
+            let decoder = png::Decoder::new(reader).read_info().ok()?;
+            decoder.info().animation_control?.num_frames
+        }
+        _ => return None,
+    };
+
+    (frames > 1).then_some(frames)
+}
+
+/// 文字列フィールドを取得（前後の空白と引用符を除去）
+fn exif_string(exif: &exif::Exif, tag: Tag) -> Option<String> {
+    let field = exif.get_field(tag, In::PRIMARY)?;
+    let value = field.display_value().to_string();
+    let value = value.trim().trim_matches('"').trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// 単位付きの表示用文字列を取得
+fn exif_display_with_unit(exif: &exif::Exif, tag: Tag) -> Option<String> {
+    let field = exif.get_field(tag, In::PRIMARY)?;
+    Some(field.display_value().with_unit(exif).to_string())
+}
+
+/// GPS座標（度分秒 + 方位）を十進度に変換
+fn exif_gps_coordinate(
+    exif: &exif::Exif,
+    tag: Tag,
+    ref_tag: Tag,
+    negative_ref: &str,
+) -> Option<f64> {
+    let field = exif.get_field(tag, In::PRIMARY)?;
+    let degrees = match &field.value {
+        Value::Rational(parts) if parts.len() >= 3 => {
+            parts[0].to_f64() + parts[1].to_f64() / 60.0 + parts[2].to_f64() / 3600.0
+        }
+        _ => return None,
+    };
+    let is_negative = exif_string(exif, ref_tag).is_some_and(|r| r.starts_with(negative_ref));
+    Some(if is_negative { -degrees } else { degrees })
+}
+
+/// EXIF情報の概要を読み取る
+fn read_exif_summary<R: BufRead + Seek>(mut reader: R) -> Option<ExifSummary> {
+    let exif = ExifReader::new().read_from_container(&mut reader).ok()?;
+
+    Some(ExifSummary {
+        make: exif_string(&exif, Tag::Make),
+        model: exif_string(&exif, Tag::Model),
+        lens_model: exif_string(&exif, Tag::LensModel),
+        exposure_time: exif_display_with_unit(&exif, Tag::ExposureTime),
+        f_number: exif_display_with_unit(&exif, Tag::FNumber),
+        iso: exif
+            .get_field(Tag::PhotographicSensitivity, In::PRIMARY)
+            .and_then(|field| field.value.get_uint(0)),
+        focal_length: exif_display_with_unit(&exif, Tag::FocalLength),
+        date_taken: exif_string(&exif, Tag::DateTimeOriginal)
+            .or_else(|| exif_string(&exif, Tag::DateTime)),
+        gps_latitude: exif_gps_coordinate(&exif, Tag::GPSLatitude, Tag::GPSLatitudeRef, "S"),
+        gps_longitude: exif_gps_coordinate(&exif, Tag::GPSLongitude, Tag::GPSLongitudeRef, "W"),
+    })
+}
+
+/// 画像データを読み取って詳細情報を組み立てる
+fn inspect<R: BufRead + Seek>(
+    mut reader: R,
+    path: &Path,
+    image_path: &str,
+    size: u64,
+    modified: Option<SystemTime>,
+) -> ImageInfo {
+    let header = read_header(&mut reader, path);
+
+    let format = header.as_ref().and_then(|h| h.format);
+    let frame_count = format.and_then(|format| {
+        reader.seek(SeekFrom::Start(0)).ok()?;
+        count_frames(&mut reader, format)
+    });
+    let exif = reader
+        .seek(SeekFrom::Start(0))
+        .ok()
+        .and_then(|_| read_exif_summary(&mut reader));
+
+    ImageInfo {
+        path: image_path.to_string(),
+        filename: path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        size,
+        modified_at: modified.map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339()),
+        width: header.as_ref().map(|h| h.width),
+        height: header.as_ref().map(|h| h.height),
+        format: format
+            .and_then(formats::format_name)
+            .map(|name| name.to_string()),
+        color_type: header.map(|h| h.color_type),
+        frame_count,
+        exif,
+    }
+}
+
+/// 画像の詳細情報を取得（内部処理用、アーカイブ内の画像にも対応）
+fn read_image_info(image_path: &str) -> Result<ImageInfo, String> {
+    match archive::split_member_path(image_path) {
+        Some((archive_path, member)) => {
+            let bytes = archive::read_member(&archive_path, &member)?;
+            let modified = fs::metadata(&archive_path).and_then(|m| m.modified()).ok();
+            let size = bytes.len() as u64;
+            Ok(inspect(
+                Cursor::new(bytes),
+                Path::new(&member),
+                image_path,
+                size,
+                modified,
+            ))
+        }
+        None => {
+            let path = Path::new(image_path);
+            let meta = fs::metadata(path)
+                .map_err(|_| format!("画像ファイルが見つかりません: {}", image_path))?;
+            let file =
+                fs::File::open(path).map_err(|e| format!("画像の読み込みに失敗しました: {}", e))?;
+            Ok(inspect(
+                BufReader::new(file),
+                path,
+                image_path,
+                meta.len(),
+                meta.modified().ok(),
+            ))
+        }
+    }
+}
+
+/// 画像の詳細情報を取得
+#[tauri::command]
+pub async fn get_image_info(image_path: String) -> Result<ImageInfo, String> {
+    run_blocking(move || read_image_info(&image_path)).await
+}
+
+/// フォルダ内のすべての画像の詳細情報を取得（並び順は get_images_in_folder と同じ）
+#[tauri::command]
+pub async fn get_images_info_in_folder(
+    folder_path: String,
+    options: Option<ScanOptions>,
+    sort: Option<SortMode>,
+) -> Result<Vec<ImageInfo>, String> {
+    run_blocking(move || {
+        let images = list_images(
+            &folder_path,
+            &options.unwrap_or_default(),
+            sort.unwrap_or_default(),
+        )?;
+
+        // 走査後に削除されたファイルは除外する
+        Ok(images
+            .iter()
+            .filter_map(|image| read_image_info(&image.path).ok())
+            .collect())
+    })
+    .await
+}
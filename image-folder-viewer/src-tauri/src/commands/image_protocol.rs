@@ -0,0 +1,116 @@
+// 画像配信用のカスタムプロトコル
+//
+// アーカイブ内の画像（"<アーカイブ>!/<エントリ名>"）は実ファイルがなく、
+// convertFileSrc（asset:// プロトコル）では表示できないため、
+// image:// プロトコル経由でエントリのバイト列をそのまま返す。
+// TIFF・PNM・QOI・TGA など WebView が表示できない形式は、デコードして
+// PNG（透過部分のある画像）または JPEG に変換して返す。
+
+use super::archive;
+use super::formats;
+use super::images::{load_image, source_file_path};
+use super::thumbnail::{encode_thumbnail, error_response, request_image_path};
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+use tauri::http::{header, Request, Response, StatusCode};
+
+/// 画像配信用のURIスキーム名（フロントエンドでは convertFileSrc(path, "image") で参照）
+pub const IMAGE_PROTOCOL: &str = "image";
+
+/// WebView が表示できない形式を変換する際のJPEG品質
+const CONVERTED_JPEG_QUALITY: u8 = 92;
+
+/// 元画像の更新日時とサイズから ETag を生成（アーカイブ内の画像はアーカイブファイルで判定）
+fn image_etag(image_path: &str) -> Option<String> {
+    let meta = fs::metadata(source_file_path(image_path)).ok()?;
+    let modified = meta
+        .modified()
+        .ok()?
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .ok()?;
+    Some(format!("\"{:x}-{:x}\"", modified.as_nanos(), meta.len()))
+}
+
+/// 画像ファイル（アーカイブ内の画像にも対応）のバイト列を読み込む
+fn read_image_bytes(image_path: &str) -> Result<Vec<u8>, String> {
+    match archive::split_member_path(image_path) {
+        Some((archive_path, member)) => archive::read_member(&archive_path, &member),
+        None => fs::read(image_path).map_err(|e| format!("画像の読み込みに失敗しました: {}", e)),
+    }
+}
+
+/// 表示用の画像データを読み込み、MIMEタイプとともに返す
+///
+/// WebView が表示できる形式はファイルの内容をそのまま、それ以外の形式は
+/// デコードして PNG または JPEG にエンコードしたものを返す。
+fn load_display_image(image_path: &str) -> Result<(Vec<u8>, &'static str), String> {
+    match formats::format_from_path(Path::new(image_path)) {
+        Some(format) if !formats::is_webview_format(format) => {
+            let image = load_image(image_path)?;
+            let converted = encode_thumbnail(&image, CONVERTED_JPEG_QUALITY)?;
+            Ok((converted.bytes, converted.encoding.mime_type()))
+        }
+        format => {
+            let mime_type =
+                format.map_or("application/octet-stream", |format| format.to_mime_type());
+            Ok((read_image_bytes(image_path)?, mime_type))
+        }
+    }
+}
+
+/// image:// プロトコルのリクエストを処理
+///
+/// URLは "image://localhost/<URLエンコードした画像パス>" 形式
+/// （Windows では "http://image.localhost/..."）。
+/// 元画像が変わっていなければ If-None-Match に対して 304 を返す。
+pub fn handle_image_request(request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let uri = request.uri();
+    let Some(image_path) = request_image_path(uri) else {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            format!("画像パスが不正です: {}", uri),
+        );
+    };
+
+    let etag = image_etag(&image_path);
+    if let Some(ref etag) = etag {
+        let not_modified = request
+            .headers()
+            .get(header::IF_NONE_MATCH)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value == etag);
+        if not_modified {
+            return Response::builder()
+                .status(StatusCode::NOT_MODIFIED)
+                .header(header::ETAG, etag.as_str())
+                .body(Vec::new())
+                .unwrap_or_default();
+        }
+    }
+
+    let (bytes, mime_type) = match load_display_image(&image_path) {
+        Ok(image) => image,
+        Err(e) => {
+            let status = if source_file_path(&image_path).exists() {
+                StatusCode::INTERNAL_SERVER_ERROR
+            } else {
+                StatusCode::NOT_FOUND
+            };
+            return error_response(status, e);
+        }
+    };
+
+    let mut builder = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, mime_type)
+        .header(header::CONTENT_LENGTH, bytes.len())
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        // URLに元画像の更新状態を含まないため、毎回 ETag で再検証させる
+        .header(header::CACHE_CONTROL, "no-cache");
+    if let Some(etag) = etag {
+        builder = builder.header(header::ETAG, etag);
+    }
+
+    builder.body(bytes).unwrap_or_default()
+}
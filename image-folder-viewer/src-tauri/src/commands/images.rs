@@ -1,295 +1,669 @@
 // 画像関連コマンド
 
-use base64::{engine::general_purpose::STANDARD, Engine};
-use image::ImageReader;
-use once_cell::sync::Lazy;
+use super::blocking::run_blocking;
+use super::{archive, formats};
+use crate::models::{ScanOptions, SortDirection, SortKey, SortMode};
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageReader};
 use serde::Serialize;
-use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::io::Cursor;
+use std::io::{BufRead, BufReader, Cursor, Seek};
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
 use std::time::SystemTime;
-use tauri::{AppHandle, Manager};
 
-/// 対応する画像拡張子
-const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif", "webp", "bmp"];
-
-/// サムネイルキャッシュの最大エントリ数
-const MAX_CACHE_ENTRIES: usize = 200;
-
-/// サムネイルキャッシュ（パス+サイズ → Base64 DataURL）
-static THUMBNAIL_CACHE: Lazy<Mutex<ThumbnailCache>> =
-    Lazy::new(|| Mutex::new(ThumbnailCache::new(MAX_CACHE_ENTRIES)));
+/// 画像パスに対応する実ファイルのパスを取得（アーカイブ内の画像はアーカイブ自体）
+pub(crate) fn source_file_path(image_path: &str) -> PathBuf {
+    match archive::split_member_path(image_path) {
+        Some((archive_path, _)) => archive_path,
+        None => PathBuf::from(image_path),
+    }
+}
 
-/// シンプルなLRU風キャッシュ（最大サイズ超過時は古いエントリを削除）
-struct ThumbnailCache {
-    map: HashMap<(String, u32), String>,
-    order: Vec<(String, u32)>,
-    max_size: usize,
+/// 画像ファイルの存在確認
+///
+/// アーカイブのエントリ一覧はアーカイブごとに1回だけ読んで使い回すため、
+/// 1回の処理（コマンドの呼び出しなど）の間だけ使う。
+#[derive(Default)]
+pub(crate) struct ImageChecker {
+    /// アーカイブのパス → エントリ名（読み込めないアーカイブは空）
+    archive_members: HashMap<PathBuf, HashSet<String>>,
 }
 
-impl ThumbnailCache {
-    fn new(max_size: usize) -> Self {
-        Self {
-            map: HashMap::new(),
-            order: Vec::new(),
-            max_size,
+impl ImageChecker {
+    /// 画像ファイルが存在するかを確認（アーカイブ内の画像はエントリの有無を確認）
+    pub(crate) fn exists(&mut self, image_path: &str) -> bool {
+        match archive::split_member_path(image_path) {
+            Some((archive_path, member)) => self
+                .archive_members
+                .entry(archive_path)
+                .or_insert_with_key(|archive_path| {
+                    archive::list_members(archive_path)
+                        .map(|members| members.into_iter().collect())
+                        .unwrap_or_default()
+                })
+                .contains(&member),
+            None => Path::new(image_path).is_file(),
         }
     }
+}
 
-    fn get(&self, key: &(String, u32)) -> Option<String> {
-        self.map.get(key).cloned()
-    }
+/// フォルダ（またはアーカイブ）内のパスならフォルダからの相対パスを取得
+pub(crate) fn relative_to<'a>(path: &'a str, folder_path: &str) -> Option<&'a str> {
+    let rest = path.strip_prefix(folder_path.trim_end_matches(['/', '\\']))?;
+    rest.strip_prefix(archive::MEMBER_SEPARATOR)
+        .or_else(|| rest.strip_prefix(['/', '\\']))
+}
 
-    fn insert(&mut self, key: (String, u32), value: String) {
-        // 既存のキーがある場合は更新のみ
-        if self.map.contains_key(&key) {
-            self.map.insert(key, value);
-            return;
+/// 画像を読み込んでデコード（アーカイブ内の画像にも対応、EXIFの向きを適用）
+pub(crate) fn load_image(image_path: &str) -> Result<DynamicImage, String> {
+    match archive::split_member_path(image_path) {
+        Some((archive_path, member)) => {
+            let bytes = archive::read_member(&archive_path, &member)?;
+            decode_image(ImageReader::new(Cursor::new(bytes)), Path::new(&member))
         }
-
-        // 最大サイズを超える場合、古いエントリを削除
-        while self.order.len() >= self.max_size {
-            if let Some(old_key) = self.order.first().cloned() {
-                self.map.remove(&old_key);
-                self.order.remove(0);
-            }
+        None => {
+            let file = fs::File::open(image_path)
+                .map_err(|e| format!("画像の読み込みに失敗しました: {}", e))?;
+            decode_image(
+                ImageReader::new(BufReader::new(file)),
+                Path::new(image_path),
+            )
         }
-
-        // 新しいエントリを追加
-        self.order.push(key.clone());
-        self.map.insert(key, value);
     }
 }
 
-/// サムネイルディスクキャッシュのサブディレクトリ名
-const THUMBNAIL_CACHE_DIR: &str = "thumbnails";
+/// 内容から形式を判定してデコード（TGAなど判定できない形式は拡張子から決定）
+///
+/// EXIFの Orientation タグに従って回転・反転を適用した画像を返す。
+fn decode_image<'a, R: 'a + BufRead + Seek>(
+    reader: ImageReader<R>,
+    path: &Path,
+) -> Result<DynamicImage, String> {
+    let mut reader = reader
+        .with_guessed_format()
+        .map_err(|e| format!("画像の読み込みに失敗しました: {}", e))?;
+    if reader.format().is_none() {
+        if let Some(format) = formats::format_from_path(path) {
+            reader.set_format(format);
+        }
+    }
+
+    let mut decoder = reader
+        .into_decoder()
+        .map_err(|e| format!("画像のデコードに失敗しました: {}", e))?;
+    // Orientation が読めない場合は無変換として扱う
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut img = DynamicImage::from_decoder(decoder)
+        .map_err(|e| format!("画像のデコードに失敗しました: {}", e))?;
+    img.apply_orientation(orientation);
 
-/// ディスクキャッシュのファイル名を計算（SHA-256ハッシュ）
-fn disk_cache_filename(image_path: &str, size: u32) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(format!("{}:{}", image_path, size).as_bytes());
-    let hash = hasher.finalize();
-    format!("{:x}.jpg", hash)
+    Ok(img)
 }
 
-/// ディスクキャッシュのベースディレクトリを取得
-fn get_cache_dir(app: &AppHandle) -> Option<PathBuf> {
-    app.path()
-        .app_cache_dir()
-        .ok()
-        .map(|dir| dir.join(THUMBNAIL_CACHE_DIR))
+/// 走査で見つかった画像ファイル（絶対パスとカードルートからの相対パス）
+struct ScannedImage {
+    path: PathBuf,
+    relative_path: String,
 }
 
-/// ディスクキャッシュの有効性を判定（元画像のmtimeと比較）
-fn is_disk_cache_valid(cache_path: &Path, source_path: &Path) -> bool {
-    let cache_meta = match fs::metadata(cache_path) {
-        Ok(m) => m,
-        Err(_) => return false,
-    };
-    let source_meta = match fs::metadata(source_path) {
-        Ok(m) => m,
-        Err(_) => return false,
-    };
-    let source_modified = source_meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
-    let cache_modified = cache_meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
-    cache_modified >= source_modified
+/// 走査対象のフォルダ（またはアーカイブファイル）を検証
+fn ensure_folder(folder_path: &str) -> Result<&Path, String> {
+    let path = Path::new(folder_path);
+
+    if !path.exists() {
+        return Err(format!("フォルダが見つかりません: {}", folder_path));
+    }
+
+    if !path.is_dir() && !archive::is_archive_file(path) {
+        return Err(format!(
+            "指定されたパスはフォルダではありません: {}",
+            folder_path
+        ));
+    }
+
+    Ok(path)
 }
 
-/// ディスクキャッシュからサムネイルを読み込み（Base64 DataURL）
-fn try_load_from_disk(cache_path: &Path, source_path: &Path) -> Option<String> {
-    if !is_disk_cache_valid(cache_path, source_path) {
-        return None;
+/// フォルダ内の画像ファイルを走査（順序は未定義、呼び出し側で `sort_images` を適用する）
+///
+/// 再帰走査時はシンボリックリンクのループを避けるため、
+/// 訪問済みディレクトリを正規化パスで記録する。
+fn scan_images(root: &Path, options: &ScanOptions) -> Result<Vec<ScannedImage>, String> {
+    if archive::is_archive_file(root) {
+        return scan_archive(root, options);
+    }
+
+    let mut images = Vec::new();
+    let mut visited = HashSet::new();
+    if let Ok(canonical) = fs::canonicalize(root) {
+        visited.insert(canonical);
     }
-    let bytes = fs::read(cache_path).ok()?;
-    let base64_str = STANDARD.encode(&bytes);
-    Some(format!("data:image/jpeg;base64,{}", base64_str))
+
+    // ルート直下の読み込み失敗のみエラーとし、サブフォルダの失敗は無視する
+    let entries =
+        fs::read_dir(root).map_err(|e| format!("フォルダの読み込みに失敗しました: {}", e))?;
+    scan_dir_entries(entries, "", 0, options, &mut visited, &mut images);
+
+    Ok(images)
 }
 
-/// サムネイルのJPEGバイト列をディスクキャッシュに保存
-fn try_save_to_disk(cache_dir: &Path, cache_path: &Path, jpeg_bytes: &[u8]) {
-    let _ = fs::create_dir_all(cache_dir);
-    let _ = fs::write(cache_path, jpeg_bytes);
+/// アーカイブ内の画像エントリを走査（エントリ名の "/" の数を深さとして扱う）
+fn scan_archive(archive_path: &Path, options: &ScanOptions) -> Result<Vec<ScannedImage>, String> {
+    let images = archive::list_members(archive_path)?
+        .into_iter()
+        .filter(|member| formats::is_supported_image(Path::new(member)))
+        .filter(|member| {
+            let depth = member.matches('/').count() as u32;
+            if options.recursive {
+                options.max_depth.is_none_or(|max| depth <= max)
+            } else {
+                depth == 0
+            }
+        })
+        .map(|member| ScannedImage {
+            path: PathBuf::from(archive::member_path(archive_path, &member)),
+            relative_path: member,
+        })
+        .collect();
+
+    Ok(images)
 }
 
-/// サムネイル画像を生成してBase64 DataURLで返す（メモリ+ディスクキャッシュ付き）
-#[tauri::command]
-pub fn get_thumbnail(app: AppHandle, image_path: String, size: u32) -> Result<String, String> {
-    let cache_key = (image_path.clone(), size);
-
-    // [1] メモリキャッシュを確認
-    {
-        let cache = THUMBNAIL_CACHE
-            .lock()
-            .map_err(|e| format!("キャッシュロックエラー: {}", e))?;
-        if let Some(cached) = cache.get(&cache_key) {
-            return Ok(cached);
+/// ディレクトリエントリを走査し、必要に応じてサブフォルダへ再帰する
+fn scan_dir_entries(
+    entries: fs::ReadDir,
+    prefix: &str,
+    depth: u32,
+    options: &ScanOptions,
+    visited: &mut HashSet<PathBuf>,
+    images: &mut Vec<ScannedImage>,
+) {
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let file_type = match entry.file_type() {
+            Ok(t) => t,
+            Err(_) => continue,
+        };
+        let entry_path = entry.path();
+
+        // シンボリックリンクは設定に応じてリンク先の種別で判定
+        let (is_file, is_dir) = if file_type.is_symlink() {
+            if !options.follow_symlinks {
+                continue;
+            }
+            match fs::metadata(&entry_path) {
+                Ok(m) => (m.is_file(), m.is_dir()),
+                Err(_) => continue,
+            }
+        } else {
+            (file_type.is_file(), file_type.is_dir())
+        };
+
+        let name = entry.file_name().to_string_lossy().to_string();
+        let relative_path = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+
+        if is_file {
+            if formats::is_supported_image(&entry_path) {
+                images.push(ScannedImage {
+                    path: entry_path,
+                    relative_path,
+                });
+            }
+        } else if is_dir && options.recursive {
+            if options.max_depth.is_some_and(|max| depth >= max) {
+                continue;
+            }
+            let canonical = match fs::canonicalize(&entry_path) {
+                Ok(c) => c,
+                Err(_) => continue,
+            };
+            if !visited.insert(canonical) {
+                continue;
+            }
+            if let Ok(sub_entries) = fs::read_dir(&entry_path) {
+                scan_dir_entries(
+                    sub_entries,
+                    &relative_path,
+                    depth + 1,
+                    options,
+                    visited,
+                    images,
+                );
+            }
         }
     }
+}
 
-    let source_path = Path::new(&image_path);
-
-    if !source_path.exists() {
-        return Err(format!("画像ファイルが見つかりません: {}", image_path));
+/// 自然順で文字列を比較（"img2" < "img10"、英字は大文字小文字を区別しない）
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+    // "2" と "02" のように数値が等しい場合の差（他に差がない場合のみ使用）
+    let mut zero_padding = Ordering::Equal;
+
+    loop {
+        let (ca, cb) = match (a_chars.peek(), b_chars.peek()) {
+            (None, None) => break,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(&ca), Some(&cb)) => (ca, cb),
+        };
+
+        let ord = if ca.is_ascii_digit() && cb.is_ascii_digit() {
+            // 数字の連続は先頭の0を除いた桁数→辞書順で比較
+            let na = take_digits(&mut a_chars);
+            let nb = take_digits(&mut b_chars);
+            let ta = na.trim_start_matches('0');
+            let tb = nb.trim_start_matches('0');
+            if zero_padding == Ordering::Equal {
+                zero_padding = na.len().cmp(&nb.len());
+            }
+            ta.len().cmp(&tb.len()).then_with(|| ta.cmp(tb))
+        } else {
+            a_chars.next();
+            b_chars.next();
+            ca.to_lowercase().cmp(cb.to_lowercase())
+        };
+
+        if ord != Ordering::Equal {
+            return ord;
+        }
     }
 
-    // [2] ディスクキャッシュを確認
-    let cache_dir = get_cache_dir(&app);
-    let disk_cache_path = cache_dir
-        .as_ref()
-        .map(|dir| dir.join(disk_cache_filename(&image_path, size)));
-
-    if let Some(ref dcp) = disk_cache_path {
-        if let Some(data_url) = try_load_from_disk(dcp, source_path) {
-            // ディスクキャッシュヒット → メモリキャッシュにも格納
-            let mut cache = THUMBNAIL_CACHE
-                .lock()
-                .map_err(|e| format!("キャッシュロックエラー: {}", e))?;
-            cache.insert(cache_key, data_url.clone());
-            return Ok(data_url);
+    // 自然順で同値の場合はバイト順で安定させる
+    zero_padding.then_with(|| a.cmp(b))
+}
+
+/// 先頭から連続する数字を取り出す
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(&c) = chars.peek() {
+        if !c.is_ascii_digit() {
+            break;
         }
+        digits.push(c);
+        chars.next();
     }
+    digits
+}
 
-    // [3] サムネイル生成
-    let img = ImageReader::open(source_path)
-        .map_err(|e| format!("画像の読み込みに失敗しました: {}", e))?
-        .decode()
-        .map_err(|e| format!("画像のデコードに失敗しました: {}", e))?;
-
-    let thumbnail = img.thumbnail(size, size);
+/// ソートキーの値（同一ソート内では常に同じバリアントになる）
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum SortValue {
+    Time(SystemTime),
+    Number(u64),
+    Text(String),
+}
 
-    let mut buffer = Cursor::new(Vec::new());
-    thumbnail
-        .write_to(&mut buffer, image::ImageFormat::Jpeg)
-        .map_err(|e| format!("サムネイルのエンコードに失敗しました: {}", e))?;
+/// EXIFの撮影日時を取得（"YYYY:MM:DD HH:MM:SS" 形式のため文字列比較で順序付け可能）
+fn read_exif_date(reader: &mut (impl BufRead + Seek)) -> Option<String> {
+    let exif = exif::Reader::new().read_from_container(reader).ok()?;
+    [exif::Tag::DateTimeOriginal, exif::Tag::DateTime]
+        .iter()
+        .find_map(|tag| exif.get_field(*tag, exif::In::PRIMARY))
+        .map(|field| field.display_value().to_string())
+}
 
-    let jpeg_bytes = buffer.into_inner();
+/// 画像のピクセル数を取得（ヘッダのみ読み込み、全体はデコードしない）
+///
+/// 内容から形式を判定できない場合は path の拡張子から決定する。
+fn read_pixel_count<R: BufRead + Seek>(reader: ImageReader<R>, path: &Path) -> Option<u64> {
+    let mut reader = reader.with_guessed_format().ok()?;
+    if reader.format().is_none() {
+        reader.set_format(formats::format_from_path(path)?);
+    }
+    let (width, height) = reader.into_dimensions().ok()?;
+    Some(width as u64 * height as u64)
+}
 
-    // ディスクキャッシュに保存
-    if let (Some(ref dir), Some(ref dcp)) = (&cache_dir, &disk_cache_path) {
-        try_save_to_disk(dir, dcp, &jpeg_bytes);
+/// ファイルのソートキーの値を取得（取得できない場合は None）
+fn file_sort_value(path: &Path, key: SortKey) -> Option<SortValue> {
+    match key {
+        SortKey::Name => None,
+        SortKey::Modified => fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .map(SortValue::Time),
+        SortKey::Created => fs::metadata(path)
+            .and_then(|m| m.created())
+            .ok()
+            .map(SortValue::Time),
+        SortKey::Size => fs::metadata(path).ok().map(|m| SortValue::Number(m.len())),
+        SortKey::ExifDate => {
+            let file = fs::File::open(path).ok()?;
+            read_exif_date(&mut BufReader::new(file)).map(SortValue::Text)
+        }
+        SortKey::Dimensions => {
+            read_pixel_count(ImageReader::open(path).ok()?, path).map(SortValue::Number)
+        }
     }
+}
 
-    // Base64エンコードしてDataURLとして返す
-    let base64_str = STANDARD.encode(&jpeg_bytes);
-    let result = format!("data:image/jpeg;base64,{}", base64_str);
+/// ソートキーの値の取得（アーカイブのエントリ情報はアーカイブごとに1回だけ読む）
+#[derive(Default)]
+struct SortValueReader {
+    /// アーカイブのパス → エントリ名 → エントリの情報（読み込めないアーカイブは空）
+    archive_members: HashMap<PathBuf, HashMap<String, archive::MemberInfo>>,
+}
 
-    // メモリキャッシュに格納
-    {
-        let mut cache = THUMBNAIL_CACHE
-            .lock()
-            .map_err(|e| format!("キャッシュロックエラー: {}", e))?;
-        cache.insert(cache_key, result.clone());
+impl SortValueReader {
+    /// ソートキーの値を取得（取得できない場合は None）
+    ///
+    /// アーカイブ内の画像は、更新日時・サイズをアーカイブのエントリ情報から、
+    /// EXIF・画像サイズをエントリの内容から取得する。アーカイブは作成日時を
+    /// 記録しないため、作成日時は更新日時で代用する。
+    fn value(&mut self, path: &Path, key: SortKey) -> Option<SortValue> {
+        if key == SortKey::Name {
+            return None;
+        }
+        let Some((archive_path, member)) = archive::split_member_path(&path.to_string_lossy())
+        else {
+            return file_sort_value(path, key);
+        };
+
+        match key {
+            SortKey::Name => None,
+            SortKey::Modified | SortKey::Created => self
+                .member_info(archive_path, &member)?
+                .modified
+                .map(SortValue::Time),
+            SortKey::Size => self
+                .member_info(archive_path, &member)
+                .map(|info| SortValue::Number(info.size)),
+            SortKey::ExifDate => {
+                let bytes = archive::read_member(&archive_path, &member).ok()?;
+                read_exif_date(&mut Cursor::new(bytes)).map(SortValue::Text)
+            }
+            SortKey::Dimensions => {
+                let bytes = archive::read_member(&archive_path, &member).ok()?;
+                read_pixel_count(ImageReader::new(Cursor::new(bytes)), Path::new(&member))
+                    .map(SortValue::Number)
+            }
+        }
     }
 
-    Ok(result)
+    /// アーカイブ内のエントリの情報を取得
+    fn member_info(&mut self, archive_path: PathBuf, member: &str) -> Option<archive::MemberInfo> {
+        self.archive_members
+            .entry(archive_path)
+            .or_insert_with_key(|archive_path| {
+                archive::member_infos(archive_path).unwrap_or_default()
+            })
+            .get(member)
+            .copied()
+    }
 }
 
-/// フォルダ内の最初の画像ファイルパスを取得
-#[tauri::command]
-pub fn get_first_image_in_folder(folder_path: String) -> Result<Option<String>, String> {
-    let path = Path::new(&folder_path);
+/// 画像一覧をソート
+///
+/// キーが取得できない画像は方向に関わらず末尾に置き、同値は相対パスの自然順で並べる。
+fn sort_images(images: Vec<ScannedImage>, mode: SortMode) -> Vec<ScannedImage> {
+    sort_by_mode(images, mode, |image| &image.path, |image| &image.relative_path)
+}
 
-    if !path.exists() {
-        return Err(format!("フォルダが見つかりません: {}", folder_path));
-    }
+/// 複数のカードから集めた画像一覧をソート
+///
+/// ファイル名で比較し、同名の画像はパスの自然順で並べる。
+pub(crate) fn sort_image_files(images: Vec<ImageFile>, mode: SortMode) -> Vec<ImageFile> {
+    let mut images = images;
+    images.sort_by(|a, b| natural_cmp(&a.path, &b.path));
+    sort_by_mode(
+        images,
+        mode,
+        |image| Path::new(&image.path),
+        |image| &image.filename,
+    )
+}
+
+/// ソート方法に従って並べ替え（path はソートキーの取得、name は名前順と同値時の比較に使用）
+fn sort_by_mode<T>(
+    items: Vec<T>,
+    mode: SortMode,
+    path: impl Fn(&T) -> &Path,
+    name: impl Fn(&T) -> &str,
+) -> Vec<T> {
+    let apply_direction = |ord: Ordering| match mode.direction {
+        SortDirection::Asc => ord,
+        SortDirection::Desc => ord.reverse(),
+    };
 
-    if !path.is_dir() {
-        return Err(format!("指定されたパスはフォルダではありません: {}", folder_path));
+    if mode.key == SortKey::Name {
+        let mut items = items;
+        items.sort_by(|a, b| apply_direction(natural_cmp(name(a), name(b))));
+        return items;
     }
 
-    // ディレクトリ内のエントリを取得してソート
-    let mut entries: Vec<_> = fs::read_dir(path)
-        .map_err(|e| format!("フォルダの読み込みに失敗しました: {}", e))?
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| {
-            if let Ok(file_type) = entry.file_type() {
-                if file_type.is_file() {
-                    if let Some(ext) = entry.path().extension() {
-                        let ext_lower = ext.to_string_lossy().to_lowercase();
-                        return IMAGE_EXTENSIONS.contains(&ext_lower.as_str());
-                    }
-                }
-            }
-            false
-        })
+    let mut reader = SortValueReader::default();
+    let mut keyed: Vec<(Option<SortValue>, T)> = items
+        .into_iter()
+        .map(|item| (reader.value(path(&item), mode.key), item))
         .collect();
 
-    // ファイル名でソート
-    entries.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+    keyed.sort_by(|(ka, a), (kb, b)| {
+        let ord = match (ka, kb) {
+            (Some(va), Some(vb)) => apply_direction(va.cmp(vb)),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+        ord.then_with(|| natural_cmp(name(a), name(b)))
+    });
+
+    keyed.into_iter().map(|(_, item)| item).collect()
+}
 
-    // 最初の画像ファイルのパスを返す
-    Ok(entries
-        .first()
-        .map(|entry| entry.path().to_string_lossy().to_string()))
+/// フォルダ内の最初の画像ファイルパスを取得
+#[tauri::command]
+pub async fn get_first_image_in_folder(
+    folder_path: String,
+    options: Option<ScanOptions>,
+    sort: Option<SortMode>,
+) -> Result<Option<String>, String> {
+    run_blocking(move || {
+        let path = ensure_folder(&folder_path)?;
+        let scanned = scan_images(path, &options.unwrap_or_default())?;
+        let images = sort_images(scanned, sort.unwrap_or_default());
+
+        // 最初の画像ファイルのパスを返す
+        Ok(images
+            .first()
+            .map(|image| image.path.to_string_lossy().to_string()))
+    })
+    .await
 }
 
-/// フォルダパスが有効かどうかを検証
+/// フォルダパスが有効かどうかを検証（アーカイブファイルも有効とみなす）
 #[tauri::command]
 pub fn validate_folder_path(path: String) -> Result<bool, String> {
     let folder_path = Path::new(&path);
-    Ok(folder_path.exists() && folder_path.is_dir())
+    Ok(folder_path.is_dir() || archive::is_archive_file(folder_path))
 }
 
 /// 画像ファイル情報（軽量版：パスとファイル名のみ）
 #[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ImageFile {
     pub path: String,
     pub filename: String,
+    /// カードのルートフォルダからの相対パス（区切り文字は "/"）
+    pub relative_path: String,
+    /// 画像のカードのID（複数のカードから集めた一覧の場合のみ）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub card_id: Option<String>,
 }
 
-/// フォルダ内のすべての画像ファイルを取得（ファイル名順でソート）
+/// フォルダ内のすべての画像ファイルを取得（省略時はファイル名の自然順でソート）
 #[tauri::command]
-pub fn get_images_in_folder(folder_path: String) -> Result<Vec<ImageFile>, String> {
-    let path = Path::new(&folder_path);
-
-    if !path.exists() {
-        return Err(format!("フォルダが見つかりません: {}", folder_path));
-    }
-
-    if !path.is_dir() {
-        return Err(format!(
-            "指定されたパスはフォルダではありません: {}",
-            folder_path
-        ));
-    }
+pub async fn get_images_in_folder(
+    folder_path: String,
+    options: Option<ScanOptions>,
+    sort: Option<SortMode>,
+) -> Result<Vec<ImageFile>, String> {
+    run_blocking(move || {
+        list_images(
+            &folder_path,
+            &options.unwrap_or_default(),
+            sort.unwrap_or_default(),
+        )
+    })
+    .await
+}
 
-    // ディレクトリ内のエントリを取得
-    let mut entries: Vec<_> = fs::read_dir(path)
-        .map_err(|e| format!("フォルダの読み込みに失敗しました: {}", e))?
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| {
-            if let Ok(file_type) = entry.file_type() {
-                if file_type.is_file() {
-                    if let Some(ext) = entry.path().extension() {
-                        let ext_lower = ext.to_string_lossy().to_lowercase();
-                        return IMAGE_EXTENSIONS.contains(&ext_lower.as_str());
-                    }
-                }
-            }
-            false
-        })
-        .collect();
+/// フォルダ（またはアーカイブ）内の画像ファイル数を取得（ソートは行わない）
+pub(crate) fn count_images(folder_path: &str, options: &ScanOptions) -> Result<usize, String> {
+    let path = ensure_folder(folder_path)?;
+    Ok(scan_images(path, options)?.len())
+}
 
-    // ファイル名でソート
-    entries.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+/// フォルダ（またはアーカイブ）内の画像ファイル一覧を取得（内部処理用）
+pub(crate) fn list_images(
+    folder_path: &str,
+    options: &ScanOptions,
+    sort: SortMode,
+) -> Result<Vec<ImageFile>, String> {
+    let path = ensure_folder(folder_path)?;
+    let scanned = scan_images(path, options)?;
+    let sorted = sort_images(scanned, sort);
 
     // 画像ファイル情報を収集
-    let images: Vec<ImageFile> = entries
-        .iter()
-        .map(|entry| {
-            let file_path = entry.path();
-            ImageFile {
-                path: file_path.to_string_lossy().to_string(),
-                filename: file_path
-                    .file_name()
-                    .map(|n| n.to_string_lossy().to_string())
-                    .unwrap_or_default(),
-            }
+    let images: Vec<ImageFile> = sorted
+        .into_iter()
+        .map(|image| ImageFile {
+            path: image.path.to_string_lossy().to_string(),
+            filename: image
+                .path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            relative_path: image.relative_path,
+            card_id: None,
         })
         .collect();
 
     Ok(images)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// テスト用の一時ディレクトリを作成
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "image-folder-viewer-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// 一覧の相対パスを取得
+    fn relative_paths(images: &[ImageFile]) -> Vec<&str> {
+        images.iter().map(|i| i.relative_path.as_str()).collect()
+    }
+
+    /// (エントリ名, サイズ, 更新日時の日) のエントリを持つZIPを作成
+    fn write_zip(path: &Path, entries: &[(&str, usize, u8)]) {
+        let mut zip = zip::ZipWriter::new(fs::File::create(path).unwrap());
+        for (name, size, day) in entries {
+            let modified = zip::DateTime::from_date_and_time(2024, 1, *day, 12, 0, 0).unwrap();
+            let options = zip::write::SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Stored)
+                .last_modified_time(modified);
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(&vec![0u8; *size]).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    /// (エントリ名, サイズ, 更新日時のUNIX秒) のエントリを持つTARを作成
+    fn write_tar(path: &Path, entries: &[(&str, usize, u64)]) {
+        let mut tar = tar::Builder::new(fs::File::create(path).unwrap());
+        for (name, size, mtime) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(*size as u64);
+            header.set_mtime(*mtime);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, name, &vec![0u8; *size][..])
+                .unwrap();
+        }
+        tar.finish().unwrap();
+    }
+
+    fn sort(key: SortKey, direction: SortDirection) -> SortMode {
+        SortMode { key, direction }
+    }
+
+    #[test]
+    fn sorts_zip_members_by_size_and_modified() {
+        let dir = temp_dir("zip-sort");
+        let zip_path = dir.join("images.zip");
+        write_zip(
+            &zip_path,
+            &[("a.png", 300, 2), ("b.png", 100, 3), ("c.png", 200, 1)],
+        );
+        let folder = zip_path.to_string_lossy();
+        let options = ScanOptions::default();
+
+        let by_size = list_images(&folder, &options, sort(SortKey::Size, SortDirection::Asc));
+        assert_eq!(
+            relative_paths(&by_size.unwrap()),
+            ["b.png", "c.png", "a.png"]
+        );
+
+        let by_modified = list_images(
+            &folder,
+            &options,
+            sort(SortKey::Modified, SortDirection::Desc),
+        );
+        assert_eq!(
+            relative_paths(&by_modified.unwrap()),
+            ["b.png", "a.png", "c.png"]
+        );
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn sorts_tar_members_by_size_and_modified() {
+        let dir = temp_dir("tar-sort");
+        let tar_path = dir.join("images.tar");
+        write_tar(
+            &tar_path,
+            &[
+                ("a.png", 300, 1_700_000_200),
+                ("b.png", 100, 1_700_000_300),
+                ("c.png", 200, 1_700_000_100),
+            ],
+        );
+        let folder = tar_path.to_string_lossy();
+        let options = ScanOptions::default();
+
+        let by_size = list_images(&folder, &options, sort(SortKey::Size, SortDirection::Desc));
+        assert_eq!(
+            relative_paths(&by_size.unwrap()),
+            ["a.png", "c.png", "b.png"]
+        );
+
+        let by_modified = list_images(
+            &folder,
+            &options,
+            sort(SortKey::Modified, SortDirection::Asc),
+        );
+        assert_eq!(
+            relative_paths(&by_modified.unwrap()),
+            ["c.png", "a.png", "b.png"]
+        );
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
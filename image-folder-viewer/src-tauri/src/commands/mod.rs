@@ -1,13 +1,54 @@
 // コマンドモジュール
 
+pub mod albums;
+pub mod animation;
+pub mod annotations;
 pub mod app_config;
+pub mod archive;
+pub mod atomic_write;
+pub mod blocking;
+pub mod cards;
 pub mod clipboard;
+pub mod collections;
+pub mod content_index;
 pub mod dialog;
+pub mod formats;
+pub mod image_info;
+pub mod image_protocol;
 pub mod images;
 pub mod profile;
+pub mod profile_backup;
+pub mod profile_lock;
+pub mod profile_merge;
+pub mod profile_paths;
+pub mod relink;
+pub mod tags;
+pub mod thumbnail;
+pub mod thumbnail_cache;
+pub mod thumbnail_jobs;
+pub mod watcher;
 
+pub use albums::*;
+pub use animation::*;
+pub use annotations::*;
 pub use app_config::*;
+pub use archive::*;
+pub use cards::*;
 pub use clipboard::*;
+pub use collections::*;
+pub use content_index::*;
 pub use dialog::*;
+pub use formats::*;
+pub use image_info::*;
+pub use image_protocol::*;
 pub use images::*;
 pub use profile::*;
+pub use profile_backup::*;
+pub use profile_lock::*;
+pub use profile_merge::*;
+pub use relink::*;
+pub use tags::*;
+pub use thumbnail::*;
+pub use thumbnail_cache::*;
+pub use thumbnail_jobs::*;
+pub use watcher::*;
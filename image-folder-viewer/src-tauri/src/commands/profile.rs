@@ -1,9 +1,105 @@
 // プロファイル管理コマンド
 
-use crate::models::ProfileData;
+use super::atomic_write::write_atomic;
+use super::profile_backup::{backup_profile, has_backups};
+use super::profile_lock::{lock_held_by_other, refresh_profile_lock, ProfileLockInfo};
+use super::profile_paths::{relativize_profile_paths, resolve_profile_paths};
+use super::annotations::validate_annotations;
+use super::tags::validate_tag_references;
+use crate::models::{migrate_profile, version_string, ProfileData, PROFILE_VERSION};
+use chrono::DateTime;
+use serde::Serialize;
+use std::fmt;
 use std::fs;
 use std::path::Path;
 
+/// プロファイルの保存エラー
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum SaveProfileError {
+    /// 読み込み後に他のユーザー（またはプロセス）がプロファイルを保存していた
+    #[serde(rename_all = "camelCase")]
+    Conflict {
+        message: String,
+        /// ディスク上のプロファイルの更新日時
+        disk_updated_at: String,
+        /// 読み込み時（前回の保存時）の更新日時
+        expected_updated_at: String,
+        /// プロファイルを開いている他のユーザー（ロックがある場合）
+        locked_by: Option<Box<ProfileLockInfo>>,
+    },
+    /// その他の保存エラー
+    Failed { message: String },
+}
+
+impl fmt::Display for SaveProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Conflict { message, .. } | Self::Failed { message } => f.write_str(message),
+        }
+    }
+}
+
+impl From<String> for SaveProfileError {
+    fn from(message: String) -> Self {
+        Self::Failed { message }
+    }
+}
+
+/// 更新日時が expected より新しいかを判定（解析できない場合は異なれば新しいとみなす）
+fn is_newer(updated_at: &str, expected: &str) -> bool {
+    match (
+        DateTime::parse_from_rfc3339(updated_at),
+        DateTime::parse_from_rfc3339(expected),
+    ) {
+        (Ok(updated_at), Ok(expected)) => updated_at > expected,
+        _ => updated_at != expected,
+    }
+}
+
+/// ディスク上のプロファイルが読み込み後に更新されていないかを確認
+fn check_conflict(path: &Path, expected_updated_at: &str) -> Result<(), SaveProfileError> {
+    // 存在しない・解析できないファイルは上書きしてよい
+    let disk_updated_at = fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|value| value["updatedAt"].as_str().map(str::to_string));
+    let Some(disk_updated_at) = disk_updated_at else {
+        return Ok(());
+    };
+    if !is_newer(&disk_updated_at, expected_updated_at) {
+        return Ok(());
+    }
+
+    let locked_by = lock_held_by_other(path).map(Box::new);
+    let message = match &locked_by {
+        Some(owner) => format!(
+            "プロファイルは読み込み後に他のユーザーによって更新されています（{}@{}）",
+            owner.user, owner.host
+        ),
+        None => "プロファイルは読み込み後に他のユーザーによって更新されています".to_string(),
+    };
+    Err(SaveProfileError::Conflict {
+        message,
+        disk_updated_at,
+        expected_updated_at: expected_updated_at.to_string(),
+        locked_by,
+    })
+}
+
+/// プロファイルの JSON を解析（古いバージョンは現在のバージョンに移行、パスは解決しない）
+///
+/// 存在しないカード・タグを参照するタグ付け・画像の注釈がある場合はエラーとする。
+pub(crate) fn parse_profile(content: &str) -> Result<ProfileData, String> {
+    let value = serde_json::from_str(content)
+        .map_err(|e| format!("プロファイルの解析に失敗しました: {}", e))?;
+    let profile: ProfileData = serde_json::from_value(migrate_profile(value)?)
+        .map_err(|e| format!("プロファイルの解析に失敗しました: {}", e))?;
+    validate_tag_references(&profile)?;
+    validate_annotations(&profile)?;
+    Ok(profile)
+}
+
 /// プロファイルを読み込む
 #[tauri::command]
 pub fn load_profile(path: String) -> Result<ProfileData, String> {
@@ -18,29 +114,56 @@ pub fn load_profile(path: String) -> Result<ProfileData, String> {
     let content = fs::read_to_string(path)
         .map_err(|e| format!("ファイルの読み込みに失敗しました: {}", e))?;
 
-    // JSONパース
-    let profile: ProfileData = serde_json::from_str(&content)
-        .map_err(|e| format!("プロファイルの解析に失敗しました: {}", e))?;
+    // JSONパース（古いバージョンは現在のバージョンに移行）
+    let mut profile = parse_profile(&content).map_err(|e| {
+        if has_backups(path) {
+            format!("{}（バックアップから復元できます）", e)
+        } else {
+            e
+        }
+    })?;
+
+    // 相対パスで保存されたカードのパスを解決
+    resolve_profile_paths(&mut profile, path);
 
     Ok(profile)
 }
 
-/// プロファイルを保存する
+/// プロファイルを保存し、保存した更新日時を返す
+///
+/// expected_updated_at を指定した場合、ディスク上のプロファイルがそれより新しければ
+/// 保存せずに競合エラーを返す（読み込み時・前回の保存時の更新日時を指定する）。
 #[tauri::command]
-pub fn save_profile(path: String, profile: ProfileData) -> Result<(), String> {
-    // 更新日時を現在時刻に更新
+pub fn save_profile(
+    path: String,
+    profile: ProfileData,
+    expected_updated_at: Option<String>,
+) -> Result<String, SaveProfileError> {
+    if let Some(expected) = &expected_updated_at {
+        check_conflict(Path::new(&path), expected)?;
+    }
+
+    // 更新日時を現在時刻に更新し、現在のバージョンで保存
     let mut profile = profile;
     profile.updated_at = chrono::Utc::now().to_rfc3339();
+    profile.version = version_string(PROFILE_VERSION);
+
+    // 設定に応じてカードのパスを相対パスに変換
+    relativize_profile_paths(&mut profile, Path::new(&path));
 
     // JSONに変換（整形あり）
     let content = serde_json::to_string_pretty(&profile)
         .map_err(|e| format!("プロファイルの変換に失敗しました: {}", e))?;
 
-    // ファイルに書き込み
-    fs::write(&path, content)
+    // 保存済みの内容をバックアップしてから、一時ファイル経由で書き込み
+    // （バックアップの失敗では保存を中止しない）
+    let path = Path::new(&path);
+    let _ = backup_profile(path);
+    write_atomic(path, content.as_bytes())
         .map_err(|e| format!("ファイルの保存に失敗しました: {}", e))?;
+    refresh_profile_lock(path);
 
-    Ok(())
+    Ok(profile.updated_at)
 }
 
 /// 新規プロファイルを作成する
@@ -64,7 +187,7 @@ pub fn create_new_profile(path: String) -> Result<ProfileData, String> {
     let profile = ProfileData::default();
 
     // 保存
-    save_profile(path, profile.clone())?;
+    save_profile(path, profile.clone(), None).map_err(|e| e.to_string())?;
 
     Ok(profile)
 }
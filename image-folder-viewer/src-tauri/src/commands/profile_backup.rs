@@ -0,0 +1,150 @@
+// プロファイルのバックアップ
+//
+// プロファイルを上書き保存する前に、保存済みの内容をプロファイルファイルの隣の
+// "<ファイル名>.backups" フォルダに日時付きのファイル名でコピーする。
+// 保存のたびにバックアップし、MAX_BACKUPS 個を超えた古いものから削除する。
+// 解析できない（壊れた）プロファイルも、復旧の手がかりになるためそのままコピーする。
+
+use super::atomic_write::write_atomic;
+use super::profile::{load_profile, parse_profile};
+use crate::models::ProfileData;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// バックアップフォルダ名の接尾辞（"<ファイル名>.backups"）
+const BACKUP_DIR_SUFFIX: &str = ".backups";
+
+/// 保持するバックアップの最大数
+const MAX_BACKUPS: usize = 10;
+
+/// バックアップファイル名の日時の形式（UTC、辞書順が日時順になる）
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+/// プロファイルのバックアップ
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileBackup {
+    /// バックアップファイルのパス
+    pub path: String,
+    /// バックアップの作成日時（ISO 8601形式）
+    pub created_at: String,
+    /// ファイルサイズ（バイト）
+    pub size: u64,
+    /// カード数（解析できない場合は None）
+    pub card_count: Option<u32>,
+}
+
+/// バックアップフォルダのパスを取得
+fn backup_dir(profile_path: &Path) -> PathBuf {
+    let name = profile_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    profile_path.with_file_name(format!("{}{}", name, BACKUP_DIR_SUFFIX))
+}
+
+/// バックアップファイル名から作成日時を取得
+fn backup_time(path: &Path) -> Option<DateTime<Utc>> {
+    let stem = path.file_stem()?.to_str()?;
+    NaiveDateTime::parse_from_str(stem, TIMESTAMP_FORMAT)
+        .ok()
+        .map(|time| time.and_utc())
+}
+
+/// バックアップファイルの一覧を取得（新しい順）
+fn backup_files(profile_path: &Path) -> Vec<(PathBuf, DateTime<Utc>)> {
+    let Ok(entries) = fs::read_dir(backup_dir(profile_path)) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter_map(|path| backup_time(&path).map(|time| (path, time)))
+        .collect();
+    backups.sort_by_key(|(_, time)| Reverse(*time));
+    backups
+}
+
+/// 保存済みのプロファイルをバックアップ（保存前に呼び出す、内容は検証せずにコピーする）
+pub(crate) fn backup_profile(profile_path: &Path) -> Result<(), String> {
+    let Ok(content) = fs::read(profile_path) else {
+        return Ok(());
+    };
+
+    let now = Utc::now();
+    let backups = backup_files(profile_path);
+
+    let dir = backup_dir(profile_path);
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("バックアップフォルダの作成に失敗しました: {}", e))?;
+    let extension = profile_path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_string())
+        .unwrap_or_else(|| "ivprofile".to_string());
+    let backup_path = dir.join(format!("{}.{}", now.format(TIMESTAMP_FORMAT), extension));
+    write_atomic(&backup_path, &content)
+        .map_err(|e| format!("バックアップの作成に失敗しました: {}", e))?;
+
+    // 古いバックアップを削除（作成したものを含めて MAX_BACKUPS 個残す）
+    for (path, _) in backups.iter().skip(MAX_BACKUPS.saturating_sub(1)) {
+        let _ = fs::remove_file(path);
+    }
+
+    Ok(())
+}
+
+/// バックアップが存在するかを確認
+pub(crate) fn has_backups(profile_path: &Path) -> bool {
+    !backup_files(profile_path).is_empty()
+}
+
+/// プロファイルのバックアップ一覧を取得（新しい順）
+#[tauri::command]
+pub fn list_profile_backups(path: String) -> Result<Vec<ProfileBackup>, String> {
+    Ok(backup_files(Path::new(&path))
+        .into_iter()
+        .map(|(backup_path, time)| {
+            let content = fs::read_to_string(&backup_path).ok();
+            ProfileBackup {
+                path: backup_path.to_string_lossy().to_string(),
+                created_at: time.to_rfc3339(),
+                size: fs::metadata(&backup_path).map(|m| m.len()).unwrap_or(0),
+                card_count: content
+                    .and_then(|content| parse_profile(&content).ok())
+                    .map(|profile| profile.cards.len() as u32),
+            }
+        })
+        .collect())
+}
+
+/// バックアップからプロファイルを復元し、復元したプロファイルを返す
+///
+/// 復元前の内容もバックアップするため、復元は取り消すことができる。
+#[tauri::command]
+pub fn restore_profile_backup(path: String, backup_path: String) -> Result<ProfileData, String> {
+    let profile_path = Path::new(&path);
+    let backup_path = PathBuf::from(&backup_path);
+    if !backup_files(profile_path)
+        .iter()
+        .any(|(candidate, _)| *candidate == backup_path)
+    {
+        return Err(format!(
+            "バックアップが見つかりません: {}",
+            backup_path.display()
+        ));
+    }
+
+    let content = fs::read_to_string(&backup_path)
+        .map_err(|e| format!("バックアップの読み込みに失敗しました: {}", e))?;
+    parse_profile(&content)?;
+
+    backup_profile(profile_path)?;
+    write_atomic(profile_path, content.as_bytes())
+        .map_err(|e| format!("プロファイルの復元に失敗しました: {}", e))?;
+
+    load_profile(path)
+}
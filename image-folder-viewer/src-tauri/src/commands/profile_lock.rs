@@ -0,0 +1,211 @@
+// プロファイルの編集ロック（勧告ロック）
+//
+// プロファイルを開いている間、プロファイルファイルの隣に "<ファイル名>.lock" を作成し、
+// 開いているユーザー・ホスト・プロセスを記録する。ロックは他のユーザーが開いていることを
+// 知らせるためのもので、保存は妨げない（同時編集による上書きは保存時の競合検出で防ぐ）。
+// 保存のたびにロックを更新し、LOCK_STALE_AFTER_HOURS 時間以上更新されていないロックは
+// 異常終了などで残ったものとみなして無視する。
+
+use super::atomic_write::write_atomic;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// ロックファイル名の接尾辞（"<ファイル名>.lock"）
+const LOCK_FILE_SUFFIX: &str = ".lock";
+
+/// ロックを無効とみなすまでの時間（時間）
+const LOCK_STALE_AFTER_HOURS: i64 = 12;
+
+/// このプロセスのセッションID（ロックの所有者の判定に使用）
+static SESSION_ID: Lazy<String> = Lazy::new(|| uuid::Uuid::new_v4().to_string());
+
+/// ロックの所有者情報（ロックファイルの内容）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileLockInfo {
+    pub session_id: String,
+    /// ユーザー名
+    pub user: String,
+    /// ホスト名
+    pub host: String,
+    /// プロセスID
+    pub pid: u32,
+    /// ロックの取得日時（ISO 8601形式）
+    pub acquired_at: String,
+    /// ロックの最終更新日時（ISO 8601形式）
+    pub refreshed_at: String,
+}
+
+/// ロックの取得結果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileLockStatus {
+    /// ロックを取得できたか（false の場合は他のユーザーが開いている）
+    pub acquired: bool,
+    /// 現在のロックの所有者
+    pub owner: ProfileLockInfo,
+}
+
+/// ロックファイルのパスを取得
+fn lock_path(profile_path: &Path) -> PathBuf {
+    let name = profile_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    profile_path.with_file_name(format!("{}{}", name, LOCK_FILE_SUFFIX))
+}
+
+/// ユーザー名を取得
+fn user_name() -> String {
+    ["USER", "USERNAME"]
+        .iter()
+        .find_map(|key| std::env::var(key).ok().filter(|value| !value.is_empty()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// ホスト名を取得
+fn host_name() -> String {
+    ["COMPUTERNAME", "HOSTNAME"]
+        .iter()
+        .find_map(|key| std::env::var(key).ok().filter(|value| !value.is_empty()))
+        .or_else(|| {
+            fs::read_to_string("/etc/hostname")
+                .ok()
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// このプロセスのロック情報を作成
+fn own_lock_info(acquired_at: Option<String>) -> ProfileLockInfo {
+    let now = Utc::now().to_rfc3339();
+    ProfileLockInfo {
+        session_id: SESSION_ID.clone(),
+        user: user_name(),
+        host: host_name(),
+        pid: std::process::id(),
+        acquired_at: acquired_at.unwrap_or_else(|| now.clone()),
+        refreshed_at: now,
+    }
+}
+
+/// ロックファイルを読み込む（存在しない・解析できない場合は None）
+fn read_lock(profile_path: &Path) -> Option<ProfileLockInfo> {
+    let content = fs::read_to_string(lock_path(profile_path)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// ロックファイルを書き込む
+fn write_lock(profile_path: &Path, info: &ProfileLockInfo) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(info)
+        .map_err(|e| format!("ロックの変換に失敗しました: {}", e))?;
+    write_atomic(&lock_path(profile_path), content.as_bytes())
+        .map_err(|e| format!("ロックファイルの書き込みに失敗しました: {}", e))
+}
+
+/// 一定時間更新されていないロックかを判定
+fn is_stale(info: &ProfileLockInfo) -> bool {
+    DateTime::parse_from_rfc3339(&info.refreshed_at).map_or(true, |refreshed| {
+        Utc::now() - refreshed.with_timezone(&Utc) > chrono::Duration::hours(LOCK_STALE_AFTER_HOURS)
+    })
+}
+
+/// このプロセスのロックかを判定
+fn is_own(info: &ProfileLockInfo) -> bool {
+    info.session_id == *SESSION_ID
+}
+
+/// 他のユーザー（またはプロセス）が保持している有効なロックを取得
+pub(crate) fn lock_held_by_other(profile_path: &Path) -> Option<ProfileLockInfo> {
+    read_lock(profile_path).filter(|info| !is_own(info) && !is_stale(info))
+}
+
+/// このプロセスのロックであれば最終更新日時を更新（保存時に呼び出す）
+pub(crate) fn refresh_profile_lock(profile_path: &Path) {
+    if let Some(info) = read_lock(profile_path).filter(is_own) {
+        let _ = write_lock(profile_path, &own_lock_info(Some(info.acquired_at)));
+    }
+}
+
+/// プロファイルのロックを取得
+///
+/// 他のユーザーが有効なロックを保持している場合は取得せず、その所有者を返す。
+/// force が true の場合は他のユーザーのロックを奪う。
+#[tauri::command]
+pub fn acquire_profile_lock(
+    path: String,
+    force: Option<bool>,
+) -> Result<ProfileLockStatus, String> {
+    let profile_path = Path::new(&path);
+    let force = force.unwrap_or(false);
+
+    if !force {
+        if let Some(owner) = lock_held_by_other(profile_path) {
+            return Ok(ProfileLockStatus {
+                acquired: false,
+                owner,
+            });
+        }
+    }
+
+    let acquired_at = read_lock(profile_path)
+        .filter(is_own)
+        .map(|info| info.acquired_at);
+    let info = own_lock_info(acquired_at);
+    if lock_path(profile_path).exists() {
+        write_lock(profile_path, &info)?;
+        return Ok(ProfileLockStatus {
+            acquired: true,
+            owner: info,
+        });
+    }
+
+    // ロックファイルがない場合は新規作成のみ行い、同時に開いた他のユーザーとの競合を避ける
+    let content = serde_json::to_string_pretty(&info)
+        .map_err(|e| format!("ロックの変換に失敗しました: {}", e))?;
+    match OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(lock_path(profile_path))
+    {
+        Ok(mut file) => {
+            file.write_all(content.as_bytes())
+                .and_then(|_| file.sync_all())
+                .map_err(|e| format!("ロックファイルの書き込みに失敗しました: {}", e))?;
+            Ok(ProfileLockStatus {
+                acquired: true,
+                owner: info,
+            })
+        }
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => match read_lock(profile_path) {
+            Some(owner) if !force => Ok(ProfileLockStatus {
+                acquired: false,
+                owner,
+            }),
+            _ => {
+                write_lock(profile_path, &info)?;
+                Ok(ProfileLockStatus {
+                    acquired: true,
+                    owner: info,
+                })
+            }
+        },
+        Err(e) => Err(format!("ロックファイルの作成に失敗しました: {}", e)),
+    }
+}
+
+/// プロファイルのロックを解除（このプロセスのロックのみ）
+#[tauri::command]
+pub fn release_profile_lock(path: String) -> Result<(), String> {
+    let profile_path = Path::new(&path);
+    if read_lock(profile_path).is_some_and(|info| is_own(&info)) {
+        fs::remove_file(lock_path(profile_path))
+            .map_err(|e| format!("ロックファイルの削除に失敗しました: {}", e))?;
+    }
+    Ok(())
+}
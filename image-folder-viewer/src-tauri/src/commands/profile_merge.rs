@@ -0,0 +1,651 @@
+// プロファイルの同時編集の統合
+//
+// 保存時に競合（読み込み後に他のユーザーが保存していた）が検出された場合、
+// 読み込み時の内容（base）・編集中の内容（local）・ディスク上の内容（disk）から
+// カード・タグ・カードとタグの関連付け・画像の注釈・スマートコレクション・アルバムを
+// 項目ごと、フィールドごとに3方向で統合する。
+// 片方のみが変更したフィールドはその変更を採用し、両方が異なる値に変更したフィールドは
+// 編集中の値を採用して競合として報告する。削除と変更が重なった項目は残して報告する。
+// 統合後、両方で作成された同じ名前のタグは1つにまとめ、削除されたカード・タグへの
+// 関連付け・注釈・スマートコレクションの条件を取り除く。
+
+use super::profile::load_profile;
+use crate::models::{CardTag, ProfileData, Tag};
+use chrono::DateTime;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+
+/// 統合で解決できなかった競合
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeConflict {
+    /// 項目の種類（"card" | "tag" | "cardTag" | "imageAnnotation" | "smartCollection" | "album"）
+    pub item: String,
+    /// 項目のID（カードとタグの関連付けは "<カードID>:<タグID>"、
+    /// 画像の注釈は "<カードID>:<相対パス>"）
+    pub id: String,
+    /// 競合したフィールド（削除と変更の競合は None）
+    pub field: Option<String>,
+    /// 編集中の値（削除した場合は None）
+    pub local_value: Option<Value>,
+    /// ディスク上の値（削除された場合は None）
+    pub disk_value: Option<Value>,
+}
+
+/// 統合結果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileMerge {
+    /// 統合したプロファイル（更新日時はディスク上のもの）
+    pub profile: ProfileData,
+    /// ディスク上のプロファイル（次回の統合の基準）
+    pub disk_profile: ProfileData,
+    /// 編集中の値を採用した競合
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// 統合する項目の一覧（JSON のキーと、項目のIDを取得する関数）
+struct ItemKind {
+    item: &'static str,
+    key: &'static str,
+    id: fn(&Value) -> String,
+}
+
+const ITEM_KINDS: [ItemKind; 6] = [
+    ItemKind {
+        item: "card",
+        key: "cards",
+        id: |value| value["id"].as_str().unwrap_or_default().to_string(),
+    },
+    ItemKind {
+        item: "tag",
+        key: "tags",
+        id: |value| value["id"].as_str().unwrap_or_default().to_string(),
+    },
+    ItemKind {
+        item: "cardTag",
+        key: "cardTags",
+        id: |value| {
+            format!(
+                "{}:{}",
+                value["cardId"].as_str().unwrap_or_default(),
+                value["tagId"].as_str().unwrap_or_default()
+            )
+        },
+    },
+    ItemKind {
+        item: "imageAnnotation",
+        key: "imageAnnotations",
+        id: |value| {
+            format!(
+                "{}:{}",
+                value["cardId"].as_str().unwrap_or_default(),
+                value["relativePath"].as_str().unwrap_or_default()
+            )
+        },
+    },
+    ItemKind {
+        item: "smartCollection",
+        key: "smartCollections",
+        id: |value| value["id"].as_str().unwrap_or_default().to_string(),
+    },
+    ItemKind {
+        item: "album",
+        key: "albums",
+        id: |value| value["id"].as_str().unwrap_or_default().to_string(),
+    },
+];
+
+/// 競合を記録する
+struct Conflicts<'a> {
+    item: &'a str,
+    id: &'a str,
+    list: &'a mut Vec<MergeConflict>,
+}
+
+impl Conflicts<'_> {
+    fn push(&mut self, field: Option<&str>, local: Option<&Value>, disk: Option<&Value>) {
+        self.list.push(MergeConflict {
+            item: self.item.to_string(),
+            id: self.id.to_string(),
+            field: field.map(str::to_string),
+            local_value: local.cloned(),
+            disk_value: disk.cloned(),
+        });
+    }
+}
+
+/// 新しい方の更新日時を取得
+fn later<'a>(a: &'a Value, b: &'a Value) -> &'a Value {
+    let parse = |value: &Value| {
+        value
+            .as_str()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+    };
+    match (parse(a), parse(b)) {
+        (Some(x), Some(y)) if y > x => b,
+        (None, Some(_)) => b,
+        _ => a,
+    }
+}
+
+/// 1つの値を3方向で統合（両方が異なる値に変更した場合は編集中の値を採用）
+fn merge_value<'a>(
+    base: Option<&'a Value>,
+    local: Option<&'a Value>,
+    disk: Option<&'a Value>,
+) -> Result<Option<&'a Value>, Option<&'a Value>> {
+    if local == disk || disk == base {
+        Ok(local)
+    } else if local == base {
+        Ok(disk)
+    } else {
+        Err(local)
+    }
+}
+
+/// 項目のフィールドを3方向で統合
+fn merge_fields(
+    base: Option<&Map<String, Value>>,
+    local: &Map<String, Value>,
+    disk: &Map<String, Value>,
+    conflicts: &mut Conflicts,
+) -> Map<String, Value> {
+    let mut merged = Map::new();
+    let keys = local
+        .keys()
+        .chain(disk.keys().filter(|key| !local.contains_key(*key)));
+    for key in keys {
+        let (l, d) = (local.get(key), disk.get(key));
+        let value = match merge_value(base.and_then(|base| base.get(key)), l, d) {
+            Ok(value) => value,
+            // 更新日時は競合とせず新しい方を採用
+            Err(_) if key == "updatedAt" => match (l, d) {
+                (Some(l), Some(d)) => Some(later(l, d)),
+                _ => l.or(d),
+            },
+            Err(value) => {
+                conflicts.push(Some(key), l, d);
+                value
+            }
+        };
+        if let Some(value) = value {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+    merged
+}
+
+/// 項目の一覧を3方向で統合（編集中の順序を基本とし、ディスク上で追加された項目を末尾に加える）
+fn merge_items(
+    kind: &ItemKind,
+    base: &[Value],
+    local: &[Value],
+    disk: &[Value],
+    conflicts: &mut Vec<MergeConflict>,
+) -> Vec<Value> {
+    let index = |items: &[Value]| -> HashMap<String, Value> {
+        items
+            .iter()
+            .map(|item| ((kind.id)(item), item.clone()))
+            .collect()
+    };
+    let (base_items, local_items, disk_items) = (index(base), index(local), index(disk));
+
+    let mut ids: Vec<String> = Vec::new();
+    let mut seen = HashSet::new();
+    for item in local.iter().chain(disk).chain(base) {
+        let id = (kind.id)(item);
+        if seen.insert(id.clone()) {
+            ids.push(id);
+        }
+    }
+
+    let mut merged = Vec::new();
+    for id in &ids {
+        let mut conflicts = Conflicts {
+            item: kind.item,
+            id,
+            list: conflicts,
+        };
+        let (b, l, d) = (base_items.get(id), local_items.get(id), disk_items.get(id));
+        match (l, d) {
+            (Some(l), Some(d)) => match (l.as_object(), d.as_object()) {
+                (Some(lo), Some(dobj)) => {
+                    let bo = b.and_then(Value::as_object);
+                    merged.push(Value::Object(merge_fields(bo, lo, dobj, &mut conflicts)));
+                }
+                _ => merged.push(l.clone()),
+            },
+            // 片方のみに存在：追加された、または他方で削除された
+            (Some(item), None) | (None, Some(item)) => match b {
+                None => merged.push(item.clone()),
+                Some(b) if b == item => {}
+                Some(_) => {
+                    conflicts.push(None, l, d);
+                    merged.push(item.clone());
+                }
+            },
+            (None, None) => {}
+        }
+    }
+    merged
+}
+
+/// 読み込み時の内容・編集中の内容・ディスク上の内容を統合
+fn merge_profiles(
+    base: &ProfileData,
+    local: &ProfileData,
+    disk: &ProfileData,
+) -> Result<(ProfileData, Vec<MergeConflict>), String> {
+    let to_json = |profile: &ProfileData| {
+        serde_json::to_value(profile)
+            .map_err(|e| format!("プロファイルの変換に失敗しました: {}", e))
+    };
+    let (base_json, local_json, disk_json) = (to_json(base)?, to_json(local)?, to_json(disk)?);
+    let items = |value: &Value, key: &str| value[key].as_array().cloned().unwrap_or_default();
+
+    let mut conflicts = Vec::new();
+    let mut merged = local_json.clone();
+    for kind in &ITEM_KINDS {
+        merged[kind.key] = Value::Array(merge_items(
+            kind,
+            &items(&base_json, kind.key),
+            &items(&local_json, kind.key),
+            &items(&disk_json, kind.key),
+            &mut conflicts,
+        ));
+    }
+    merged["updatedAt"] = disk_json["updatedAt"].clone();
+
+    let mut merged: ProfileData = serde_json::from_value(merged)
+        .map_err(|e| format!("統合したプロファイルの変換に失敗しました: {}", e))?;
+    // 真偽値は両方が異なる値に変更することがないため、競合は発生しない
+    merged.relative_paths = merge_value(
+        Some(&base_json["relativePaths"]),
+        Some(&local_json["relativePaths"]),
+        Some(&disk_json["relativePaths"]),
+    )
+    .unwrap_or_else(|value| value)
+    .and_then(Value::as_bool)
+    .unwrap_or(local.relative_paths);
+
+    let replaced = unify_duplicate_tags(&mut merged.tags);
+    remap_tags(&mut merged, &replaced);
+    Ok((merged, conflicts))
+}
+
+/// 同じ名前（大文字・小文字を区別しない）のタグを先にあるタグにまとめ、
+/// まとめたタグのID → 残したタグのIDを返す
+fn unify_duplicate_tags(tags: &mut Vec<Tag>) -> HashMap<String, String> {
+    let mut kept: HashMap<String, String> = HashMap::new();
+    let mut replaced = HashMap::new();
+    tags.retain(|tag| match kept.get(&tag.name.to_lowercase()) {
+        Some(kept_id) => {
+            replaced.insert(tag.id.clone(), kept_id.clone());
+            false
+        }
+        None => {
+            kept.insert(tag.name.to_lowercase(), tag.id.clone());
+            true
+        }
+    });
+    replaced
+}
+
+/// タグの付け替えを反映し、削除されたカード・タグへの関連付け・注釈・条件を取り除く
+fn remap_tags(profile: &mut ProfileData, replaced: &HashMap<String, String>) {
+    let card_ids: HashSet<String> = profile.cards.iter().map(|card| card.id.clone()).collect();
+    let tag_ids: HashSet<String> = profile.tags.iter().map(|tag| tag.id.clone()).collect();
+    let map = |id: &str| {
+        let id = replaced.get(id).map_or(id, String::as_str);
+        tag_ids.contains(id).then(|| id.to_string())
+    };
+
+    let mut seen = HashSet::new();
+    profile.card_tags = std::mem::take(&mut profile.card_tags)
+        .into_iter()
+        .filter(|ct| card_ids.contains(&ct.card_id))
+        .filter_map(|ct| {
+            let tag_id = map(&ct.tag_id)?;
+            seen.insert((ct.card_id.clone(), tag_id.clone()))
+                .then_some(CardTag {
+                    card_id: ct.card_id,
+                    tag_id,
+                })
+        })
+        .collect();
+
+    profile
+        .image_annotations
+        .retain(|annotation| card_ids.contains(&annotation.card_id));
+    for annotation in profile.image_annotations.iter_mut() {
+        let mut tag_ids: Vec<String> = Vec::new();
+        for id in annotation.tag_ids.iter().filter_map(|id| map(id)) {
+            if !tag_ids.contains(&id) {
+                tag_ids.push(id);
+            }
+        }
+        annotation.tag_ids = tag_ids;
+    }
+    profile.image_annotations.retain(|a| !a.is_empty());
+
+    for collection in profile.smart_collections.iter_mut() {
+        collection.query.remap_tag_ids(&map);
+    }
+}
+
+/// ディスク上のプロファイルを読み込み直し、編集中の変更と統合する
+///
+/// base には読み込み時（前回の保存・統合時）のプロファイル、local には編集中のプロファイルを指定する。
+/// 統合したプロファイルの更新日時はディスク上のものとなるため、そのまま競合確認付きで保存できる。
+#[tauri::command]
+pub fn merge_profile_changes(
+    path: String,
+    base: ProfileData,
+    local: ProfileData,
+) -> Result<ProfileMerge, String> {
+    let disk = load_profile(path)?;
+    let (profile, conflicts) = merge_profiles(&base, &local, &disk)?;
+    Ok(ProfileMerge {
+        profile,
+        disk_profile: disk,
+        conflicts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Card, ImageAnnotation, SmartCollection, SmartQuery, TagExpression};
+
+    const BASE_TIME: &str = "2026-01-01T00:00:00+00:00";
+
+    fn card(id: &str, title: &str) -> Card {
+        Card {
+            id: id.to_string(),
+            title: title.to_string(),
+            folder_path: format!("/photos/{}", id),
+            thumbnail: None,
+            sort_order: 0,
+            created_at: BASE_TIME.to_string(),
+            updated_at: BASE_TIME.to_string(),
+            scan_options: Default::default(),
+            sort_mode: Default::default(),
+        }
+    }
+
+    fn tag(id: &str, name: &str) -> Tag {
+        Tag {
+            id: id.to_string(),
+            name: name.to_string(),
+            color: None,
+        }
+    }
+
+    fn card_tag(card_id: &str, tag_id: &str) -> CardTag {
+        CardTag {
+            card_id: card_id.to_string(),
+            tag_id: tag_id.to_string(),
+        }
+    }
+
+    /// カード card-1・card-2、タグ tag-1、card-1 への tag-1 のタグ付けを持つプロファイル
+    fn base() -> ProfileData {
+        ProfileData {
+            updated_at: BASE_TIME.to_string(),
+            cards: vec![card("card-1", "One"), card("card-2", "Two")],
+            tags: vec![tag("tag-1", "Red")],
+            card_tags: vec![card_tag("card-1", "tag-1")],
+            ..ProfileData::default()
+        }
+    }
+
+    fn merge(
+        base: &ProfileData,
+        local: &ProfileData,
+        disk: &ProfileData,
+    ) -> (ProfileData, Vec<MergeConflict>) {
+        merge_profiles(base, local, disk).expect("merge succeeds")
+    }
+
+    fn find_card<'a>(profile: &'a ProfileData, id: &str) -> Option<&'a Card> {
+        profile.cards.iter().find(|card| card.id == id)
+    }
+
+    #[test]
+    fn changes_on_different_fields_are_combined() {
+        let base = base();
+        let mut local = base.clone();
+        local.cards[0].title = "Local".to_string();
+        let mut disk = base.clone();
+        disk.cards[0].thumbnail = Some("/photos/card-1/a.png".to_string());
+
+        let (merged, conflicts) = merge(&base, &local, &disk);
+        let card = find_card(&merged, "card-1").unwrap();
+        assert_eq!(card.title, "Local");
+        assert_eq!(card.thumbnail.as_deref(), Some("/photos/card-1/a.png"));
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn local_value_wins_a_conflicting_field() {
+        let base = base();
+        let mut local = base.clone();
+        local.cards[0].title = "Local".to_string();
+        let mut disk = base.clone();
+        disk.cards[0].title = "Disk".to_string();
+
+        let (merged, conflicts) = merge(&base, &local, &disk);
+        assert_eq!(find_card(&merged, "card-1").unwrap().title, "Local");
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].item, "card");
+        assert_eq!(conflicts[0].id, "card-1");
+        assert_eq!(conflicts[0].field.as_deref(), Some("title"));
+        assert_eq!(conflicts[0].disk_value, Some(Value::from("Disk")));
+    }
+
+    #[test]
+    fn updated_at_takes_the_later_value_without_conflict() {
+        let base = base();
+        let mut local = base.clone();
+        local.cards[0].updated_at = "2026-03-01T00:00:00+00:00".to_string();
+        let mut disk = base.clone();
+        disk.cards[0].updated_at = "2026-02-01T00:00:00+00:00".to_string();
+        disk.updated_at = "2026-02-01T00:00:00+00:00".to_string();
+
+        let (merged, conflicts) = merge(&base, &local, &disk);
+        assert_eq!(
+            find_card(&merged, "card-1").unwrap().updated_at,
+            "2026-03-01T00:00:00+00:00"
+        );
+        // プロファイルの更新日時はディスク上のもの（競合確認付きで保存するため）
+        assert_eq!(merged.updated_at, "2026-02-01T00:00:00+00:00");
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn items_added_on_both_sides_are_kept() {
+        let base = base();
+        let mut local = base.clone();
+        local.cards.push(card("card-local", "Local"));
+        let mut disk = base.clone();
+        disk.cards.push(card("card-disk", "Disk"));
+
+        let (merged, conflicts) = merge(&base, &local, &disk);
+        let ids: Vec<&str> = merged.cards.iter().map(|card| card.id.as_str()).collect();
+        assert_eq!(ids, ["card-1", "card-2", "card-local", "card-disk"]);
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn unchanged_item_deleted_on_one_side_is_removed() {
+        let base = base();
+        let local = base.clone();
+        let mut disk = base.clone();
+        disk.cards.retain(|card| card.id != "card-2");
+
+        let (merged, conflicts) = merge(&base, &local, &disk);
+        assert!(find_card(&merged, "card-2").is_none());
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn item_deleted_on_one_side_and_modified_on_the_other_is_kept() {
+        let base = base();
+        let mut local = base.clone();
+        local.cards.retain(|card| card.id != "card-2");
+        let mut disk = base.clone();
+        disk.cards[1].title = "Renamed".to_string();
+
+        let (merged, conflicts) = merge(&base, &local, &disk);
+        assert_eq!(find_card(&merged, "card-2").unwrap().title, "Renamed");
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].id, "card-2");
+        assert_eq!(conflicts[0].field, None);
+        assert!(conflicts[0].local_value.is_none());
+        assert!(conflicts[0].disk_value.is_some());
+    }
+
+    #[test]
+    fn references_to_deleted_cards_and_tags_are_pruned() {
+        let mut base = base();
+        base.image_annotations.push(ImageAnnotation {
+            card_id: "card-2".to_string(),
+            relative_path: "a.png".to_string(),
+            rating: 3,
+            favorite: false,
+            color_label: None,
+            tag_ids: Vec::new(),
+            updated_at: BASE_TIME.to_string(),
+        });
+        // ディスク上で card-2 と tag-1 が削除され、編集中に tag-1 を card-2 に付けた
+        let mut local = base.clone();
+        local.card_tags.push(card_tag("card-2", "tag-1"));
+        local.image_annotations[0].tag_ids.push("tag-1".to_string());
+        let mut disk = base.clone();
+        disk.cards.retain(|card| card.id != "card-2");
+        disk.tags.clear();
+        disk.card_tags.clear();
+        disk.image_annotations.clear();
+
+        let (merged, _) = merge(&base, &local, &disk);
+        assert!(merged.tags.is_empty());
+        assert!(merged.card_tags.is_empty());
+        assert!(merged.image_annotations.is_empty());
+    }
+
+    #[test]
+    fn tags_created_with_the_same_name_on_both_sides_are_unified() {
+        let base = base();
+        let mut local = base.clone();
+        local.tags.push(tag("tag-local", "Blue"));
+        local.card_tags.push(card_tag("card-1", "tag-local"));
+        let mut disk = base.clone();
+        disk.tags.push(tag("tag-disk", "blue"));
+        disk.card_tags.push(card_tag("card-2", "tag-disk"));
+        disk.card_tags.push(card_tag("card-1", "tag-disk"));
+
+        let (merged, _) = merge(&base, &local, &disk);
+        let ids: Vec<&str> = merged.tags.iter().map(|tag| tag.id.as_str()).collect();
+        assert_eq!(ids, ["tag-1", "tag-local"]);
+        let blue: Vec<&str> = merged
+            .card_tags
+            .iter()
+            .filter(|ct| ct.tag_id == "tag-local")
+            .map(|ct| ct.card_id.as_str())
+            .collect();
+        assert_eq!(blue, ["card-1", "card-2"]);
+    }
+
+    #[test]
+    fn annotation_tags_follow_unified_tags() {
+        let base = base();
+        let annotation = |tag_id: &str| ImageAnnotation {
+            card_id: "card-1".to_string(),
+            relative_path: "a.png".to_string(),
+            rating: 0,
+            favorite: false,
+            color_label: None,
+            tag_ids: vec![tag_id.to_string()],
+            updated_at: BASE_TIME.to_string(),
+        };
+        let mut local = base.clone();
+        local.tags.push(tag("tag-local", "Blue"));
+        local.image_annotations.push(annotation("tag-local"));
+        let mut disk = base.clone();
+        disk.tags.push(tag("tag-disk", "blue"));
+        disk.image_annotations.push(ImageAnnotation {
+            relative_path: "b.png".to_string(),
+            ..annotation("tag-disk")
+        });
+
+        let (merged, _) = merge(&base, &local, &disk);
+        let tags: Vec<(&str, &[String])> = merged
+            .image_annotations
+            .iter()
+            .map(|a| (a.relative_path.as_str(), a.tag_ids.as_slice()))
+            .collect();
+        let unified = ["tag-local".to_string()];
+        assert_eq!(tags, [("a.png", &unified[..]), ("b.png", &unified[..])]);
+    }
+
+    #[test]
+    fn smart_collection_conditions_on_deleted_tags_are_pruned() {
+        let mut base = base();
+        base.smart_collections.push(SmartCollection {
+            id: "collection-1".to_string(),
+            title: "Red".to_string(),
+            query: SmartQuery {
+                card_tags: Some(TagExpression::Tag {
+                    tag_id: "tag-1".to_string(),
+                }),
+                ..SmartQuery::default()
+            },
+            sort_order: 0,
+            created_at: BASE_TIME.to_string(),
+            updated_at: BASE_TIME.to_string(),
+        });
+        let local = base.clone();
+        let mut disk = base.clone();
+        disk.tags.clear();
+        disk.card_tags.clear();
+
+        let (merged, _) = merge(&base, &local, &disk);
+        let mut referenced = Vec::new();
+        if let Some(expression) = &merged.smart_collections[0].query.card_tags {
+            expression.collect_tag_ids(&mut referenced);
+        }
+        assert!(referenced.is_empty());
+    }
+
+    #[test]
+    fn smart_collection_conditions_follow_unified_tags() {
+        let base = base();
+        let mut local = base.clone();
+        local.tags.push(tag("tag-local", "Blue"));
+        let mut disk = base.clone();
+        disk.tags.push(tag("tag-disk", "blue"));
+        disk.smart_collections.push(SmartCollection {
+            id: "collection-1".to_string(),
+            title: "Blue".to_string(),
+            query: SmartQuery {
+                card_tags: Some(TagExpression::Tag {
+                    tag_id: "tag-disk".to_string(),
+                }),
+                ..SmartQuery::default()
+            },
+            sort_order: 0,
+            created_at: BASE_TIME.to_string(),
+            updated_at: BASE_TIME.to_string(),
+        });
+
+        let (merged, _) = merge(&base, &local, &disk);
+        let mut referenced = Vec::new();
+        if let Some(expression) = &merged.smart_collections[0].query.card_tags {
+            expression.collect_tag_ids(&mut referenced);
+        }
+        assert_eq!(referenced, ["tag-local"]);
+    }
+}
@@ -0,0 +1,113 @@
+// プロファイル内のパスの相対化
+//
+// プロファイルの relative_paths が有効な場合、プロファイルファイルのフォルダ以下にある
+// カードのフォルダとサムネイル、アルバムの画像を、プロファイルファイルのフォルダからの
+// 相対パスで保存する。
+// 相対パスの区切りは OS によらず "/" とし、フォルダ外のパスは絶対パスのまま保存する。
+// 読み込み時は relative_paths の設定によらず、相対パスを絶対パスに解決する。
+
+use super::archive;
+use crate::models::ProfileData;
+use std::path::{Path, PathBuf};
+
+/// プロファイルファイルのフォルダ（相対パスの基準）を取得
+fn base_dir(profile_path: &Path) -> Option<&Path> {
+    profile_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+}
+
+/// 基準フォルダ以下のパスを "/" 区切りの相対パスに変換（フォルダ外なら None）
+fn relativize(base: &Path, path: &str) -> Option<String> {
+    let (file_path, member) = match archive::split_member_path(path) {
+        Some((archive_path, member)) => (archive_path, Some(member)),
+        None => (PathBuf::from(path), None),
+    };
+    let relative = file_path.strip_prefix(base).ok()?;
+    if relative.as_os_str().is_empty() {
+        return None;
+    }
+
+    let relative = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    Some(match member {
+        Some(member) => format!("{}{}{}", relative, archive::MEMBER_SEPARATOR, member),
+        None => relative,
+    })
+}
+
+/// "/" 区切りの相対パスを基準フォルダに連結
+fn join_relative(base: &Path, relative: &str) -> PathBuf {
+    relative
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .fold(base.to_path_buf(), |path, segment| path.join(segment))
+}
+
+/// 相対パスを絶対パスに解決（絶対パスはそのまま）
+///
+/// アーカイブ内の画像は、エントリ名を変換せずアーカイブのパスのみを解決する。
+fn resolve(base: &Path, path: &str) -> String {
+    if path.is_empty() || Path::new(path).is_absolute() {
+        return path.to_string();
+    }
+    for (idx, _) in path.match_indices(archive::MEMBER_SEPARATOR) {
+        let archive_path = join_relative(base, &path[..idx]);
+        if archive::is_archive_file(&archive_path) {
+            return archive::member_path(
+                &archive_path,
+                &path[idx + archive::MEMBER_SEPARATOR.len()..],
+            );
+        }
+    }
+    join_relative(base, path).to_string_lossy().to_string()
+}
+
+/// 保存用にカード・アルバムのパスを相対パスに変換（relative_paths が無効なら何もしない）
+pub(crate) fn relativize_profile_paths(profile: &mut ProfileData, profile_path: &Path) {
+    let Some(base) = base_dir(profile_path).filter(|_| profile.relative_paths) else {
+        return;
+    };
+    for card in profile.cards.iter_mut() {
+        if let Some(relative) = relativize(base, &card.folder_path) {
+            card.folder_path = relative;
+        }
+        if let Some(thumbnail) = card.thumbnail.as_mut() {
+            if let Some(relative) = relativize(base, thumbnail) {
+                *thumbnail = relative;
+            }
+        }
+    }
+    for item in profile
+        .albums
+        .iter_mut()
+        .flat_map(|album| album.items.iter_mut())
+    {
+        if let Some(relative) = relativize(base, &item.path) {
+            item.path = relative;
+        }
+    }
+}
+
+/// 読み込んだカード・アルバムの相対パスを絶対パスに解決
+pub(crate) fn resolve_profile_paths(profile: &mut ProfileData, profile_path: &Path) {
+    let Some(base) = base_dir(profile_path) else {
+        return;
+    };
+    for card in profile.cards.iter_mut() {
+        card.folder_path = resolve(base, &card.folder_path);
+        if let Some(thumbnail) = card.thumbnail.as_mut() {
+            *thumbnail = resolve(base, thumbnail);
+        }
+    }
+    for item in profile
+        .albums
+        .iter_mut()
+        .flat_map(|album| album.items.iter_mut())
+    {
+        item.path = resolve(base, &item.path);
+    }
+}
@@ -0,0 +1,457 @@
+// カードのフォルダの再リンク
+//
+// フォルダの移動・リネームで見つからなくなったカードについて、指定したフォルダ以下から
+// 移動先の候補を探す。候補はフォルダ名（アーカイブはファイル名）の一致、
+// 以前に表示した画像とのファイル名の一致、画像数から評価する。
+// 以前の画像のファイル名は内容キーのインデックスの記録から取得する。
+
+use super::archive;
+use super::blocking::run_blocking;
+use super::cards::card_status;
+use super::content_index::indexed_relative_paths;
+use super::formats::is_supported_image;
+use super::images::{list_images, relative_to, ImageChecker};
+use crate::models::{Card, CardStatus, ProfileData, SortMode};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 探索するフォルダの深さの既定値
+const DEFAULT_MAX_DEPTH: u32 = 8;
+
+/// カードごとに返す候補の最大数
+const MAX_CANDIDATES: usize = 5;
+
+/// 推奨する移動先とする一致度の下限
+const SUGGEST_THRESHOLD: f64 = 0.5;
+
+/// 移動先の候補
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelinkCandidate {
+    pub folder_path: String,
+    /// 一致度（0〜1）
+    pub score: f64,
+    /// フォルダ名（アーカイブはファイル名）が一致した
+    pub name_matched: bool,
+    /// 以前の画像とファイル名が一致した画像数
+    pub matched_files: u32,
+    /// 画像数（カードの走査オプションで数えた値）
+    pub image_count: u32,
+}
+
+/// 見つからないカードの移動先の提案
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelinkProposal {
+    pub card_id: String,
+    pub old_folder_path: String,
+    /// ファイル名がわかっている以前の画像数
+    pub known_files: u32,
+    /// 候補（一致度の高い順）
+    pub candidates: Vec<RelinkCandidate>,
+    /// 推奨する移動先（最上位の候補が十分に一致し、同じ一致度の候補がない場合のみ）
+    pub suggested_folder_path: Option<String>,
+}
+
+/// カードの移動先の指定
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CardRelink {
+    pub card_id: String,
+    pub folder_path: String,
+}
+
+/// 探索で見つかったフォルダまたはアーカイブファイル
+struct Location {
+    path: PathBuf,
+    /// 小文字にしたフォルダ名（アーカイブはファイル名）
+    name: String,
+    /// 直下の画像のファイル名（小文字、アーカイブは空）
+    image_names: HashSet<String>,
+}
+
+/// パスの最後の要素を小文字で取得
+fn lowercase_name(path: &str) -> String {
+    path.rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(path)
+        .to_lowercase()
+}
+
+/// フォルダ以下のフォルダとアーカイブファイルを収集（隠しフォルダとシンボリックリンクは除外）
+fn collect_locations(dir: &Path, depth: u32, max_depth: u32, locations: &mut Vec<Location>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    let mut image_names = HashSet::new();
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_lowercase();
+
+        if file_type.is_dir() {
+            if depth < max_depth && !name.starts_with('.') {
+                collect_locations(&path, depth + 1, max_depth, locations);
+            }
+        } else if file_type.is_file() {
+            if is_supported_image(&path) {
+                image_names.insert(name);
+            } else if archive::archive_kind(&path).is_some() {
+                locations.push(Location {
+                    path,
+                    name,
+                    image_names: HashSet::new(),
+                });
+            }
+        }
+    }
+
+    locations.push(Location {
+        path: dir.to_path_buf(),
+        name: dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default(),
+        image_names,
+    });
+}
+
+/// 候補を評価（画像がない場合は None）
+fn evaluate(card: &Card, known: &HashSet<String>, location: &Location) -> Option<RelinkCandidate> {
+    let folder_path = location.path.to_string_lossy().to_string();
+    let images = list_images(&folder_path, &card.scan_options, SortMode::default()).ok()?;
+    if images.is_empty() {
+        return None;
+    }
+
+    let name_matched = location.name == lowercase_name(&card.folder_path);
+    let matched_files = images
+        .iter()
+        .filter(|image| known.contains(&image.filename.to_lowercase()))
+        .count() as u32;
+    let name_score = if name_matched { 1.0 } else { 0.0 };
+    let score = if known.is_empty() {
+        name_score
+    } else {
+        0.4 * name_score + 0.6 * (matched_files as f64 / known.len() as f64)
+    };
+
+    Some(RelinkCandidate {
+        folder_path,
+        score,
+        name_matched,
+        matched_files,
+        image_count: images.len() as u32,
+    })
+}
+
+/// 見つからないカードの移動先の候補を探す
+fn propose(card: &Card, locations: &[Location], linked: &HashSet<&Path>) -> RelinkProposal {
+    let old_name = lowercase_name(&card.folder_path);
+    let mut known: HashSet<String> = indexed_relative_paths(&card.folder_path)
+        .iter()
+        .map(|path| lowercase_name(path))
+        .collect();
+    if let Some(relative) = card
+        .thumbnail
+        .as_deref()
+        .and_then(|thumbnail| relative_to(thumbnail, &card.folder_path))
+    {
+        known.insert(lowercase_name(relative));
+    }
+
+    // フォルダ名か直下の画像のファイル名が一致するものだけを詳しく調べる
+    let mut candidates: Vec<RelinkCandidate> = locations
+        .iter()
+        .filter(|location| !linked.contains(location.path.as_path()))
+        .filter(|location| location.name == old_name || !location.image_names.is_disjoint(&known))
+        .filter_map(|location| evaluate(card, &known, location))
+        .collect();
+
+    // 一致度が同じなら画像数が以前の画像数に近いものを優先
+    let known_files = known.len() as u32;
+    candidates.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| {
+                a.image_count
+                    .abs_diff(known_files)
+                    .cmp(&b.image_count.abs_diff(known_files))
+            })
+            .then_with(|| a.folder_path.cmp(&b.folder_path))
+    });
+    candidates.truncate(MAX_CANDIDATES);
+
+    let suggested_folder_path = match candidates.as_slice() {
+        [best, rest @ ..]
+            if best.score >= SUGGEST_THRESHOLD
+                && rest.first().is_none_or(|second| second.score < best.score) =>
+        {
+            Some(best.folder_path.clone())
+        }
+        _ => None,
+    };
+
+    RelinkProposal {
+        card_id: card.id.clone(),
+        old_folder_path: card.folder_path.clone(),
+        known_files,
+        candidates,
+        suggested_folder_path,
+    }
+}
+
+/// 見つからないカードについて、指定したフォルダ以下から移動先の候補を探す
+///
+/// 他のカードが参照しているフォルダは候補から除外する。
+/// フォルダの走査はブロッキング処理用のスレッドで行う。
+#[tauri::command]
+pub async fn find_relink_candidates(
+    cards: Vec<Card>,
+    search_root: String,
+    max_depth: Option<u32>,
+) -> Result<Vec<RelinkProposal>, String> {
+    run_blocking(move || find_candidates(&cards, &search_root, max_depth)).await
+}
+
+/// 移動先の候補を探す（find_relink_candidates の本体）
+fn find_candidates(
+    cards: &[Card],
+    search_root: &str,
+    max_depth: Option<u32>,
+) -> Result<Vec<RelinkProposal>, String> {
+    let root = Path::new(search_root);
+    if !root.is_dir() {
+        return Err(format!("フォルダが見つかりません: {}", search_root));
+    }
+
+    let mut checker = ImageChecker::default();
+    let missing: Vec<&Card> = cards
+        .iter()
+        .filter(|card| card_status(card, &mut checker).status == CardStatus::Missing)
+        .collect();
+    if missing.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut locations = Vec::new();
+    collect_locations(
+        root,
+        0,
+        max_depth.unwrap_or(DEFAULT_MAX_DEPTH),
+        &mut locations,
+    );
+    let linked: HashSet<&Path> = cards
+        .iter()
+        .map(|card| Path::new(card.folder_path.as_str()))
+        .collect();
+
+    Ok(missing
+        .into_iter()
+        .map(|card| propose(card, &locations, &linked))
+        .collect())
+}
+
+/// フォルダからの相対パスを新しいフォルダ以下のパスにする（アーカイブはエントリのパス）
+fn relinked_path(relative: &str, new_folder: &str) -> String {
+    let new_path = Path::new(new_folder);
+    if archive::is_archive_file(new_path) {
+        archive::member_path(new_path, relative)
+    } else {
+        new_path.join(relative).to_string_lossy().to_string()
+    }
+}
+
+/// サムネイルの相対パスを新しいフォルダ以下に付け替える（付け替え先に画像がなければ None）
+fn relink_thumbnail(
+    relative: &str,
+    new_folder: &str,
+    checker: &mut ImageChecker,
+) -> Option<String> {
+    let relinked = relinked_path(relative, new_folder);
+    checker.exists(&relinked).then_some(relinked)
+}
+
+/// アルバムの画像のパスを、付け替えたカードの新しいフォルダ以下に付け替える
+///
+/// 元のフォルダが入れ子になっている場合は最も深いフォルダを基準にする。
+/// 付け替え先に画像がない場合も付け替える（元のパスはすでに見つからないため）。
+fn relink_album_item(path: &str, moves: &[(&str, &str)]) -> Option<String> {
+    moves
+        .iter()
+        .filter_map(|(old_folder, new_folder)| {
+            relative_to(path, old_folder)
+                .filter(|relative| !relative.is_empty())
+                .map(|relative| (old_folder.len(), relative, *new_folder))
+        })
+        .max_by_key(|(depth, _, _)| *depth)
+        .map(|(_, relative, new_folder)| relinked_path(relative, new_folder))
+}
+
+/// カードのフォルダを一括で付け替え、更新後のプロファイルを返す（保存はしない）
+///
+/// サムネイルが元のフォルダ内の画像だった場合は新しいフォルダ内の同じ画像に付け替え、
+/// 見つからなければ解除する。アルバムの画像のうち元のフォルダ内の画像も付け替える。
+#[tauri::command]
+pub fn apply_card_relinks(
+    mut profile: ProfileData,
+    relinks: Vec<CardRelink>,
+) -> Result<ProfileData, String> {
+    let relinks: HashMap<&str, &str> = relinks
+        .iter()
+        .map(|relink| (relink.card_id.as_str(), relink.folder_path.as_str()))
+        .collect();
+
+    for (card_id, folder_path) in &relinks {
+        if !profile.cards.iter().any(|card| card.id == *card_id) {
+            return Err(format!("カードが見つかりません: {}", card_id));
+        }
+        let path = Path::new(folder_path);
+        if !path.is_dir() && !archive::is_archive_file(path) {
+            return Err(format!("フォルダが見つかりません: {}", folder_path));
+        }
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut checker = ImageChecker::default();
+    let mut moves: Vec<(String, &str)> = Vec::new();
+    for card in profile.cards.iter_mut() {
+        let Some(folder_path) = relinks.get(card.id.as_str()) else {
+            continue;
+        };
+        if let Some(thumbnail) = card.thumbnail.take() {
+            card.thumbnail = match relative_to(&thumbnail, &card.folder_path) {
+                Some(relative) => relink_thumbnail(relative, folder_path, &mut checker),
+                None => Some(thumbnail),
+            };
+        }
+        let old_folder = std::mem::replace(&mut card.folder_path, folder_path.to_string());
+        moves.push((old_folder, folder_path));
+        card.updated_at = now.clone();
+    }
+
+    let moves: Vec<(&str, &str)> = moves
+        .iter()
+        .map(|(old_folder, new_folder)| (old_folder.as_str(), *new_folder))
+        .collect();
+    for album in profile.albums.iter_mut() {
+        let mut changed = false;
+        for item in album.items.iter_mut() {
+            if let Some(path) = relink_album_item(&item.path, &moves) {
+                item.path = path;
+                changed = true;
+            }
+        }
+        if changed {
+            // 付け替えで同じ画像になった場合は先のものを残す
+            let mut seen = HashSet::new();
+            album.items.retain(|item| seen.insert(item.path.clone()));
+            album.updated_at = now.clone();
+        }
+    }
+
+    profile.updated_at = now;
+    Ok(profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Album, AlbumItem};
+
+    /// テスト用の一時ディレクトリを作成
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "image-folder-viewer-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// テスト用のカードを作成
+    fn card(id: &str, folder_path: &str, thumbnail: Option<String>) -> Card {
+        Card {
+            id: id.to_string(),
+            title: id.to_string(),
+            folder_path: folder_path.to_string(),
+            thumbnail,
+            sort_order: 0,
+            created_at: String::new(),
+            updated_at: String::new(),
+            scan_options: Default::default(),
+            sort_mode: SortMode::default(),
+        }
+    }
+
+    /// テスト用のアルバムの画像を作成
+    fn album_item(path: &str) -> AlbumItem {
+        AlbumItem {
+            path: path.to_string(),
+            added_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn relinks_album_items_with_card_folder() {
+        let root = temp_dir("relink-album");
+        let old_folder = root.join("old");
+        let new_folder = root.join("new");
+        fs::create_dir_all(new_folder.join("sub")).unwrap();
+        fs::write(new_folder.join("a.png"), b"").unwrap();
+        fs::write(new_folder.join("sub").join("b.png"), b"").unwrap();
+        let old_path = |relative: &str| old_folder.join(relative).to_string_lossy().to_string();
+        let new_path = |relative: &str| new_folder.join(relative).to_string_lossy().to_string();
+        let other = root
+            .join("other")
+            .join("c.png")
+            .to_string_lossy()
+            .to_string();
+
+        let mut profile = ProfileData::default();
+        profile.cards.push(card(
+            "card",
+            &old_folder.to_string_lossy(),
+            Some(old_path("a.png")),
+        ));
+        profile.albums.push(Album {
+            id: "album".to_string(),
+            title: "album".to_string(),
+            items: vec![
+                album_item(&old_path("sub/b.png")),
+                album_item(&other),
+                album_item(&old_path("a.png")),
+                album_item(&new_path("a.png")),
+            ],
+            sort_order: 0,
+            created_at: String::new(),
+            updated_at: String::new(),
+        });
+
+        let profile = apply_card_relinks(
+            profile,
+            vec![CardRelink {
+                card_id: "card".to_string(),
+                folder_path: new_folder.to_string_lossy().to_string(),
+            }],
+        )
+        .unwrap();
+
+        let card = &profile.cards[0];
+        assert_eq!(card.folder_path, new_folder.to_string_lossy());
+        assert_eq!(card.thumbnail, Some(new_path("a.png")));
+        let album = &profile.albums[0];
+        let paths: Vec<&str> = album.items.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, [new_path("sub/b.png"), other, new_path("a.png")]);
+        assert!(!album.updated_at.is_empty());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
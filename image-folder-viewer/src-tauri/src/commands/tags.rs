@@ -0,0 +1,279 @@
+// タグの管理
+//
+// プロファイルのタグと、カードとタグの関連付けを編集し、タグの条件式でカードを検索する。
+// 編集コマンドは編集中のプロファイルを受け取り、更新後のプロファイルを返す（保存はしない）。
+
+use crate::models::{Card, CardTag, ProfileData, Tag, TagExpression};
+use std::collections::HashSet;
+
+/// エラーメッセージに含めるIDの最大数
+const MAX_REPORTED_IDS: usize = 5;
+
+/// IDの一覧をエラーメッセージ用に整形
+pub(crate) fn format_ids<'a>(ids: impl IntoIterator<Item = &'a str>) -> String {
+    let ids: Vec<_> = ids.into_iter().collect();
+    let mut text = ids
+        .iter()
+        .take(MAX_REPORTED_IDS)
+        .copied()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if ids.len() > MAX_REPORTED_IDS {
+        text.push_str(&format!(" ほか{}件", ids.len() - MAX_REPORTED_IDS));
+    }
+    text
+}
+
+/// カードとタグの関連付けが存在するカード・タグのみを参照しているかを検証（読み込み時に使用）
+pub(crate) fn validate_tag_references(profile: &ProfileData) -> Result<(), String> {
+    let card_ids: HashSet<&str> = profile.cards.iter().map(|c| c.id.as_str()).collect();
+    let tag_ids: HashSet<&str> = profile.tags.iter().map(|t| t.id.as_str()).collect();
+
+    let mut missing_cards: Vec<&str> = Vec::new();
+    let mut missing_tags: Vec<&str> = Vec::new();
+    for card_tag in &profile.card_tags {
+        if !card_ids.contains(card_tag.card_id.as_str())
+            && !missing_cards.contains(&card_tag.card_id.as_str())
+        {
+            missing_cards.push(&card_tag.card_id);
+        }
+        if !tag_ids.contains(card_tag.tag_id.as_str())
+            && !missing_tags.contains(&card_tag.tag_id.as_str())
+        {
+            missing_tags.push(&card_tag.tag_id);
+        }
+    }
+
+    if !missing_cards.is_empty() {
+        return Err(format!(
+            "タグ付けが存在しないカードを参照しています: {}",
+            format_ids(missing_cards)
+        ));
+    }
+    if !missing_tags.is_empty() {
+        return Err(format!(
+            "タグ付けが存在しないタグを参照しています: {}",
+            format_ids(missing_tags)
+        ));
+    }
+    Ok(())
+}
+
+/// タグ名を検証して前後の空白を除く（空の名前・他のタグと重複する名前はエラー）
+fn validate_name(tags: &[Tag], name: &str, exclude_id: Option<&str>) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("タグ名を入力してください".to_string());
+    }
+    let duplicated = tags
+        .iter()
+        .filter(|tag| Some(tag.id.as_str()) != exclude_id)
+        .any(|tag| tag.name.to_lowercase() == name.to_lowercase());
+    if duplicated {
+        return Err(format!("同じ名前のタグがあります: {}", name));
+    }
+    Ok(name.to_string())
+}
+
+/// 色を検証（"#RGB" または "#RRGGBB" 形式、空の場合は色なし）
+fn validate_color(color: Option<String>) -> Result<Option<String>, String> {
+    let Some(color) = color
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+    else {
+        return Ok(None);
+    };
+    let valid = color.strip_prefix('#').is_some_and(|hex| {
+        matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    });
+    if !valid {
+        return Err(format!(
+            "色の形式が不正です（#RRGGBB 形式で指定してください）: {}",
+            color
+        ));
+    }
+    Ok(Some(color.to_lowercase()))
+}
+
+/// タグを取得
+fn find_tag<'a>(profile: &'a mut ProfileData, tag_id: &str) -> Result<&'a mut Tag, String> {
+    profile
+        .tags
+        .iter_mut()
+        .find(|tag| tag.id == tag_id)
+        .ok_or_else(|| format!("タグが見つかりません: {}", tag_id))
+}
+
+/// 存在しないカード・タグを指定していないかを確認
+fn check_ids(profile: &ProfileData, card_ids: &[String], tag_ids: &[String]) -> Result<(), String> {
+    let missing_cards: Vec<&str> = card_ids
+        .iter()
+        .filter(|id| !profile.cards.iter().any(|card| card.id == **id))
+        .map(String::as_str)
+        .collect();
+    if !missing_cards.is_empty() {
+        return Err(format!(
+            "カードが見つかりません: {}",
+            format_ids(missing_cards)
+        ));
+    }
+    let missing_tags: Vec<&str> = tag_ids
+        .iter()
+        .filter(|id| !profile.tags.iter().any(|tag| tag.id == **id))
+        .map(String::as_str)
+        .collect();
+    if !missing_tags.is_empty() {
+        return Err(format!(
+            "タグが見つかりません: {}",
+            format_ids(missing_tags)
+        ));
+    }
+    Ok(())
+}
+
+/// 更新日時を現在時刻にする
+fn touch(mut profile: ProfileData) -> ProfileData {
+    profile.updated_at = chrono::Utc::now().to_rfc3339();
+    profile
+}
+
+/// タグを作成し、更新後のプロファイルを返す（作成したタグは末尾に追加される）
+#[tauri::command]
+pub fn create_tag(
+    mut profile: ProfileData,
+    name: String,
+    color: Option<String>,
+) -> Result<ProfileData, String> {
+    let name = validate_name(&profile.tags, &name, None)?;
+    let color = validate_color(color)?;
+    profile.tags.push(Tag {
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        color,
+    });
+    Ok(touch(profile))
+}
+
+/// タグ名を変更し、更新後のプロファイルを返す
+#[tauri::command]
+pub fn rename_tag(
+    mut profile: ProfileData,
+    tag_id: String,
+    name: String,
+) -> Result<ProfileData, String> {
+    let name = validate_name(&profile.tags, &name, Some(&tag_id))?;
+    find_tag(&mut profile, &tag_id)?.name = name;
+    Ok(touch(profile))
+}
+
+/// タグの色を変更し、更新後のプロファイルを返す（None で色なし）
+#[tauri::command]
+pub fn set_tag_color(
+    mut profile: ProfileData,
+    tag_id: String,
+    color: Option<String>,
+) -> Result<ProfileData, String> {
+    let color = validate_color(color)?;
+    find_tag(&mut profile, &tag_id)?.color = color;
+    Ok(touch(profile))
+}
+
+/// タグを削除し、更新後のプロファイルを返す
+///
+/// カード・画像へのタグ付けも削除し、スマートコレクションの条件式のタグは一致しない条件に置き換える。
+#[tauri::command]
+pub fn delete_tag(mut profile: ProfileData, tag_id: String) -> Result<ProfileData, String> {
+    find_tag(&mut profile, &tag_id)?;
+    profile.tags.retain(|tag| tag.id != tag_id);
+    profile
+        .card_tags
+        .retain(|card_tag| card_tag.tag_id != tag_id);
+    for annotation in profile.image_annotations.iter_mut() {
+        annotation.tag_ids.retain(|id| *id != tag_id);
+    }
+    profile.image_annotations.retain(|a| !a.is_empty());
+    for collection in profile.smart_collections.iter_mut() {
+        collection
+            .query
+            .remap_tag_ids(&|id| (id != tag_id).then(|| id.to_string()));
+    }
+    Ok(touch(profile))
+}
+
+/// 複数のカードに複数のタグを付け、更新後のプロファイルを返す（付いているタグは無視）
+#[tauri::command]
+pub fn attach_tags(
+    mut profile: ProfileData,
+    card_ids: Vec<String>,
+    tag_ids: Vec<String>,
+) -> Result<ProfileData, String> {
+    check_ids(&profile, &card_ids, &tag_ids)?;
+
+    let mut existing: HashSet<(String, String)> = profile
+        .card_tags
+        .iter()
+        .map(|ct| (ct.card_id.clone(), ct.tag_id.clone()))
+        .collect();
+    for card_id in &card_ids {
+        for tag_id in &tag_ids {
+            if existing.insert((card_id.clone(), tag_id.clone())) {
+                profile.card_tags.push(CardTag {
+                    card_id: card_id.clone(),
+                    tag_id: tag_id.clone(),
+                });
+            }
+        }
+    }
+    Ok(touch(profile))
+}
+
+/// 複数のカードから複数のタグを外し、更新後のプロファイルを返す
+#[tauri::command]
+pub fn detach_tags(
+    mut profile: ProfileData,
+    card_ids: Vec<String>,
+    tag_ids: Vec<String>,
+) -> Result<ProfileData, String> {
+    check_ids(&profile, &card_ids, &tag_ids)?;
+
+    let card_ids: HashSet<&str> = card_ids.iter().map(String::as_str).collect();
+    let tag_ids: HashSet<&str> = tag_ids.iter().map(String::as_str).collect();
+    profile.card_tags.retain(|ct| {
+        !(card_ids.contains(ct.card_id.as_str()) && tag_ids.contains(ct.tag_id.as_str()))
+    });
+    Ok(touch(profile))
+}
+
+/// タグの条件式に一致するカードを取得（sortOrder順）
+#[tauri::command]
+pub fn find_cards_by_tags(
+    profile: ProfileData,
+    expression: TagExpression,
+) -> Result<Vec<Card>, String> {
+    let mut referenced = Vec::new();
+    expression.collect_tag_ids(&mut referenced);
+    let missing: Vec<&str> = referenced
+        .into_iter()
+        .filter(|id| !profile.tags.iter().any(|tag| tag.id == *id))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("タグが見つかりません: {}", format_ids(missing)));
+    }
+
+    let mut cards: Vec<Card> = profile
+        .cards
+        .iter()
+        .filter(|card| {
+            let tags: HashSet<&str> = profile
+                .card_tags
+                .iter()
+                .filter(|ct| ct.card_id == card.id)
+                .map(|ct| ct.tag_id.as_str())
+                .collect();
+            expression.matches(&tags)
+        })
+        .cloned()
+        .collect();
+    cards.sort_by_key(|card| card.sort_order);
+    Ok(cards)
+}
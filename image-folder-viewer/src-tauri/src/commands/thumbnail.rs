@@ -0,0 +1,482 @@
+// サムネイル関連コマンド
+//
+// サムネイルはメモリキャッシュ → ディスクキャッシュ → 生成の順に取得する。
+// 透過部分のある画像はPNG、それ以外はJPEGでエンコードする。
+// アニメーションサムネイルを要求された場合、アニメーション画像はアニメーションGIFで返す。
+// フロントエンドへは thumb:// プロトコル経由でバイト列をそのまま返し、
+// IPC で Base64 文字列を送らずに済むようにする（get_thumbnail は互換用）。
+
+use super::animation::encode_animated_thumbnail;
+use super::images::{load_image, source_file_path};
+use super::thumbnail_cache::{
+    apply_disk_cache_config, disk_cache_slot, record_lookup, try_load_from_disk, try_save_to_disk,
+    CacheLookup, DISK_CACHE_VERSION,
+};
+use crate::models::AppConfig;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::DynamicImage;
+use lru::LruCache;
+use once_cell::sync::Lazy;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tauri::http::{header, Request, Response, StatusCode, Uri};
+use tauri::AppHandle;
+
+/// サムネイル配信用のURIスキーム名（フロントエンドでは convertFileSrc(path, "thumb") で参照）
+pub const THUMBNAIL_PROTOCOL: &str = "thumb";
+
+/// サムネイルサイズの既定値（クエリで size が指定されない場合）
+const DEFAULT_THUMBNAIL_SIZE: u32 = 200;
+
+/// メモリキャッシュの上限サイズの既定値（MB、設定の読み込み前に使用）
+const DEFAULT_MEMORY_CACHE_MB: u64 = 64;
+
+/// JPEG品質の既定値（設定の読み込み前に使用）
+const DEFAULT_JPEG_QUALITY: u8 = 85;
+
+/// JPEGサムネイルの品質（1〜100）
+static JPEG_QUALITY: AtomicU8 = AtomicU8::new(DEFAULT_JPEG_QUALITY);
+
+/// サムネイルのエンコード形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ThumbnailEncoding {
+    Jpeg,
+    Png,
+    /// アニメーションサムネイル
+    Gif,
+}
+
+impl ThumbnailEncoding {
+    pub(crate) const ALL: [ThumbnailEncoding; 3] = [
+        ThumbnailEncoding::Jpeg,
+        ThumbnailEncoding::Png,
+        ThumbnailEncoding::Gif,
+    ];
+
+    /// ディスクキャッシュのファイル拡張子
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            ThumbnailEncoding::Jpeg => "jpg",
+            ThumbnailEncoding::Png => "png",
+            ThumbnailEncoding::Gif => "gif",
+        }
+    }
+
+    pub(crate) fn mime_type(self) -> &'static str {
+        match self {
+            ThumbnailEncoding::Jpeg => "image/jpeg",
+            ThumbnailEncoding::Png => "image/png",
+            ThumbnailEncoding::Gif => "image/gif",
+        }
+    }
+}
+
+/// エンコード済みのサムネイル
+pub(crate) struct Thumbnail {
+    pub bytes: Vec<u8>,
+    pub encoding: ThumbnailEncoding,
+}
+
+/// メモリキャッシュのキー（画像パス, サイズ, アニメーションサムネイルか）
+type CacheKey = (String, u32, bool);
+
+/// サムネイルのメモリキャッシュ（パス+サイズ → サムネイル）
+static THUMBNAIL_CACHE: Lazy<Mutex<ThumbnailCache>> = Lazy::new(|| {
+    Mutex::new(ThumbnailCache::new(
+        (DEFAULT_MEMORY_CACHE_MB * 1024 * 1024) as usize,
+    ))
+});
+
+/// 元画像のサイズと更新日時（変更検出用、アーカイブ内の画像はアーカイブ自体）
+type SourceStamp = (u64, SystemTime);
+
+/// メモリキャッシュのエントリ
+struct CachedThumbnail {
+    thumbnail: Arc<Thumbnail>,
+    /// 生成時の元画像の状態
+    stamp: SourceStamp,
+}
+
+/// 合計バイト数で上限を設けたLRUキャッシュ（取得・追加・削除は O(1)）
+struct ThumbnailCache {
+    entries: LruCache<CacheKey, CachedThumbnail>,
+    total_bytes: usize,
+    max_bytes: usize,
+}
+
+impl ThumbnailCache {
+    fn new(max_bytes: usize) -> Self {
+        Self {
+            entries: LruCache::unbounded(),
+            total_bytes: 0,
+            max_bytes,
+        }
+    }
+
+    /// 取得（最近使用したものとして扱う）。元画像が変わっていればエントリを破棄して None
+    fn get(&mut self, key: &CacheKey, stamp: SourceStamp) -> Option<Arc<Thumbnail>> {
+        let cached = self.entries.get(key)?;
+        if cached.stamp == stamp {
+            return Some(cached.thumbnail.clone());
+        }
+        self.remove(key);
+        None
+    }
+
+    fn insert(&mut self, key: CacheKey, thumbnail: Arc<Thumbnail>, stamp: SourceStamp) {
+        // 上限より大きいものは格納しない
+        if thumbnail.bytes.len() > self.max_bytes {
+            self.remove(&key);
+            return;
+        }
+        self.total_bytes += thumbnail.bytes.len();
+        if let Some(old) = self.entries.put(key, CachedThumbnail { thumbnail, stamp }) {
+            self.total_bytes -= old.thumbnail.bytes.len();
+        }
+        self.shrink();
+    }
+
+    fn remove(&mut self, key: &CacheKey) {
+        if let Some(old) = self.entries.pop(key) {
+            self.total_bytes -= old.thumbnail.bytes.len();
+        }
+    }
+
+    /// 上限を超えている間、最も長く使われていないエントリから削除
+    fn shrink(&mut self) {
+        while self.total_bytes > self.max_bytes {
+            match self.entries.pop_lru() {
+                Some((_, old)) => self.total_bytes -= old.thumbnail.bytes.len(),
+                None => break,
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.total_bytes = 0;
+    }
+}
+
+/// メモリキャッシュの使用状況
+pub(crate) struct MemoryCacheUsage {
+    pub entries: usize,
+    pub bytes: usize,
+    pub max_bytes: usize,
+}
+
+/// サムネイル関連の設定を反映（起動時と設定保存時に呼び出す）
+///
+/// ディスクキャッシュの上限サイズと保持期間もここで反映する。
+/// JPEG品質が変わった場合はメモリキャッシュを破棄する
+/// （ディスクキャッシュは品質ごとに別ファイルになる）。
+pub fn apply_thumbnail_config(config: &AppConfig) {
+    let quality = config.thumbnail_jpeg_quality.clamp(1, 100);
+    let quality_changed = JPEG_QUALITY.swap(quality, Ordering::Relaxed) != quality;
+    if let Ok(mut cache) = THUMBNAIL_CACHE.lock() {
+        if quality_changed {
+            cache.clear();
+        }
+        // 設定ファイルの値が大きすぎる場合も溢れないよう、上限で打ち切る
+        cache.max_bytes =
+            usize::try_from(config.thumbnail_memory_cache_mb.saturating_mul(1024 * 1024))
+                .unwrap_or(usize::MAX);
+        cache.shrink();
+    }
+    apply_disk_cache_config(config);
+}
+
+/// メモリキャッシュの使用状況を取得
+pub(crate) fn memory_cache_usage() -> MemoryCacheUsage {
+    match THUMBNAIL_CACHE.lock() {
+        Ok(cache) => MemoryCacheUsage {
+            entries: cache.entries.len(),
+            bytes: cache.total_bytes,
+            max_bytes: cache.max_bytes,
+        },
+        Err(_) => MemoryCacheUsage {
+            entries: 0,
+            bytes: 0,
+            max_bytes: 0,
+        },
+    }
+}
+
+/// 指定したファイルを元画像とするサムネイルをメモリキャッシュから削除
+///
+/// アーカイブファイルを指定した場合はアーカイブ内の画像のサムネイルも削除する。
+pub(crate) fn invalidate_memory_cache(source_path: &Path) {
+    let Ok(mut cache) = THUMBNAIL_CACHE.lock() else {
+        return;
+    };
+    let stale: Vec<CacheKey> = cache
+        .entries
+        .iter()
+        .filter(|(key, _)| source_file_path(&key.0) == source_path)
+        .map(|(key, _)| key.clone())
+        .collect();
+    for key in stale {
+        cache.remove(&key);
+    }
+}
+
+/// メモリキャッシュをすべて削除
+pub(crate) fn clear_memory_cache() {
+    if let Ok(mut cache) = THUMBNAIL_CACHE.lock() {
+        cache.clear();
+    }
+}
+
+/// 元画像のサイズと更新日時を取得（存在しない場合は None）
+fn source_stamp(image_path: &str) -> Option<SourceStamp> {
+    let meta = fs::metadata(source_file_path(image_path)).ok()?;
+    Some((
+        meta.len(),
+        meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+    ))
+}
+
+/// 実際に透過しているピクセルがあるかを判定（アルファチャンネルがあっても全て不透明なら false）
+fn has_transparency(image: &DynamicImage) -> bool {
+    image.color().has_alpha() && image.to_rgba8().pixels().any(|p| p[3] < u8::MAX)
+}
+
+/// 画像をエンコード（透過があればPNG、なければJPEG）
+pub(crate) fn encode_thumbnail(image: &DynamicImage, jpeg_quality: u8) -> Result<Thumbnail, String> {
+    let mut bytes = Vec::new();
+    let (result, encoding) = if has_transparency(image) {
+        (
+            image
+                .to_rgba8()
+                .write_with_encoder(PngEncoder::new(&mut bytes)),
+            ThumbnailEncoding::Png,
+        )
+    } else {
+        (
+            image
+                .to_rgb8()
+                .write_with_encoder(JpegEncoder::new_with_quality(&mut bytes, jpeg_quality)),
+            ThumbnailEncoding::Jpeg,
+        )
+    };
+    result.map_err(|e| format!("サムネイルのエンコードに失敗しました: {}", e))?;
+    Ok(Thumbnail { bytes, encoding })
+}
+
+/// サムネイルを生成
+///
+/// animated が true でアニメーション画像の場合はアニメーションGIF、それ以外は静止画。
+fn generate_thumbnail(
+    image_path: &str,
+    size: u32,
+    jpeg_quality: u8,
+    animated: bool,
+) -> Result<Thumbnail, String> {
+    if animated {
+        if let Some(bytes) = encode_animated_thumbnail(image_path, size)? {
+            return Ok(Thumbnail {
+                bytes,
+                encoding: ThumbnailEncoding::Gif,
+            });
+        }
+    }
+    let img = load_image(image_path)?;
+    encode_thumbnail(&img.thumbnail(size, size), jpeg_quality)
+}
+
+/// サムネイルを取得（メモリ+ディスクキャッシュ付き）
+pub(crate) fn load_thumbnail(
+    app: &AppHandle,
+    image_path: &str,
+    size: u32,
+    animated: bool,
+) -> Result<Arc<Thumbnail>, String> {
+    let cache_key = (image_path.to_string(), size, animated);
+    let jpeg_quality = JPEG_QUALITY.load(Ordering::Relaxed);
+
+    let stamp = source_stamp(image_path)
+        .ok_or_else(|| format!("画像ファイルが見つかりません: {}", image_path))?;
+
+    // [1] メモリキャッシュを確認（元画像が変わっていれば破棄される）
+    {
+        let mut cache = THUMBNAIL_CACHE
+            .lock()
+            .map_err(|e| format!("キャッシュロックエラー: {}", e))?;
+        if let Some(cached) = cache.get(&cache_key, stamp) {
+            record_lookup(CacheLookup::MemoryHit);
+            return Ok(cached);
+        }
+    }
+
+    // [2] ディスクキャッシュを確認
+    let disk_cache_slot = disk_cache_slot(app, image_path, size, jpeg_quality, animated);
+
+    if let Some(ref slot) = disk_cache_slot {
+        if let Some(thumbnail) = try_load_from_disk(slot) {
+            record_lookup(CacheLookup::DiskHit);
+            // ディスクキャッシュヒット → メモリキャッシュにも格納
+            let thumbnail = Arc::new(thumbnail);
+            let mut cache = THUMBNAIL_CACHE
+                .lock()
+                .map_err(|e| format!("キャッシュロックエラー: {}", e))?;
+            cache.insert(cache_key, thumbnail.clone(), stamp);
+            return Ok(thumbnail);
+        }
+    }
+
+    // [3] サムネイル生成
+    record_lookup(CacheLookup::Miss);
+    let thumbnail = Arc::new(generate_thumbnail(
+        image_path,
+        size,
+        jpeg_quality,
+        animated,
+    )?);
+
+    // ディスクキャッシュに保存
+    if let Some(ref slot) = disk_cache_slot {
+        try_save_to_disk(slot, &thumbnail);
+    }
+
+    // メモリキャッシュに格納
+    {
+        let mut cache = THUMBNAIL_CACHE
+            .lock()
+            .map_err(|e| format!("キャッシュロックエラー: {}", e))?;
+        cache.insert(cache_key, thumbnail.clone(), stamp);
+    }
+
+    Ok(thumbnail)
+}
+
+/// サムネイル画像を生成してBase64 DataURLで返す（互換用、通常は thumb:// プロトコルを使用）
+#[tauri::command]
+pub fn get_thumbnail(
+    app: AppHandle,
+    image_path: String,
+    size: u32,
+    animated: Option<bool>,
+) -> Result<String, String> {
+    let thumbnail = load_thumbnail(&app, &image_path, size, animated.unwrap_or(false))?;
+
+    // Base64エンコードしてDataURLとして返す
+    let base64_str = STANDARD.encode(thumbnail.bytes.as_slice());
+    Ok(format!(
+        "data:{};base64,{}",
+        thumbnail.encoding.mime_type(),
+        base64_str
+    ))
+}
+
+/// 元画像の更新日時とサイズから ETag を生成
+fn source_etag(image_path: &str) -> Option<String> {
+    let meta = fs::metadata(source_file_path(image_path)).ok()?;
+    let modified = meta
+        .modified()
+        .ok()?
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .ok()?;
+    Some(format!(
+        "\"{:x}-{:x}-{:x}-{:x}\"",
+        modified.as_nanos(),
+        meta.len(),
+        DISK_CACHE_VERSION,
+        JPEG_QUALITY.load(Ordering::Relaxed)
+    ))
+}
+
+/// テキスト本文のエラーレスポンスを生成
+pub(crate) fn error_response(status: StatusCode, message: String) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(message.into_bytes())
+        .unwrap_or_default()
+}
+
+/// プロトコルのURLのパス部分から画像パスを取得
+pub(crate) fn request_image_path(uri: &Uri) -> Option<String> {
+    let encoded_path = uri.path().trim_start_matches('/');
+    percent_encoding::percent_decode_str(encoded_path)
+        .decode_utf8()
+        .ok()
+        .filter(|path| !path.is_empty())
+        .map(|path| path.to_string())
+}
+
+/// thumb:// プロトコルのリクエストを処理
+///
+/// URLは "thumb://localhost/<URLエンコードした画像パス>?size=<サイズ>[&animated=1]" 形式
+/// （Windows では "http://thumb.localhost/..."）。
+/// 元画像が変わっていなければ If-None-Match に対して 304 を返す。
+pub fn handle_thumbnail_request(app: &AppHandle, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let uri = request.uri();
+    let Some(image_path) = request_image_path(uri) else {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            format!("画像パスが不正です: {}", uri),
+        );
+    };
+
+    let query_value = |name: &str| {
+        uri.query().and_then(|query| {
+            query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value)
+        })
+    };
+    let size = query_value("size")
+        .and_then(|value| value.parse::<u32>().ok())
+        .filter(|size| *size > 0)
+        .unwrap_or(DEFAULT_THUMBNAIL_SIZE);
+    let animated = query_value("animated").is_some_and(|value| value == "1" || value == "true");
+
+    let etag = source_etag(&image_path);
+    if let Some(ref etag) = etag {
+        let not_modified = request
+            .headers()
+            .get(header::IF_NONE_MATCH)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value == etag);
+        if not_modified {
+            return Response::builder()
+                .status(StatusCode::NOT_MODIFIED)
+                .header(header::ETAG, etag.as_str())
+                .body(Vec::new())
+                .unwrap_or_default();
+        }
+    }
+
+    let thumbnail = match load_thumbnail(app, &image_path, size, animated) {
+        Ok(thumbnail) => thumbnail,
+        Err(e) => {
+            let status = if source_file_path(&image_path).exists() {
+                StatusCode::INTERNAL_SERVER_ERROR
+            } else {
+                StatusCode::NOT_FOUND
+            };
+            return error_response(status, e);
+        }
+    };
+
+    let mut builder = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, thumbnail.encoding.mime_type())
+        .header(header::CONTENT_LENGTH, thumbnail.bytes.len())
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        // URLに元画像の更新状態を含まないため、毎回 ETag で再検証させる
+        .header(header::CACHE_CONTROL, "no-cache");
+    if let Some(etag) = etag {
+        builder = builder.header(header::ETAG, etag);
+    }
+
+    builder.body(thumbnail.bytes.clone()).unwrap_or_default()
+}
@@ -0,0 +1,431 @@
+// サムネイルディスクキャッシュの管理
+//
+// キャッシュは app_cache_dir()/thumbnails に保存し、ファイル名は元画像のパスではなく
+// 内容キー（content_index 参照）から計算する。フォルダの移動・リネームや更新日時のみの変更では
+// 既存のサムネイルをそのまま使い、内容が変わった場合は別のファイル名になる。
+// サムネイルごとに内容キーを記録したサイドカーファイル（.src）を置く。
+// ヒット時にファイルの更新日時を現在時刻に更新し、これを最終アクセス日時として
+// 上限サイズ超過時の LRU 削除と保持期間による削除に使う。
+
+use super::content_index::{content_key, prune_content_index};
+use super::thumbnail::{clear_memory_cache, memory_cache_usage, Thumbnail, ThumbnailEncoding};
+use crate::models::AppConfig;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager};
+
+/// サムネイルディスクキャッシュのサブディレクトリ名
+const THUMBNAIL_CACHE_DIR: &str = "thumbnails";
+
+/// 内容キーを記録するサイドカーファイルの拡張子
+const SOURCE_SIDECAR_EXTENSION: &str = "src";
+
+/// 上限超過時に削除後の目標とする使用率（上限の90%まで削除）
+const EVICTION_TARGET_RATIO: f64 = 0.9;
+
+/// ディスクキャッシュの形式バージョン
+/// （サムネイルの生成方法を変えた場合に上げ、古いキャッシュを再利用しないようにする）
+/// 2: EXIFの Orientation を適用
+/// 3: ファイル名を元画像のパスではなく内容キーから計算
+/// 4: 透過画像をPNGで保存し、JPEG品質をファイル名に含める
+pub(crate) const DISK_CACHE_VERSION: u32 = 4;
+
+/// ディスクキャッシュの合計サイズ（None は未集計）
+static DISK_CACHE_BYTES: Lazy<Mutex<Option<u64>>> = Lazy::new(|| Mutex::new(None));
+
+/// ディスクキャッシュの上限サイズ（バイト、0 は無制限）と保持期間（日、0 は無期限）
+/// （サムネイルの保存ごとに設定ファイルを読まないよう、起動時と設定保存時に反映する）
+static DISK_CACHE_MAX_BYTES: AtomicU64 = AtomicU64::new(0);
+static DISK_CACHE_MAX_AGE_DAYS: AtomicU32 = AtomicU32::new(0);
+
+/// キャッシュ参照結果の集計（起動後の累計）
+static MEMORY_HITS: AtomicU64 = AtomicU64::new(0);
+static DISK_HITS: AtomicU64 = AtomicU64::new(0);
+static MISSES: AtomicU64 = AtomicU64::new(0);
+
+/// キャッシュ参照結果
+pub(crate) enum CacheLookup {
+    MemoryHit,
+    DiskHit,
+    Miss,
+}
+
+/// キャッシュ参照結果を記録
+pub(crate) fn record_lookup(lookup: CacheLookup) {
+    let counter = match lookup {
+        CacheLookup::MemoryHit => &MEMORY_HITS,
+        CacheLookup::DiskHit => &DISK_HITS,
+        CacheLookup::Miss => &MISSES,
+    };
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+/// ディスクキャッシュの上限サイズと保持期間を反映（apply_thumbnail_config から呼び出す）
+pub(crate) fn apply_disk_cache_config(config: &AppConfig) {
+    DISK_CACHE_MAX_BYTES.store(
+        config.thumbnail_cache_max_mb.saturating_mul(1024 * 1024),
+        Ordering::Relaxed,
+    );
+    DISK_CACHE_MAX_AGE_DAYS.store(config.thumbnail_cache_max_age_days, Ordering::Relaxed);
+}
+
+/// ディスクキャッシュのファイル名（拡張子なし）を計算（SHA-256ハッシュ）
+///
+/// 拡張子はエンコード形式（.jpg / .png / .gif）によって決まる。
+fn disk_cache_stem(content_key: &str, size: u32, jpeg_quality: u8, animated: bool) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(
+        format!(
+            "v{}:{}:{}:q{}{}",
+            DISK_CACHE_VERSION,
+            content_key,
+            size,
+            jpeg_quality,
+            if animated { ":anim" } else { "" }
+        )
+        .as_bytes(),
+    );
+    let hash = hasher.finalize();
+    format!("{:x}", hash)
+}
+
+/// ファイルの拡張子からサムネイルのエンコード形式を判定（サムネイル以外は None）
+fn thumbnail_encoding(path: &Path) -> Option<ThumbnailEncoding> {
+    let ext = path.extension()?.to_str()?;
+    ThumbnailEncoding::ALL
+        .into_iter()
+        .find(|encoding| encoding.extension() == ext)
+}
+
+/// ディスクキャッシュのベースディレクトリを取得
+fn get_cache_dir(app: &AppHandle) -> Option<PathBuf> {
+    app.path()
+        .app_cache_dir()
+        .ok()
+        .map(|dir| dir.join(THUMBNAIL_CACHE_DIR))
+}
+
+/// サムネイルのディスクキャッシュ上の保存先
+pub(crate) struct DiskCacheSlot {
+    /// 拡張子なしのパス
+    base_path: PathBuf,
+    content_key: String,
+}
+
+/// サムネイルのディスクキャッシュ上の保存先を取得（元画像が存在しない場合は None）
+pub(crate) fn disk_cache_slot(
+    app: &AppHandle,
+    image_path: &str,
+    size: u32,
+    jpeg_quality: u8,
+    animated: bool,
+) -> Option<DiskCacheSlot> {
+    let cache_dir = get_cache_dir(app)?;
+    let content_key = content_key(image_path)?;
+    Some(DiskCacheSlot {
+        base_path: cache_dir.join(disk_cache_stem(&content_key, size, jpeg_quality, animated)),
+        content_key,
+    })
+}
+
+/// ディスクキャッシュからサムネイルを読み込み（ヒット時は最終アクセス日時を更新）
+pub(crate) fn try_load_from_disk(slot: &DiskCacheSlot) -> Option<Thumbnail> {
+    ThumbnailEncoding::ALL.into_iter().find_map(|encoding| {
+        let path = slot.base_path.with_extension(encoding.extension());
+        let bytes = fs::read(&path).ok()?;
+        if let Ok(file) = fs::File::options().write(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        Some(Thumbnail { bytes, encoding })
+    })
+}
+
+/// サムネイルをディスクキャッシュに保存し、上限を超えた場合は古いものから削除
+pub(crate) fn try_save_to_disk(slot: &DiskCacheSlot, thumbnail: &Thumbnail) {
+    let Some(cache_dir) = slot.base_path.parent() else {
+        return;
+    };
+    let bytes = &thumbnail.bytes;
+    let _ = fs::create_dir_all(cache_dir);
+    let path = slot
+        .base_path
+        .with_extension(thumbnail.encoding.extension());
+    // 既存のファイルを上書きする場合は、その分を合計サイズから差し引く
+    let replaced_bytes = fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0);
+    if fs::write(&path, bytes).is_err() {
+        return;
+    }
+    let _ = fs::write(
+        slot.base_path.with_extension(SOURCE_SIDECAR_EXTENSION),
+        &slot.content_key,
+    );
+
+    let total = match DISK_CACHE_BYTES.lock() {
+        Ok(mut tally) => {
+            let total = match *tally {
+                Some(total) => total.saturating_sub(replaced_bytes) + bytes.len() as u64,
+                // 未集計の場合は保存したファイルを含めて集計
+                None => total_size(&list_entries(cache_dir)),
+            };
+            *tally = Some(total);
+            total
+        }
+        Err(_) => return,
+    };
+
+    let max_bytes = DISK_CACHE_MAX_BYTES.load(Ordering::Relaxed);
+    if max_bytes > 0 && total > max_bytes {
+        evict(cache_dir, max_bytes, 0);
+    }
+}
+
+/// キャッシュ内のサムネイルファイル
+struct CacheEntry {
+    path: PathBuf,
+    bytes: u64,
+    /// 最終アクセス日時（ファイルの更新日時）
+    accessed: SystemTime,
+}
+
+/// キャッシュディレクトリ内のサムネイルファイルを列挙（サイドカーなどは含まない）
+fn list_entries(cache_dir: &Path) -> Vec<CacheEntry> {
+    let Ok(entries) = fs::read_dir(cache_dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| thumbnail_encoding(&entry.path()).is_some())
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            meta.is_file().then(|| CacheEntry {
+                path: entry.path(),
+                bytes: meta.len(),
+                accessed: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            })
+        })
+        .collect()
+}
+
+/// 合計サイズを計算
+fn total_size(entries: &[CacheEntry]) -> u64 {
+    entries.iter().map(|entry| entry.bytes).sum()
+}
+
+/// サムネイルとサイドカーを削除
+fn remove_entry(entry: &CacheEntry) -> bool {
+    let removed = fs::remove_file(&entry.path).is_ok();
+    let _ = fs::remove_file(entry.path.with_extension(SOURCE_SIDECAR_EXTENSION));
+    removed
+}
+
+/// キャッシュ整理の結果
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThumbnailCacheCleanup {
+    /// 削除したサムネイル数
+    pub removed_files: u64,
+    /// 解放したバイト数
+    pub freed_bytes: u64,
+}
+
+impl ThumbnailCacheCleanup {
+    fn add(&mut self, entry: &CacheEntry) {
+        self.removed_files += 1;
+        self.freed_bytes += entry.bytes;
+    }
+}
+
+/// 保持期間を過ぎたサムネイルと、上限サイズを超えた分の古いサムネイルを削除
+///
+/// max_bytes / max_age_days が 0 の場合はその条件で削除しない。
+fn evict(cache_dir: &Path, max_bytes: u64, max_age_days: u32) -> ThumbnailCacheCleanup {
+    let mut entries = list_entries(cache_dir);
+    let mut cleanup = ThumbnailCacheCleanup::default();
+
+    // 保持期間による削除
+    if max_age_days > 0 {
+        let max_age = Duration::from_secs(u64::from(max_age_days) * 24 * 60 * 60);
+        let now = SystemTime::now();
+        entries.retain(|entry| {
+            let expired = now
+                .duration_since(entry.accessed)
+                .is_ok_and(|age| age > max_age);
+            if expired && remove_entry(entry) {
+                cleanup.add(entry);
+                return false;
+            }
+            true
+        });
+    }
+
+    // 上限サイズによる削除（最終アクセスが古い順）
+    let mut total = total_size(&entries);
+    if max_bytes > 0 && total > max_bytes {
+        let target = (max_bytes as f64 * EVICTION_TARGET_RATIO) as u64;
+        entries.sort_by_key(|entry| entry.accessed);
+        for entry in &entries {
+            if total <= target {
+                break;
+            }
+            if remove_entry(entry) {
+                total = total.saturating_sub(entry.bytes);
+                cleanup.add(entry);
+            }
+        }
+    }
+
+    if let Ok(mut tally) = DISK_CACHE_BYTES.lock() {
+        *tally = Some(total);
+    }
+
+    cleanup
+}
+
+/// 設定に従ってキャッシュの上限サイズと保持期間を適用（起動時、設定の反映後に呼び出す）
+pub fn run_thumbnail_cache_maintenance(app: &AppHandle) -> ThumbnailCacheCleanup {
+    let Some(cache_dir) = get_cache_dir(app) else {
+        return ThumbnailCacheCleanup::default();
+    };
+    evict(
+        &cache_dir,
+        DISK_CACHE_MAX_BYTES.load(Ordering::Relaxed),
+        DISK_CACHE_MAX_AGE_DAYS.load(Ordering::Relaxed),
+    )
+}
+
+/// サムネイルキャッシュの統計情報
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThumbnailCacheStats {
+    /// ディスクキャッシュのサムネイル数
+    pub file_count: u64,
+    /// ディスクキャッシュの合計サイズ（バイト）
+    pub total_bytes: u64,
+    /// ディスクキャッシュの上限サイズ（バイト、0 は無制限）
+    pub max_bytes: u64,
+    /// メモリキャッシュのサムネイル数
+    pub memory_entries: u64,
+    /// メモリキャッシュの合計サイズ（バイト）
+    pub memory_bytes: u64,
+    /// メモリキャッシュの上限サイズ（バイト）
+    pub memory_max_bytes: u64,
+    /// 起動後のメモリキャッシュのヒット数
+    pub memory_hits: u64,
+    /// 起動後のディスクキャッシュのヒット数
+    pub disk_hits: u64,
+    /// 起動後のキャッシュミス（新規生成）数
+    pub misses: u64,
+    /// ヒット率（0.0〜1.0、参照がない場合は 0.0）
+    pub hit_ratio: f64,
+}
+
+/// サムネイルキャッシュの統計情報を取得
+#[tauri::command]
+pub fn get_thumbnail_cache_stats(app: AppHandle) -> Result<ThumbnailCacheStats, String> {
+    let entries = get_cache_dir(&app)
+        .map(|dir| list_entries(&dir))
+        .unwrap_or_default();
+    let total_bytes = total_size(&entries);
+    if let Ok(mut tally) = DISK_CACHE_BYTES.lock() {
+        *tally = Some(total_bytes);
+    }
+
+    let memory_hits = MEMORY_HITS.load(Ordering::Relaxed);
+    let disk_hits = DISK_HITS.load(Ordering::Relaxed);
+    let misses = MISSES.load(Ordering::Relaxed);
+    let lookups = memory_hits + disk_hits + misses;
+    let memory = memory_cache_usage();
+
+    Ok(ThumbnailCacheStats {
+        file_count: entries.len() as u64,
+        total_bytes,
+        max_bytes: DISK_CACHE_MAX_BYTES.load(Ordering::Relaxed),
+        memory_entries: memory.entries as u64,
+        memory_bytes: memory.bytes as u64,
+        memory_max_bytes: memory.max_bytes as u64,
+        memory_hits,
+        disk_hits,
+        misses,
+        hit_ratio: if lookups > 0 {
+            (memory_hits + disk_hits) as f64 / lookups as f64
+        } else {
+            0.0
+        },
+    })
+}
+
+/// サムネイルキャッシュ（メモリ+ディスク）をすべて削除
+#[tauri::command]
+pub fn clear_thumbnail_cache(app: AppHandle) -> Result<ThumbnailCacheCleanup, String> {
+    clear_memory_cache();
+
+    let mut cleanup = ThumbnailCacheCleanup::default();
+    if let Some(cache_dir) = get_cache_dir(&app) {
+        for entry in list_entries(&cache_dir) {
+            if remove_entry(&entry) {
+                cleanup.add(&entry);
+            }
+        }
+        // 対応するサムネイルのないサイドカーも削除
+        if let Ok(entries) = fs::read_dir(&cache_dir) {
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+
+    if let Ok(mut tally) = DISK_CACHE_BYTES.lock() {
+        *tally = Some(0);
+    }
+
+    Ok(cleanup)
+}
+
+/// 元画像が存在しなくなったサムネイルを削除
+///
+/// 存在するどのパスからも参照されていない内容キーのサムネイルを削除する。
+/// 内容キーが記録されていない（旧形式の）サムネイルも削除対象とする。
+#[tauri::command]
+pub fn collect_thumbnail_cache_garbage(app: AppHandle) -> Result<ThumbnailCacheCleanup, String> {
+    let mut cleanup = ThumbnailCacheCleanup::default();
+    let Some(cache_dir) = get_cache_dir(&app) else {
+        return Ok(cleanup);
+    };
+
+    let live_keys = prune_content_index();
+    let entries = list_entries(&cache_dir);
+    for entry in &entries {
+        let is_live = fs::read_to_string(entry.path.with_extension(SOURCE_SIDECAR_EXTENSION))
+            .is_ok_and(|key| live_keys.contains(key.trim()));
+        if !is_live && remove_entry(entry) {
+            cleanup.add(entry);
+        }
+    }
+
+    // 対応するサムネイルのないサイドカーを削除
+    if let Ok(dir_entries) = fs::read_dir(&cache_dir) {
+        for entry in dir_entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            let is_sidecar =
+                path.extension().and_then(|ext| ext.to_str()) == Some(SOURCE_SIDECAR_EXTENSION);
+            let has_thumbnail = entries
+                .iter()
+                .any(|e| e.path.with_extension(SOURCE_SIDECAR_EXTENSION) == path);
+            if is_sidecar && !has_thumbnail {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+
+    if let Ok(mut tally) = DISK_CACHE_BYTES.lock() {
+        *tally = None;
+    }
+
+    Ok(cleanup)
+}
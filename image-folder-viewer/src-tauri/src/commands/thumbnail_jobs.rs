@@ -0,0 +1,312 @@
+// サムネイル一括生成ジョブ
+//
+// プロファイル全体やフォルダ内のサムネイルを、全ジョブで共有するワーカースレッドで並列に生成し、
+// 1件ごとに進捗イベントをフロントエンドへ送る。新しいジョブ（表示中のフォルダ）から順に処理し、
+// 表示中の項目はキューの先頭へ移動して優先的に処理する。画面遷移時にはキャンセルできる。
+
+use super::blocking::run_blocking;
+use super::images::list_images;
+use super::thumbnail::load_thumbnail;
+use crate::models::{ScanOptions, SortMode};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread;
+use tauri::{AppHandle, Emitter};
+
+/// 1件ごとの進捗イベント名
+const PROGRESS_EVENT: &str = "thumbnail-progress";
+
+/// ジョブ終了（完了・キャンセル）イベント名
+const FINISHED_EVENT: &str = "thumbnail-job-finished";
+
+/// ワーカースレッド数の上限（全ジョブで共有）
+const MAX_WORKERS: usize = 4;
+
+/// 実行中のジョブ（ジョブID → ジョブ）
+static THUMBNAIL_JOBS: Lazy<Mutex<HashMap<String, Arc<ThumbnailJob>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 全ジョブで共有するワーカープール
+static WORKER_POOL: Lazy<WorkerPool> = Lazy::new(WorkerPool::default);
+
+/// サムネイル生成ジョブ
+struct ThumbnailJob {
+    id: String,
+    app: AppHandle,
+    size: u32,
+    /// 未処理の画像パス（先頭から処理）
+    queue: Mutex<VecDeque<String>>,
+    total: usize,
+    completed: AtomicUsize,
+    /// 生成中の画像数（0 になり、キューが空なら終了）
+    in_flight: AtomicUsize,
+    cancelled: AtomicBool,
+    /// 終了イベントを送信済み
+    finished: AtomicBool,
+}
+
+/// 進捗イベントのペイロード
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ThumbnailProgress {
+    job_id: String,
+    image_path: String,
+    size: u32,
+    completed: usize,
+    total: usize,
+    /// 生成に失敗した場合のエラーメッセージ
+    error: Option<String>,
+}
+
+/// ジョブ終了イベントのペイロード
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ThumbnailJobFinished {
+    job_id: String,
+    completed: usize,
+    total: usize,
+    cancelled: bool,
+}
+
+impl ThumbnailJob {
+    /// キューから次の画像パスを取り出す（キャンセル済みなら None）
+    fn next(&self) -> Option<String> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return None;
+        }
+        let mut queue = self.queue.lock().ok()?;
+        let image_path = queue.pop_front()?;
+        // 終了判定と競合しないよう、キューのロック中に生成中として数える
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        Some(image_path)
+    }
+
+    /// 指定した画像パスをキューの先頭へ移動（指定順を維持）
+    fn prioritize(&self, image_paths: &[String]) {
+        let Ok(mut queue) = self.queue.lock() else {
+            return;
+        };
+        let wanted: HashSet<&String> = image_paths.iter().collect();
+        let (mut front, rest): (VecDeque<String>, VecDeque<String>) =
+            queue.drain(..).partition(|path| wanted.contains(path));
+        let order: HashMap<&String, usize> = image_paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| (path, i))
+            .collect();
+        front
+            .make_contiguous()
+            .sort_by_key(|path| order.get(path).copied().unwrap_or(usize::MAX));
+        front.extend(rest);
+        *queue = front;
+    }
+
+    /// サムネイルを1件生成して進捗を通知
+    fn process(&self, image_path: String) {
+        let error = load_thumbnail(&self.app, &image_path, self.size, false).err();
+        let completed = self.completed.fetch_add(1, Ordering::Relaxed) + 1;
+        let _ = self.app.emit(
+            PROGRESS_EVENT,
+            ThumbnailProgress {
+                job_id: self.id.clone(),
+                image_path,
+                size: self.size,
+                completed,
+                total: self.total,
+                error,
+            },
+        );
+        self.in_flight.fetch_sub(1, Ordering::AcqRel);
+        self.finish_if_done();
+    }
+
+    /// キューが空（またはキャンセル済み）で生成中の画像がなければ、ジョブを登録解除して終了を通知
+    fn finish_if_done(&self) {
+        {
+            let Ok(queue) = self.queue.lock() else {
+                return;
+            };
+            let drained = self.cancelled.load(Ordering::Relaxed) || queue.is_empty();
+            if !drained
+                || self.in_flight.load(Ordering::Acquire) > 0
+                || self.finished.swap(true, Ordering::AcqRel)
+            {
+                return;
+            }
+        }
+
+        if let Ok(mut jobs) = THUMBNAIL_JOBS.lock() {
+            jobs.remove(&self.id);
+        }
+        let _ = self.app.emit(
+            FINISHED_EVENT,
+            ThumbnailJobFinished {
+                job_id: self.id.clone(),
+                completed: self.completed.load(Ordering::Relaxed),
+                total: self.total,
+                cancelled: self.cancelled.load(Ordering::Relaxed),
+            },
+        );
+    }
+}
+
+/// ワーカープール（ワーカースレッドは最初のジョブの登録時に起動し、以後は待機して再利用する）
+#[derive(Default)]
+struct WorkerPool {
+    /// 未処理の画像が残っている可能性のあるジョブ（先頭のジョブから処理）
+    jobs: Mutex<VecDeque<Arc<ThumbnailJob>>>,
+    /// ジョブの登録を待機中のワーカーに通知
+    available: Condvar,
+    /// 起動済みのワーカー数
+    workers: AtomicUsize,
+}
+
+impl WorkerPool {
+    /// ジョブを先頭に登録し、必要ならワーカーを起動
+    fn submit(&'static self, job: Arc<ThumbnailJob>) {
+        self.jobs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push_front(job);
+        self.available.notify_all();
+
+        let target = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .clamp(1, MAX_WORKERS);
+        while self
+            .workers
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n < target).then_some(n + 1)
+            })
+            .is_ok()
+        {
+            thread::spawn(move || self.run_worker());
+        }
+    }
+
+    /// 次に生成する画像を取り出す（処理する画像がなければ待機）
+    fn next_task(&self) -> (Arc<ThumbnailJob>, String) {
+        let mut jobs = self.jobs.lock().unwrap_or_else(PoisonError::into_inner);
+        loop {
+            while let Some(job) = jobs.front().cloned() {
+                if let Some(image_path) = job.next() {
+                    return (job, image_path);
+                }
+                // キューが空・キャンセル済みのジョブは外す（生成中の画像がなければここで終了）
+                jobs.pop_front();
+                job.finish_if_done();
+            }
+            jobs = self
+                .available
+                .wait(jobs)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// ワーカースレッドの処理
+    fn run_worker(&self) {
+        loop {
+            let (job, image_path) = self.next_task();
+            job.process(image_path);
+        }
+    }
+}
+
+/// ジョブを登録してワーカープールに追加し、ジョブIDを返す
+fn spawn_job(app: AppHandle, image_paths: Vec<String>, size: u32) -> Result<String, String> {
+    // 同じ画像が複数回指定された場合は1回だけ生成する
+    let mut seen = HashSet::new();
+    let queue: VecDeque<String> = image_paths
+        .into_iter()
+        .filter(|path| seen.insert(path.clone()))
+        .collect();
+
+    let job = Arc::new(ThumbnailJob {
+        id: uuid::Uuid::new_v4().to_string(),
+        app,
+        size,
+        total: queue.len(),
+        queue: Mutex::new(queue),
+        completed: AtomicUsize::new(0),
+        in_flight: AtomicUsize::new(0),
+        cancelled: AtomicBool::new(false),
+        finished: AtomicBool::new(false),
+    });
+
+    THUMBNAIL_JOBS
+        .lock()
+        .map_err(|e| format!("ジョブ管理のロックに失敗しました: {}", e))?
+        .insert(job.id.clone(), job.clone());
+
+    WORKER_POOL.submit(job.clone());
+    Ok(job.id.clone())
+}
+
+/// 指定した画像のサムネイルをバックグラウンドで一括生成（プロファイル全体のカードサムネイル用）
+#[tauri::command]
+pub fn start_thumbnail_job(
+    app: AppHandle,
+    image_paths: Vec<String>,
+    size: u32,
+) -> Result<String, String> {
+    spawn_job(app, image_paths, size)
+}
+
+/// フォルダ内のすべての画像のサムネイルをバックグラウンドで一括生成
+///
+/// フォルダの走査・ソート（EXIF・画像サイズの読み込みを含む）はブロッキング処理用のスレッドで行う。
+#[tauri::command]
+pub async fn start_folder_thumbnail_job(
+    app: AppHandle,
+    folder_path: String,
+    options: Option<ScanOptions>,
+    sort: Option<SortMode>,
+    size: u32,
+) -> Result<String, String> {
+    let images = run_blocking(move || {
+        list_images(
+            &folder_path,
+            &options.unwrap_or_default(),
+            sort.unwrap_or_default(),
+        )
+    })
+    .await?;
+    spawn_job(
+        app,
+        images.into_iter().map(|image| image.path).collect(),
+        size,
+    )
+}
+
+/// 表示中の画像を優先的に処理するようキューを並べ替える
+#[tauri::command]
+pub fn prioritize_thumbnail_job(job_id: String, image_paths: Vec<String>) -> Result<(), String> {
+    let jobs = THUMBNAIL_JOBS
+        .lock()
+        .map_err(|e| format!("ジョブ管理のロックに失敗しました: {}", e))?;
+    // 既に終了したジョブは無視
+    if let Some(job) = jobs.get(&job_id) {
+        job.prioritize(&image_paths);
+    }
+    Ok(())
+}
+
+/// ジョブをキャンセル（処理中の画像は完了させ、残りは破棄）
+#[tauri::command]
+pub fn cancel_thumbnail_job(job_id: String) -> Result<(), String> {
+    let job = THUMBNAIL_JOBS
+        .lock()
+        .map_err(|e| format!("ジョブ管理のロックに失敗しました: {}", e))?
+        .get(&job_id)
+        .cloned();
+    if let Some(job) = job {
+        job.cancelled.store(true, Ordering::Relaxed);
+        // 生成中の画像がなければすぐに終了を通知
+        job.finish_if_done();
+    }
+    Ok(())
+}
@@ -0,0 +1,231 @@
+// フォルダ監視
+//
+// 表示中のカードのフォルダと、全カードのフォルダの有効性を監視し、
+// 変更をまとめて（デバウンスして）フロントエンドへイベントで通知する。
+// - "images-changed": 表示中のカードのフォルダで画像が追加・削除・変更された
+// - "card-status-changed": カードのフォルダの有効性が変わった
+// 監視は新しい監視を開始するか、解除コマンドを呼ぶまで続く。
+
+use super::archive;
+use super::blocking::run_blocking;
+use super::cards::card_status;
+use super::formats::is_supported_image;
+use super::images::ImageChecker;
+use super::thumbnail::invalidate_memory_cache;
+use crate::models::{Card, CardWithStatus, ScanOptions};
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// 画像一覧の変更イベント名
+const IMAGES_CHANGED_EVENT: &str = "images-changed";
+
+/// カードの有効性の変更イベント名
+const CARD_STATUS_CHANGED_EVENT: &str = "card-status-changed";
+
+/// 変更をまとめる待ち時間
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// 表示中のカードのフォルダの監視
+static FOLDER_WATCH: Lazy<Mutex<Option<Debouncer<RecommendedWatcher>>>> =
+    Lazy::new(|| Mutex::new(None));
+
+/// 全カードの有効性の監視
+static STATUS_WATCH: Lazy<Mutex<Option<Debouncer<RecommendedWatcher>>>> =
+    Lazy::new(|| Mutex::new(None));
+
+/// 画像一覧の変更イベントのペイロード
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImagesChanged {
+    card_id: String,
+    folder_path: String,
+    /// 変更されたパス（追加・削除・変更されたファイルやフォルダ）
+    changed_paths: Vec<String>,
+}
+
+/// 画像一覧に影響しうる変更かを判定
+///
+/// 画像ファイルとフォルダの変更を対象とする。削除されたパスは種類を判定できないため、
+/// 拡張子のないものはフォルダとみなす。
+fn affects_listing(path: &Path) -> bool {
+    is_supported_image(path) || path.is_dir() || (!path.exists() && path.extension().is_none())
+}
+
+/// デバウンサーを作成（監視エラーは無視して次のイベントを待つ）
+fn create_debouncer(
+    mut handler: impl FnMut(Vec<PathBuf>) + Send + 'static,
+) -> Result<Debouncer<RecommendedWatcher>, String> {
+    new_debouncer(DEBOUNCE_TIMEOUT, move |result: DebounceEventResult| {
+        if let Ok(events) = result {
+            handler(events.into_iter().map(|event| event.path).collect());
+        }
+    })
+    .map_err(|e| format!("フォルダ監視の開始に失敗しました: {}", e))
+}
+
+/// 表示中のカードのフォルダの監視を開始（以前の監視は解除される）
+///
+/// アーカイブファイルの場合はアーカイブファイル自体の変更を監視する。
+#[tauri::command]
+pub fn watch_card_folder(
+    app: AppHandle,
+    card_id: String,
+    folder_path: String,
+    options: Option<ScanOptions>,
+) -> Result<(), String> {
+    let root = PathBuf::from(&folder_path);
+    let is_archive = archive::is_archive_file(&root);
+    let (target, mode) = if is_archive {
+        let parent = root
+            .parent()
+            .ok_or_else(|| format!("フォルダが見つかりません: {}", folder_path))?
+            .to_path_buf();
+        (parent, RecursiveMode::NonRecursive)
+    } else if root.is_dir() {
+        let mode = if options.unwrap_or_default().recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        (root.clone(), mode)
+    } else {
+        return Err(format!("フォルダが見つかりません: {}", folder_path));
+    };
+
+    let mut debouncer = create_debouncer(move |paths| {
+        let changed: Vec<PathBuf> = paths
+            .into_iter()
+            .filter(|path| {
+                if is_archive {
+                    *path == root
+                } else {
+                    path != &root && affects_listing(path)
+                }
+            })
+            .collect();
+        if changed.is_empty() {
+            return;
+        }
+
+        for path in &changed {
+            invalidate_memory_cache(path);
+        }
+        let _ = app.emit(
+            IMAGES_CHANGED_EVENT,
+            ImagesChanged {
+                card_id: card_id.clone(),
+                folder_path: folder_path.clone(),
+                changed_paths: changed
+                    .iter()
+                    .map(|path| path.to_string_lossy().to_string())
+                    .collect(),
+            },
+        );
+    })?;
+    debouncer
+        .watcher()
+        .watch(&target, mode)
+        .map_err(|e| format!("フォルダ監視の開始に失敗しました: {}", e))?;
+
+    *FOLDER_WATCH
+        .lock()
+        .map_err(|e| format!("フォルダ監視のロックに失敗しました: {}", e))? = Some(debouncer);
+    Ok(())
+}
+
+/// 表示中のカードのフォルダの監視を解除
+#[tauri::command]
+pub fn unwatch_card_folder() -> Result<(), String> {
+    FOLDER_WATCH
+        .lock()
+        .map_err(|e| format!("フォルダ監視のロックに失敗しました: {}", e))?
+        .take();
+    Ok(())
+}
+
+/// 存在する最も近い祖先ディレクトリを取得（フォルダの作成・削除・リネームを検出する監視対象）
+fn nearest_existing_parent(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .find(|ancestor| ancestor.is_dir())
+        .map(Path::to_path_buf)
+}
+
+/// 変更されたパスがカードのフォルダに影響しうるかを判定
+///
+/// フォルダ自体・その祖先（作成・削除・リネーム）・フォルダ内のパスの変更を対象とする。
+fn affects_card(card: &Card, changed_paths: &[PathBuf]) -> bool {
+    let folder = Path::new(&card.folder_path);
+    changed_paths
+        .iter()
+        .any(|path| folder.starts_with(path) || path.starts_with(folder))
+}
+
+/// 全カードのフォルダの有効性の監視を開始し、現在の検証結果を返す（以前の監視は解除される）
+#[tauri::command]
+pub async fn watch_card_statuses(
+    app: AppHandle,
+    cards: Vec<Card>,
+) -> Result<Vec<CardWithStatus>, String> {
+    run_blocking(move || start_status_watch(app, cards)).await
+}
+
+/// 全カードを検証し、フォルダの有効性の監視を開始
+fn start_status_watch(app: AppHandle, cards: Vec<Card>) -> Result<Vec<CardWithStatus>, String> {
+    let mut checker = ImageChecker::default();
+    let statuses: Vec<CardWithStatus> = cards
+        .iter()
+        .map(|card| card_status(card, &mut checker))
+        .collect();
+    let targets: HashSet<PathBuf> = cards
+        .iter()
+        .filter_map(|card| nearest_existing_parent(Path::new(&card.folder_path)))
+        .collect();
+
+    // 変更されたパスに関係するカードのみ再検証し、結果が変わったカードのみ通知する
+    let last_statuses = Arc::new(Mutex::new(statuses.clone()));
+    let mut debouncer = create_debouncer(move |paths| {
+        let Ok(mut last_statuses) = last_statuses.lock() else {
+            return;
+        };
+        let mut checker = ImageChecker::default();
+        for last in last_statuses
+            .iter_mut()
+            .filter(|last| affects_card(&last.card, &paths))
+        {
+            let current = card_status(&last.card, &mut checker);
+            if current.status != last.status {
+                let _ = app.emit(CARD_STATUS_CHANGED_EVENT, current.clone());
+                *last = current;
+            }
+        }
+    })?;
+    // 監視できないディレクトリ（権限がないなど）は無視する
+    for target in &targets {
+        let _ = debouncer
+            .watcher()
+            .watch(target, RecursiveMode::NonRecursive);
+    }
+
+    *STATUS_WATCH
+        .lock()
+        .map_err(|e| format!("フォルダ監視のロックに失敗しました: {}", e))? = Some(debouncer);
+    Ok(statuses)
+}
+
+/// 全カードのフォルダの有効性の監視を解除
+#[tauri::command]
+pub fn unwatch_card_statuses() -> Result<(), String> {
+    STATUS_WATCH
+        .lock()
+        .map_err(|e| format!("フォルダ監視のロックに失敗しました: {}", e))?
+        .take();
+    Ok(())
+}
@@ -5,27 +5,92 @@ mod models;
 
 use tauri::Manager;
 use commands::{
+    handle_image_request,
+    handle_thumbnail_request,
+    init_archive_index_dir,
+    init_content_index,
     load_app_config,
+    run_thumbnail_cache_maintenance,
+    apply_thumbnail_config,
+    IMAGE_PROTOCOL,
+    THUMBNAIL_PROTOCOL,
     // プロファイル管理
+    acquire_profile_lock,
     create_new_profile,
+    list_profile_backups,
     load_profile,
+    merge_profile_changes,
+    release_profile_lock,
+    restore_profile_backup,
     save_profile,
+    // カード検証
+    validate_profile_cards,
+    // カードの再リンク
+    apply_card_relinks,
+    find_relink_candidates,
+    // タグ
+    attach_tags,
+    create_tag,
+    delete_tag,
+    detach_tags,
+    find_cards_by_tags,
+    rename_tag,
+    set_tag_color,
+    // 画像の注釈
+    list_annotated_images,
+    set_image_annotations,
+    // スマートコレクション
+    create_smart_collection,
+    delete_smart_collection,
+    list_smart_collection_images,
+    preview_smart_query,
+    update_smart_collection,
+    // アルバム
+    add_album_images,
+    create_album,
+    delete_album,
+    list_album_images,
+    remove_album_images,
+    rename_album,
+    reorder_album_images,
+    validate_albums,
     // アプリ共通設定
     add_recent_profile,
     get_app_config,
     remove_recent_profile,
     save_app_config,
     // ダイアログ
+    select_archive_file,
     select_folder,
     select_image_file,
     select_profile_file,
     select_profile_save_path,
     // 画像
+    export_animation_frame,
+    get_animation_info,
     get_first_image_in_folder,
+    get_image_info,
     get_images_in_folder,
+    get_images_info_in_folder,
+    get_supported_formats,
     get_thumbnail,
     validate_folder_path,
+    // サムネイル一括生成
+    cancel_thumbnail_job,
+    prioritize_thumbnail_job,
+    start_folder_thumbnail_job,
+    start_thumbnail_job,
+    // サムネイルキャッシュ
+    clear_thumbnail_cache,
+    collect_thumbnail_cache_garbage,
+    get_thumbnail_cache_stats,
+    // フォルダ監視
+    unwatch_card_folder,
+    unwatch_card_statuses,
+    watch_card_folder,
+    watch_card_statuses,
     // クリップボード
+    copy_animation_frame_to_clipboard,
     copy_image_to_clipboard,
     copy_text_to_clipboard,
 };
@@ -34,8 +99,23 @@ use commands::{
 pub fn run() {
     tauri::Builder::default()
         .setup(|app| {
-            // 起動時にウィンドウをフォアグラウンドに表示（設定で制御可能）
+            // TARアーカイブのインデックス保存先を設定
+            init_archive_index_dir(app.handle());
+            // 画像の内容キーのインデックス保存先を設定
+            init_content_index(app.handle());
+
             let config = load_app_config(app.handle());
+
+            // サムネイルのキャッシュの上限サイズ・保持期間とJPEG品質を設定
+            apply_thumbnail_config(&config);
+
+            // サムネイルのディスクキャッシュに上限サイズと保持期間を適用
+            let handle = app.handle().clone();
+            std::thread::spawn(move || {
+                run_thumbnail_cache_maintenance(&handle);
+            });
+
+            // 起動時にウィンドウをフォアグラウンドに表示（設定で制御可能）
             if config.focus_on_startup {
                 // Linux のフォーカス盗み防止対策：always_on_top で前面に出し、
                 // フォーカスを得た時点で解除する
@@ -52,6 +132,19 @@ pub fn run() {
             }
             Ok(())
         })
+        // サムネイル配信用のカスタムプロトコル（生成に時間がかかるため別スレッドで処理）
+        .register_asynchronous_uri_scheme_protocol(THUMBNAIL_PROTOCOL, |ctx, request, responder| {
+            let app = ctx.app_handle().clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                responder.respond(handle_thumbnail_request(&app, &request));
+            });
+        })
+        // 画像配信用のカスタムプロトコル（アーカイブ内の画像の表示に使用）
+        .register_asynchronous_uri_scheme_protocol(IMAGE_PROTOCOL, |_ctx, request, responder| {
+            tauri::async_runtime::spawn_blocking(move || {
+                responder.respond(handle_image_request(&request));
+            });
+        })
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .invoke_handler(tauri::generate_handler![
@@ -59,6 +152,42 @@ pub fn run() {
             load_profile,
             save_profile,
             create_new_profile,
+            list_profile_backups,
+            restore_profile_backup,
+            acquire_profile_lock,
+            release_profile_lock,
+            merge_profile_changes,
+            // カード検証
+            validate_profile_cards,
+            // カードの再リンク
+            find_relink_candidates,
+            apply_card_relinks,
+            // タグ
+            create_tag,
+            rename_tag,
+            set_tag_color,
+            delete_tag,
+            attach_tags,
+            detach_tags,
+            find_cards_by_tags,
+            // 画像の注釈
+            set_image_annotations,
+            list_annotated_images,
+            // スマートコレクション
+            create_smart_collection,
+            update_smart_collection,
+            delete_smart_collection,
+            list_smart_collection_images,
+            preview_smart_query,
+            // アルバム
+            create_album,
+            rename_album,
+            delete_album,
+            add_album_images,
+            remove_album_images,
+            reorder_album_images,
+            list_album_images,
+            validate_albums,
             // アプリ共通設定
             get_app_config,
             save_app_config,
@@ -66,6 +195,7 @@ pub fn run() {
             remove_recent_profile,
             // ダイアログ
             select_folder,
+            select_archive_file,
             select_image_file,
             select_profile_file,
             select_profile_save_path,
@@ -74,8 +204,28 @@ pub fn run() {
             get_first_image_in_folder,
             get_images_in_folder,
             validate_folder_path,
+            get_supported_formats,
+            get_image_info,
+            get_images_info_in_folder,
+            get_animation_info,
+            export_animation_frame,
+            // サムネイル一括生成
+            start_thumbnail_job,
+            start_folder_thumbnail_job,
+            prioritize_thumbnail_job,
+            cancel_thumbnail_job,
+            // サムネイルキャッシュ
+            get_thumbnail_cache_stats,
+            clear_thumbnail_cache,
+            collect_thumbnail_cache_garbage,
+            // フォルダ監視
+            watch_card_folder,
+            unwatch_card_folder,
+            watch_card_statuses,
+            unwatch_card_statuses,
             // クリップボード
             copy_image_to_clipboard,
+            copy_animation_frame_to_clipboard,
             copy_text_to_clipboard,
         ])
         .run(tauri::generate_context!())
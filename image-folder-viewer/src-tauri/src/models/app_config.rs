@@ -1,9 +1,10 @@
 // アプリ共通設定関連のデータ構造
 
+use super::migration::{version_string, APP_CONFIG_VERSION};
 use serde::{Deserialize, Serialize};
 
 fn default_version() -> String {
-    "1.0".to_string()
+    version_string(APP_CONFIG_VERSION)
 }
 fn default_max_recent_profiles() -> i32 {
     10
@@ -14,6 +15,18 @@ fn default_theme() -> String {
 fn default_true() -> bool {
     true
 }
+fn default_thumbnail_cache_max_mb() -> u64 {
+    512
+}
+fn default_thumbnail_cache_max_age_days() -> u32 {
+    90
+}
+fn default_thumbnail_memory_cache_mb() -> u64 {
+    64
+}
+fn default_thumbnail_jpeg_quality() -> u8 {
+    85
+}
 
 /// 最近使用したプロファイル
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +60,18 @@ pub struct AppConfig {
     /// 起動時にウィンドウを最前面に表示する（Linux のフォーカス盗み防止対策）
     #[serde(default = "default_true")]
     pub focus_on_startup: bool,
+    /// サムネイルのディスクキャッシュの上限サイズ（MB、0 は無制限）
+    #[serde(default = "default_thumbnail_cache_max_mb")]
+    pub thumbnail_cache_max_mb: u64,
+    /// サムネイルのディスクキャッシュの保持期間（最終アクセスからの日数、0 は無期限）
+    #[serde(default = "default_thumbnail_cache_max_age_days")]
+    pub thumbnail_cache_max_age_days: u32,
+    /// サムネイルのメモリキャッシュの上限サイズ（MB）
+    #[serde(default = "default_thumbnail_memory_cache_mb")]
+    pub thumbnail_memory_cache_mb: u64,
+    /// JPEGサムネイルの品質（1〜100、透過画像のPNGサムネイルには影響しない）
+    #[serde(default = "default_thumbnail_jpeg_quality")]
+    pub thumbnail_jpeg_quality: u8,
 }
 
 impl Default for AppConfig {
@@ -57,6 +82,10 @@ impl Default for AppConfig {
             max_recent_profiles: default_max_recent_profiles(),
             theme: default_theme(),
             focus_on_startup: default_true(),
+            thumbnail_cache_max_mb: default_thumbnail_cache_max_mb(),
+            thumbnail_cache_max_age_days: default_thumbnail_cache_max_age_days(),
+            thumbnail_memory_cache_mb: default_thumbnail_memory_cache_mb(),
+            thumbnail_jpeg_quality: default_thumbnail_jpeg_quality(),
         }
     }
 }
@@ -0,0 +1,326 @@
+// スキーマのバージョン管理と移行
+//
+// プロファイルとアプリ共通設定は "version" に "<スキーマバージョン>.0" を記録する。
+// 読み込み時はデシリアライズ前の JSON に対して、記録されたバージョンから現在の
+// バージョンまでの移行処理を順に適用する。現在より新しいバージョンのファイルは
+// 読み込むと未知のフィールドが失われるため、開かずにエラーとする。
+//
+// スキーマを変更する場合は現在のバージョンを上げ、移行処理を追加する。
+
+use super::{AppConfig, ScanOptions, SortMode};
+use serde_json::{Map, Value};
+
+/// プロファイルの現在のスキーマバージョン
+/// 1: 初期バージョン
+/// 2: カードの走査オプション・ソート方法と、相対パスでの保存設定を追加
+/// 3: 画像ごとの注釈を追加
+/// 4: スマートコレクションを追加
+/// 5: アルバムを追加
+pub const PROFILE_VERSION: u32 = 5;
+
+/// アプリ共通設定の現在のスキーマバージョン
+/// 1: 初期バージョン
+/// 2: サムネイルキャッシュの設定を追加
+pub const APP_CONFIG_VERSION: u32 = 2;
+
+/// 1つ前のバージョンから移行する処理（JSON オブジェクトを直接書き換える）
+type Migration = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// プロファイルの移行処理（添字 n はバージョン n+1 → n+2）
+const PROFILE_MIGRATIONS: [Migration; PROFILE_VERSION as usize - 1] = [
+    profile_v1_to_v2,
+    profile_v2_to_v3,
+    profile_v3_to_v4,
+    profile_v4_to_v5,
+];
+
+/// アプリ共通設定の移行処理（添字 n はバージョン n+1 → n+2）
+const APP_CONFIG_MIGRATIONS: [Migration; APP_CONFIG_VERSION as usize - 1] = [app_config_v1_to_v2];
+
+/// バージョン番号を "version" に記録する文字列に変換
+pub fn version_string(version: u32) -> String {
+    format!("{}.0", version)
+}
+
+/// 記録されたバージョンを取得（"version" がない場合は 1 とみなす）
+pub fn schema_version(value: &Value) -> Result<u32, String> {
+    let version = match value.get("version") {
+        None | Some(Value::Null) => return Ok(1),
+        Some(Value::String(s)) => s.split('.').next().and_then(|major| major.parse().ok()),
+        Some(Value::Number(n)) => n.as_u64().and_then(|n| u32::try_from(n).ok()),
+        Some(_) => None,
+    };
+    version
+        .filter(|&version| version >= 1)
+        .ok_or_else(|| format!("バージョンの形式が不正です: {}", value["version"]))
+}
+
+/// 移行処理を順に適用して現在のバージョンにする
+fn migrate(
+    mut value: Value,
+    migrations: &[Migration],
+    current: u32,
+    kind: &str,
+) -> Result<Value, String> {
+    let version = schema_version(&value).map_err(|e| format!("{}の{}", kind, e))?;
+    if version > current {
+        return Err(format!(
+            "{}はこのアプリより新しいバージョン（{}）で作成されています",
+            kind,
+            version_string(version)
+        ));
+    }
+
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| format!("{}の形式が不正です", kind))?;
+    for (from, migration) in (1..).zip(migrations).skip(version as usize - 1) {
+        migration(object).map_err(|e| {
+            format!(
+                "{}の移行に失敗しました（バージョン {} → {}）: {}",
+                kind,
+                version_string(from),
+                version_string(from + 1),
+                e
+            )
+        })?;
+        object.insert(
+            "version".to_string(),
+            Value::String(version_string(from + 1)),
+        );
+    }
+
+    Ok(value)
+}
+
+/// プロファイルの JSON を現在のバージョンに移行
+pub fn migrate_profile(value: Value) -> Result<Value, String> {
+    migrate(value, &PROFILE_MIGRATIONS, PROFILE_VERSION, "プロファイル")
+}
+
+/// アプリ共通設定の JSON を現在のバージョンに移行
+pub fn migrate_app_config(value: Value) -> Result<Value, String> {
+    migrate(
+        value,
+        &APP_CONFIG_MIGRATIONS,
+        APP_CONFIG_VERSION,
+        "設定ファイル",
+    )
+}
+
+/// 値を JSON に変換
+fn to_json<T: serde::Serialize>(value: T) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| e.to_string())
+}
+
+/// 1 → 2: カードの走査オプション・ソート方法と、相対パスでの保存設定を既定値で追加
+fn profile_v1_to_v2(profile: &mut Map<String, Value>) -> Result<(), String> {
+    if let Some(Value::Array(cards)) = profile.get_mut("cards") {
+        for card in cards.iter_mut().filter_map(Value::as_object_mut) {
+            if !card.contains_key("scanOptions") {
+                card.insert("scanOptions".to_string(), to_json(ScanOptions::default())?);
+            }
+            if !card.contains_key("sortMode") {
+                card.insert("sortMode".to_string(), to_json(SortMode::default())?);
+            }
+        }
+    }
+    profile.entry("relativePaths").or_insert(Value::Bool(false));
+    Ok(())
+}
+
+/// 2 → 3: 画像ごとの注釈を空で追加
+fn profile_v2_to_v3(profile: &mut Map<String, Value>) -> Result<(), String> {
+    profile
+        .entry("imageAnnotations")
+        .or_insert(Value::Array(Vec::new()));
+    Ok(())
+}
+
+/// 3 → 4: スマートコレクションを空で追加
+fn profile_v3_to_v4(profile: &mut Map<String, Value>) -> Result<(), String> {
+    profile
+        .entry("smartCollections")
+        .or_insert(Value::Array(Vec::new()));
+    Ok(())
+}
+
+/// 4 → 5: アルバムを空で追加
+fn profile_v4_to_v5(profile: &mut Map<String, Value>) -> Result<(), String> {
+    profile.entry("albums").or_insert(Value::Array(Vec::new()));
+    Ok(())
+}
+
+/// 1 → 2: サムネイルキャッシュの設定を既定値で追加
+fn app_config_v1_to_v2(config: &mut Map<String, Value>) -> Result<(), String> {
+    let defaults = to_json(AppConfig::default())?;
+    for key in [
+        "thumbnailCacheMaxMb",
+        "thumbnailCacheMaxAgeDays",
+        "thumbnailMemoryCacheMb",
+        "thumbnailJpegQuality",
+    ] {
+        if !config.contains_key(key) {
+            config.insert(key.to_string(), defaults[key].clone());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ColorLabel, ProfileData, TagExpression};
+
+    const PROFILE_V1: &str = include_str!("../../tests/fixtures/profile_v1.json");
+    const PROFILE_V2: &str = include_str!("../../tests/fixtures/profile_v2.json");
+    const PROFILE_V3: &str = include_str!("../../tests/fixtures/profile_v3.json");
+    const PROFILE_V4: &str = include_str!("../../tests/fixtures/profile_v4.json");
+    const PROFILE_V5: &str = include_str!("../../tests/fixtures/profile_v5.json");
+    const PROFILE_FUTURE: &str = include_str!("../../tests/fixtures/profile_future.json");
+    const APP_CONFIG_V1: &str = include_str!("../../tests/fixtures/app_config_v1.json");
+    const APP_CONFIG_V2: &str = include_str!("../../tests/fixtures/app_config_v2.json");
+
+    fn parse(content: &str) -> Value {
+        serde_json::from_str(content).expect("fixture is valid JSON")
+    }
+
+    fn load_profile(content: &str) -> ProfileData {
+        let value = migrate_profile(parse(content)).expect("migration succeeds");
+        serde_json::from_value(value).expect("migrated profile deserializes")
+    }
+
+    #[test]
+    fn profile_v1_is_migrated_to_current_version() {
+        let profile = load_profile(PROFILE_V1);
+
+        assert_eq!(profile.version, version_string(PROFILE_VERSION));
+        assert_eq!(profile.cards.len(), 2);
+        assert_eq!(profile.cards[0].title, "Photos");
+        assert_eq!(profile.cards[1].folder_path, "/media/comics/vol1.cbz");
+        assert!(!profile.cards[0].scan_options.recursive);
+        assert!(!profile.relative_paths);
+        assert_eq!(profile.card_tags.len(), 1);
+        assert_eq!(profile.app_state.last_card_id.as_deref(), Some("card-1"));
+    }
+
+    #[test]
+    fn profile_v1_migration_adds_card_defaults() {
+        let value = migrate_profile(parse(PROFILE_V1)).unwrap();
+        let card = &value["cards"][0];
+
+        assert_eq!(
+            card["scanOptions"],
+            to_json(ScanOptions::default()).unwrap()
+        );
+        assert_eq!(card["sortMode"], to_json(SortMode::default()).unwrap());
+        assert_eq!(value["relativePaths"], Value::Bool(false));
+    }
+
+    #[test]
+    fn profile_v2_keeps_its_settings() {
+        let profile = load_profile(PROFILE_V2);
+        assert_eq!(profile.version, version_string(PROFILE_VERSION));
+        assert!(profile.relative_paths);
+        assert!(profile.cards[0].scan_options.recursive);
+        assert_eq!(profile.cards[0].scan_options.max_depth, Some(2));
+    }
+
+    #[test]
+    fn profile_v2_migration_adds_empty_annotations() {
+        let value = migrate_profile(parse(PROFILE_V2)).unwrap();
+        assert_eq!(value["imageAnnotations"], Value::Array(Vec::new()));
+    }
+
+    #[test]
+    fn profile_v3_keeps_its_annotations() {
+        let migrated = migrate_profile(parse(PROFILE_V3)).unwrap();
+        assert_eq!(migrated["smartCollections"], Value::Array(Vec::new()));
+
+        let profile = load_profile(PROFILE_V3);
+        assert_eq!(profile.image_annotations.len(), 1);
+        let annotation = &profile.image_annotations[0];
+        assert_eq!(annotation.relative_path, "2026/001.jpg");
+        assert_eq!(annotation.rating, 4);
+        assert!(annotation.favorite);
+        assert_eq!(annotation.color_label, Some(ColorLabel::Green));
+        assert_eq!(annotation.tag_ids, vec!["tag-1".to_string()]);
+    }
+
+    #[test]
+    fn profile_without_version_is_treated_as_v1() {
+        let mut value = parse(PROFILE_V1);
+        value.as_object_mut().unwrap().remove("version");
+
+        let migrated = migrate_profile(value).unwrap();
+        assert_eq!(migrated["version"], version_string(PROFILE_VERSION));
+        assert!(migrated["cards"][0].get("scanOptions").is_some());
+    }
+
+    #[test]
+    fn profile_from_newer_version_is_refused() {
+        let error = migrate_profile(parse(PROFILE_FUTURE)).unwrap_err();
+        assert!(error.contains("新しいバージョン"), "{}", error);
+        assert!(error.contains("99.0"), "{}", error);
+    }
+
+    #[test]
+    fn invalid_version_is_refused() {
+        let mut value = parse(PROFILE_V1);
+        value["version"] = Value::String("latest".to_string());
+        assert!(migrate_profile(value).is_err());
+    }
+
+    #[test]
+    fn profile_v4_keeps_its_smart_collections() {
+        let migrated = migrate_profile(parse(PROFILE_V4)).unwrap();
+        assert_eq!(migrated["albums"], Value::Array(Vec::new()));
+
+        let profile = load_profile(PROFILE_V4);
+        assert_eq!(profile.smart_collections.len(), 1);
+        let query = &profile.smart_collections[0].query;
+        assert_eq!(query.annotations.min_rating, Some(5));
+        assert_eq!(query.modified_within_days, Some(30));
+        assert!(matches!(
+            query.card_tags,
+            Some(TagExpression::Tag { ref tag_id }) if tag_id == "tag-1"
+        ));
+    }
+
+    #[test]
+    fn profile_v5_keeps_its_albums() {
+        let original = parse(PROFILE_V5);
+        assert_eq!(migrate_profile(original.clone()).unwrap(), original);
+
+        let profile = load_profile(PROFILE_V5);
+        assert_eq!(profile.albums.len(), 1);
+        let album = &profile.albums[0];
+        assert_eq!(album.title, "Review");
+        assert_eq!(album.items.len(), 2);
+        assert_eq!(album.items[1].path, "/media/comics/vol1.cbz!/p001.png");
+    }
+
+    #[test]
+    fn app_config_v1_is_migrated_to_current_version() {
+        let value = migrate_app_config(parse(APP_CONFIG_V1)).unwrap();
+        let config: AppConfig = serde_json::from_value(value).unwrap();
+
+        assert_eq!(config.version, version_string(APP_CONFIG_VERSION));
+        assert_eq!(config.recent_profiles.len(), 1);
+        assert_eq!(config.theme, "dark");
+        assert_eq!(
+            config.thumbnail_jpeg_quality,
+            AppConfig::default().thumbnail_jpeg_quality
+        );
+    }
+
+    #[test]
+    fn app_config_v2_keeps_its_settings() {
+        let original = parse(APP_CONFIG_V2);
+        assert_eq!(migrate_app_config(original.clone()).unwrap(), original);
+
+        let config: AppConfig = serde_json::from_value(original).unwrap();
+        assert_eq!(config.thumbnail_cache_max_mb, 1024);
+        assert_eq!(config.thumbnail_jpeg_quality, 70);
+    }
+}
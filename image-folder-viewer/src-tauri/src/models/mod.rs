@@ -1,7 +1,11 @@
 // モデル定義モジュール
 
 pub mod app_config;
+pub mod migration;
 pub mod profile;
+pub mod query;
 
 pub use app_config::*;
+pub use migration::*;
 pub use profile::*;
+pub use query::*;
@@ -1,7 +1,66 @@
 // プロファイル関連のデータ構造
+//
+// 後から追加した項目には #[serde(default)] を付けている。保存済みのプロファイルでは
+// 読み込み時の移行（migration.rs）で補われるが、フロントエンドで作成したカードなど、
+// コマンドに渡されるデータでは省略されることがあるため。
 
+use super::migration::{version_string, PROFILE_VERSION};
+use super::query::SmartCollection;
 use serde::{Deserialize, Serialize};
 
+/// フォルダ走査オプション（カードごとに保存）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanOptions {
+    /// サブフォルダを再帰的に走査する
+    #[serde(default)]
+    pub recursive: bool,
+    /// 再帰走査の最大深さ（None は無制限、0 は直下のみ）
+    #[serde(default)]
+    pub max_depth: Option<u32>,
+    /// シンボリックリンクをたどる（false の場合はスキップ）
+    #[serde(default)]
+    pub follow_symlinks: bool,
+}
+
+/// 画像一覧のソートキー
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SortKey {
+    /// ファイル名（数値部分を数値として比較する自然順）
+    #[default]
+    Name,
+    /// 更新日時
+    Modified,
+    /// 作成日時
+    Created,
+    /// ファイルサイズ
+    Size,
+    /// EXIF撮影日時
+    ExifDate,
+    /// 画像サイズ（ピクセル数）
+    Dimensions,
+}
+
+/// ソート方向
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// 画像一覧のソート方法（カードごとに保存）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SortMode {
+    #[serde(default)]
+    pub key: SortKey,
+    #[serde(default)]
+    pub direction: SortDirection,
+}
+
 /// カード情報
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -13,6 +72,31 @@ pub struct Card {
     pub sort_order: i32,
     pub created_at: String,
     pub updated_at: String,
+    /// フォルダ走査オプション
+    #[serde(default)]
+    pub scan_options: ScanOptions,
+    /// 画像一覧のソート方法
+    #[serde(default)]
+    pub sort_mode: SortMode,
+}
+
+/// カードのフォルダの状態
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CardStatus {
+    /// 画像を表示できる
+    #[default]
+    Ok,
+    /// フォルダが存在しない
+    Missing,
+    /// フォルダでもアーカイブファイルでもない
+    NotADirectory,
+    /// アクセス権限がない
+    PermissionDenied,
+    /// 画像が1枚もない
+    NoImages,
+    /// その他の理由で読み込めない
+    Unreadable,
 }
 
 /// カード（検証結果付き）
@@ -23,9 +107,18 @@ pub struct CardWithStatus {
     pub card: Card,
     pub is_valid: bool,
     pub error_message: Option<String>,
+    /// 状態の詳細
+    #[serde(default)]
+    pub status: CardStatus,
+    /// 画像数（フォルダを走査していない場合は None）
+    #[serde(default)]
+    pub image_count: Option<u32>,
+    /// サムネイルに指定した画像が見つからない
+    #[serde(default)]
+    pub thumbnail_missing: bool,
 }
 
-/// タグ情報（将来の拡張用）
+/// タグ情報
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Tag {
@@ -34,7 +127,7 @@ pub struct Tag {
     pub color: Option<String>,
 }
 
-/// カードとタグの関連（将来の拡張用）
+/// カードとタグの関連
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CardTag {
@@ -42,6 +135,68 @@ pub struct CardTag {
     pub tag_id: String,
 }
 
+/// 画像の色ラベル
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ColorLabel {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+    Gray,
+}
+
+/// 画像ごとの注釈（評価・お気に入り・色ラベル・タグ）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageAnnotation {
+    pub card_id: String,
+    /// カードのルートフォルダからの相対パス（区切り文字は "/"）
+    pub relative_path: String,
+    /// 評価（0〜5、0 は未評価）
+    #[serde(default)]
+    pub rating: u8,
+    #[serde(default)]
+    pub favorite: bool,
+    #[serde(default)]
+    pub color_label: Option<ColorLabel>,
+    /// 画像に付けたタグ（プロファイルのタグのID）
+    #[serde(default)]
+    pub tag_ids: Vec<String>,
+    pub updated_at: String,
+}
+
+impl ImageAnnotation {
+    /// 評価・お気に入り・色ラベル・タグのいずれも設定されていない
+    pub fn is_empty(&self) -> bool {
+        self.rating == 0 && !self.favorite && self.color_label.is_none() && self.tag_ids.is_empty()
+    }
+}
+
+/// アルバムの画像
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlbumItem {
+    /// 画像のパス（アーカイブ内の画像は "<アーカイブ>!/<エントリ名>"）
+    pub path: String,
+    pub added_at: String,
+}
+
+/// アルバム（複数のフォルダから選んだ画像を手動で並べたもの）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Album {
+    pub id: String,
+    pub title: String,
+    /// 画像（表示順）
+    pub items: Vec<AlbumItem>,
+    pub sort_order: i32,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
 /// ウィンドウ状態
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -98,17 +253,33 @@ pub struct ProfileData {
     pub tags: Vec<Tag>,
     pub card_tags: Vec<CardTag>,
     pub app_state: AppState,
+    /// カードのパスをプロファイルファイルのフォルダからの相対パスで保存する
+    #[serde(default)]
+    pub relative_paths: bool,
+    /// 画像ごとの注釈
+    #[serde(default)]
+    pub image_annotations: Vec<ImageAnnotation>,
+    /// スマートコレクション
+    #[serde(default)]
+    pub smart_collections: Vec<SmartCollection>,
+    /// アルバム
+    #[serde(default)]
+    pub albums: Vec<Album>,
 }
 
 impl Default for ProfileData {
     fn default() -> Self {
         Self {
-            version: "1.0".to_string(),
+            version: version_string(PROFILE_VERSION),
             updated_at: chrono::Utc::now().to_rfc3339(),
             cards: Vec::new(),
             tags: Vec::new(),
             card_tags: Vec::new(),
             app_state: AppState::default(),
+            relative_paths: false,
+            image_annotations: Vec::new(),
+            smart_collections: Vec::new(),
+            albums: Vec::new(),
         }
     }
 }
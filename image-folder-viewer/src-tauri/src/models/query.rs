@@ -0,0 +1,152 @@
+// 検索条件のデータ構造（タグの条件式・画像の注釈の条件・スマートコレクション）
+
+use super::{ColorLabel, ImageAnnotation, SortMode};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// タグの条件式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum TagExpression {
+    /// タグが付いている
+    #[serde(rename_all = "camelCase")]
+    Tag { tag_id: String },
+    /// すべての条件を満たす（空の場合は常に満たす）
+    And { operands: Vec<TagExpression> },
+    /// いずれかの条件を満たす（空の場合は満たさない）
+    Or { operands: Vec<TagExpression> },
+    /// 条件を満たさない
+    Not { operand: Box<TagExpression> },
+}
+
+impl TagExpression {
+    /// 付いているタグが条件を満たすかを判定
+    pub fn matches(&self, tag_ids: &HashSet<&str>) -> bool {
+        match self {
+            Self::Tag { tag_id } => tag_ids.contains(tag_id.as_str()),
+            Self::And { operands } => operands.iter().all(|e| e.matches(tag_ids)),
+            Self::Or { operands } => operands.iter().any(|e| e.matches(tag_ids)),
+            Self::Not { operand } => !operand.matches(tag_ids),
+        }
+    }
+
+    /// 条件式で参照しているタグIDを収集
+    pub fn collect_tag_ids<'a>(&'a self, ids: &mut Vec<&'a str>) {
+        match self {
+            Self::Tag { tag_id } => ids.push(tag_id),
+            Self::And { operands } | Self::Or { operands } => {
+                operands.iter().for_each(|e| e.collect_tag_ids(ids))
+            }
+            Self::Not { operand } => operand.collect_tag_ids(ids),
+        }
+    }
+
+    /// タグIDを付け替える
+    ///
+    /// map が None を返したタグ（削除されたタグ）の条件は、付いていないタグと同じく
+    /// 常に満たさない条件（空の Or）に置き換える。
+    pub fn remap_tag_ids(&mut self, map: &impl Fn(&str) -> Option<String>) {
+        match self {
+            Self::Tag { tag_id } => match map(tag_id) {
+                Some(id) => *tag_id = id,
+                None => {
+                    *self = Self::Or {
+                        operands: Vec::new(),
+                    }
+                }
+            },
+            Self::And { operands } | Self::Or { operands } => {
+                operands.iter_mut().for_each(|e| e.remap_tag_ids(map))
+            }
+            Self::Not { operand } => operand.remap_tag_ids(map),
+        }
+    }
+}
+
+/// 画像の注釈による絞り込み条件（省略した条件は判定しない）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageAnnotationFilter {
+    /// 評価の下限
+    #[serde(default)]
+    pub min_rating: Option<u8>,
+    #[serde(default)]
+    pub favorite: Option<bool>,
+    /// いずれかの色ラベルが付いている
+    #[serde(default)]
+    pub color_labels: Option<Vec<ColorLabel>>,
+    /// 画像のタグの条件式
+    #[serde(default)]
+    pub tags: Option<TagExpression>,
+}
+
+impl ImageAnnotationFilter {
+    /// 注釈が条件を満たすかを判定（注釈のない画像は未設定として判定する）
+    pub fn matches(&self, annotation: Option<&ImageAnnotation>) -> bool {
+        let rating = annotation.map_or(0, |a| a.rating);
+        let favorite = annotation.is_some_and(|a| a.favorite);
+        let color_label = annotation.and_then(|a| a.color_label);
+        let tag_ids: HashSet<&str> = annotation
+            .map(|a| a.tag_ids.iter().map(String::as_str).collect())
+            .unwrap_or_default();
+
+        self.min_rating.is_none_or(|min| rating >= min)
+            && self.favorite.is_none_or(|f| favorite == f)
+            && self
+                .color_labels
+                .as_ref()
+                .is_none_or(|labels| color_label.is_some_and(|l| labels.contains(&l)))
+            && self.tags.as_ref().is_none_or(|e| e.matches(&tag_ids))
+    }
+}
+
+/// スマートコレクションの検索条件（省略した条件は判定しない）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmartQuery {
+    /// 対象のカード（省略時はすべてのカード）
+    #[serde(default)]
+    pub card_ids: Option<Vec<String>>,
+    /// カードのタグの条件式
+    #[serde(default)]
+    pub card_tags: Option<TagExpression>,
+    /// 画像の注釈の条件
+    #[serde(default)]
+    pub annotations: ImageAnnotationFilter,
+    /// 更新日時の下限（ISO 8601形式）
+    #[serde(default)]
+    pub modified_after: Option<String>,
+    /// 直近の日数以内に更新された
+    #[serde(default)]
+    pub modified_within_days: Option<u32>,
+    /// 画像の並び順（省略時はカードの並び順に、カードごとのソート方法で並べる）
+    #[serde(default)]
+    pub sort: Option<SortMode>,
+    /// 最大件数
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+impl SmartQuery {
+    /// 条件式のタグIDを付け替える（TagExpression::remap_tag_ids を参照）
+    pub fn remap_tag_ids(&mut self, map: &impl Fn(&str) -> Option<String>) {
+        if let Some(expression) = self.card_tags.as_mut() {
+            expression.remap_tag_ids(map);
+        }
+        if let Some(expression) = self.annotations.tags.as_mut() {
+            expression.remap_tag_ids(map);
+        }
+    }
+}
+
+/// スマートコレクション（全カードから条件に一致する画像を集める保存済みの検索）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmartCollection {
+    pub id: String,
+    pub title: String,
+    pub query: SmartQuery,
+    pub sort_order: i32,
+    pub created_at: String,
+    pub updated_at: String,
+}
@@ -7,7 +7,7 @@ import { IndexPage } from "./pages/IndexPage";
 import { ViewerPage } from "./pages/ViewerPage";
 import { StartupPage } from "./pages/StartupPage";
 import { useProfileStore } from "./store/profileStore";
-import { saveProfile } from "./api/tauri";
+import { releaseProfileLock } from "./api/tauri";
 
 function App() {
   // ウィンドウ終了時にウィンドウ状態を保存
@@ -35,11 +35,11 @@ function App() {
           },
         });
 
-        const { currentProfile: updated, currentProfilePath: path } =
-          useProfileStore.getState();
-        if (updated && path) {
-          await saveProfile(path, updated);
-        }
+        await useProfileStore
+          .getState()
+          .syncCurrentProfile()
+          .catch((e) => console.error("プロファイル保存に失敗:", e));
+        await releaseProfileLock(currentProfilePath).catch(() => undefined);
       })
       .then((fn) => {
         unlistenFn = fn;
@@ -1,7 +1,36 @@
 // Tauriコマンド呼び出しラッパー
 
-import { invoke } from "@tauri-apps/api/core";
-import type { ProfileData, AppConfig, ImageFile } from "../types";
+import { convertFileSrc, invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import type {
+  AlbumStatus,
+  AnimationInfo,
+  AnnotatedImage,
+  ProfileBackup,
+  ProfileConflict,
+  ProfileData,
+  ProfileLockStatus,
+  ProfileMerge,
+  AppConfig,
+  Card,
+  CardRelink,
+  CardWithStatus,
+  ImageAnnotationFilter,
+  ImageAnnotationUpdate,
+  ImageFile,
+  ImagesChanged,
+  ImageInfo,
+  RelinkProposal,
+  ScanOptions,
+  SortMode,
+  SmartQuery,
+  SupportedFormat,
+  TagExpression,
+  ThumbnailCacheCleanup,
+  ThumbnailCacheStats,
+  ThumbnailJobFinished,
+  ThumbnailProgress,
+} from "../types";
 
 // ========================================
 // プロファイル管理
@@ -14,14 +43,78 @@ export async function loadProfile(path: string): Promise<ProfileData> {
   return invoke<ProfileData>("load_profile", { path });
 }
 
+/**
+ * プロファイルの保存時の競合エラー
+ */
+export class ProfileConflictError extends Error {
+  readonly conflict: ProfileConflict;
+
+  constructor(conflict: ProfileConflict) {
+    super(conflict.message);
+    this.name = "ProfileConflictError";
+    this.conflict = conflict;
+  }
+
+  toString(): string {
+    return this.message;
+  }
+}
+
 /**
  * プロファイルを保存する
+ * @param expectedUpdatedAt 読み込み時（前回の保存時）の更新日時。
+ *   ディスク上のプロファイルがこれより新しい場合は ProfileConflictError となる
+ * @returns 保存したプロファイルの更新日時
  */
 export async function saveProfile(
   path: string,
-  profile: ProfileData
-): Promise<void> {
-  return invoke("save_profile", { path, profile });
+  profile: ProfileData,
+  expectedUpdatedAt?: string
+): Promise<string> {
+  try {
+    return await invoke<string>("save_profile", {
+      path,
+      profile,
+      expectedUpdatedAt,
+    });
+  } catch (e) {
+    const error = e as ProfileConflict | { kind: "failed"; message: string };
+    if (error?.kind === "conflict") throw new ProfileConflictError(error);
+    throw error?.message ?? e;
+  }
+}
+
+/**
+ * ディスク上のプロファイルを読み込み直し、編集中の変更と統合する
+ * @param base 読み込み時（前回の保存・統合時）のプロファイル
+ * @param local 編集中のプロファイル
+ * @returns 統合結果（統合したプロファイルの更新日時はディスク上のもの）
+ */
+export async function mergeProfileChanges(
+  path: string,
+  base: ProfileData,
+  local: ProfileData
+): Promise<ProfileMerge> {
+  return invoke<ProfileMerge>("merge_profile_changes", { path, base, local });
+}
+
+/**
+ * プロファイルのロックを取得する
+ * @param force true の場合は他のユーザーのロックを奪う
+ * @returns 取得結果（他のユーザーが開いている場合は acquired が false）
+ */
+export async function acquireProfileLock(
+  path: string,
+  force = false
+): Promise<ProfileLockStatus> {
+  return invoke<ProfileLockStatus>("acquire_profile_lock", { path, force });
+}
+
+/**
+ * プロファイルのロックを解除する（自分のロックのみ）
+ */
+export async function releaseProfileLock(path: string): Promise<void> {
+  return invoke("release_profile_lock", { path });
 }
 
 /**
@@ -31,6 +124,30 @@ export async function createNewProfile(path: string): Promise<ProfileData> {
   return invoke<ProfileData>("create_new_profile", { path });
 }
 
+/**
+ * プロファイルのバックアップ一覧を取得
+ * @param path プロファイルファイルのパス
+ * @returns バックアップ（新しい順）
+ */
+export async function listProfileBackups(
+  path: string
+): Promise<ProfileBackup[]> {
+  return invoke<ProfileBackup[]>("list_profile_backups", { path });
+}
+
+/**
+ * バックアップからプロファイルを復元する（復元前の内容もバックアップされる）
+ * @param path プロファイルファイルのパス
+ * @param backupPath 復元するバックアップのパス
+ * @returns 復元したプロファイル
+ */
+export async function restoreProfileBackup(
+  path: string,
+  backupPath: string
+): Promise<ProfileData> {
+  return invoke<ProfileData>("restore_profile_backup", { path, backupPath });
+}
+
 // ========================================
 // アプリ共通設定
 // ========================================
@@ -91,6 +208,14 @@ export async function selectFolder(): Promise<string | null> {
   return invoke<string | null>("select_folder");
 }
 
+/**
+ * アーカイブファイル選択ダイアログを開く（ZIP/CBZ/TAR/TAR.GZをカードとして追加する用）
+ * @returns 選択されたファイルパス、キャンセル時はnull
+ */
+export async function selectArchiveFile(): Promise<string | null> {
+  return invoke<string | null>("select_archive_file");
+}
+
 /**
  * 画像ファイル選択ダイアログを開く（サムネイル選択用）
  * @param initialDir 初期ディレクトリ（省略可能）
@@ -106,48 +231,694 @@ export async function selectImageFile(
 // 画像
 // ========================================
 
+// アーカイブ内の画像パスの区切り（"<アーカイブ>!/<エントリ名>"）
+const ARCHIVE_MEMBER_SEPARATOR = "!/";
+
+// WebView がそのまま表示できる画像の拡張子（バックエンドの formats.rs と合わせる）
+const WEBVIEW_IMAGE_EXTENSIONS = new Set([
+  "jpg",
+  "jpeg",
+  "png",
+  "gif",
+  "webp",
+  "bmp",
+  "ico",
+  "avif",
+]);
+
+/**
+ * 表示用の画像のURLを取得
+ * アーカイブ内の画像と、TIFF など WebView が表示できない形式の画像は
+ * image:// プロトコル経由で読み込む（バックエンドでPNG/JPEGに変換される）
+ * @param imagePath 画像ファイルパス
+ * @returns <img> の src に指定できるURL
+ */
+export function getImageUrl(imagePath: string): string {
+  const filename = imagePath.split(/[\\/]/).pop() ?? "";
+  const dot = filename.lastIndexOf(".");
+  const extension = dot >= 0 ? filename.slice(dot + 1).toLowerCase() : "";
+  return imagePath.includes(ARCHIVE_MEMBER_SEPARATOR) ||
+    !WEBVIEW_IMAGE_EXTENSIONS.has(extension)
+    ? convertFileSrc(imagePath, "image")
+    : convertFileSrc(imagePath);
+}
+
 /**
- * サムネイル画像を取得（Base64 DataURL）
+ * サムネイル画像のURLを取得（thumb:// プロトコル、IPCを経由しない）
  * @param imagePath 画像ファイルパス
  * @param size サムネイルサイズ（デフォルト: 200）
+ * @param animated アニメーション画像をアニメーションGIFのサムネイルで返す（デフォルト: false）
+ * @returns <img> の src に指定できるURL
+ */
+export function getThumbnailUrl(
+  imagePath: string,
+  size: number = 200,
+  animated: boolean = false
+): string {
+  const url = `${convertFileSrc(imagePath, "thumb")}?size=${size}`;
+  return animated ? `${url}&animated=1` : url;
+}
+
+/**
+ * サムネイル画像を取得（Base64 DataURL、互換用。通常は getThumbnailUrl を使用）
+ * @param imagePath 画像ファイルパス
+ * @param size サムネイルサイズ（デフォルト: 200）
+ * @param animated アニメーション画像をアニメーションGIFのサムネイルで返す（デフォルト: false）
  * @returns Base64 DataURL
  */
 export async function getThumbnail(
   imagePath: string,
-  size: number = 200
+  size: number = 200,
+  animated: boolean = false
 ): Promise<string> {
-  return invoke<string>("get_thumbnail", { imagePath, size });
+  return invoke<string>("get_thumbnail", { imagePath, size, animated });
 }
 
 /**
  * フォルダ内の最初の画像ファイルパスを取得
  * @param folderPath フォルダパス
+ * @param options 走査オプション（省略時は直下のみ）
+ * @param sort ソート方法（省略時はファイル名の自然順）
  * @returns 最初の画像ファイルパス、画像がない場合はnull
  */
 export async function getFirstImageInFolder(
-  folderPath: string
+  folderPath: string,
+  options?: ScanOptions,
+  sort?: SortMode
 ): Promise<string | null> {
-  return invoke<string | null>("get_first_image_in_folder", { folderPath });
+  return invoke<string | null>("get_first_image_in_folder", {
+    folderPath,
+    options,
+    sort,
+  });
 }
 
 /**
  * フォルダパスが有効かどうかを検証
  * @param path 検証するパス
- * @returns フォルダが存在すればtrue
+ * @returns フォルダまたはアーカイブファイル（ZIP/CBZ/TAR/TAR.GZ）が存在すればtrue
  */
 export async function validateFolderPath(path: string): Promise<boolean> {
   return invoke<boolean>("validate_folder_path", { path });
 }
 
 /**
- * フォルダ内のすべての画像ファイルを取得（ファイル名順でソート）
+ * 全カードを並列に検証（画像数とサムネイル画像の有無も確認）
+ * @param cards 検証するカード
+ * @returns 検証結果（cards と同じ順序）
+ */
+export async function validateProfileCards(
+  cards: Card[]
+): Promise<CardWithStatus[]> {
+  return invoke<CardWithStatus[]>("validate_profile_cards", { cards });
+}
+
+/**
+ * 見つからないカードの移動先の候補を探す
+ * @param cards 全カード（他のカードが参照しているフォルダは候補から除外）
+ * @param searchRoot 探索するフォルダ
+ * @param maxDepth 探索する深さ（省略時は 8）
+ * @returns 見つからないカードごとの提案
+ */
+export async function findRelinkCandidates(
+  cards: Card[],
+  searchRoot: string,
+  maxDepth?: number
+): Promise<RelinkProposal[]> {
+  return invoke<RelinkProposal[]>("find_relink_candidates", {
+    cards,
+    searchRoot,
+    maxDepth,
+  });
+}
+
+/**
+ * カードのフォルダを一括で付け替える
+ * サムネイルとアルバムの画像も新しいフォルダ内の同じ画像に付け替える
+ * @param profile 編集中のプロファイル
+ * @param relinks 付け替えるカードと移動先
+ * @returns 更新後のプロファイル
+ */
+export async function applyCardRelinks(
+  profile: ProfileData,
+  relinks: CardRelink[]
+): Promise<ProfileData> {
+  return invoke<ProfileData>("apply_card_relinks", { profile, relinks });
+}
+
+/**
+ * フォルダ内のすべての画像ファイルを取得
  * @param folderPath フォルダパス
+ * @param options 走査オプション（省略時は直下のみ）
+ * @param sort ソート方法（省略時はファイル名の自然順）
  * @returns 画像ファイル情報の配列
  */
 export async function getImagesInFolder(
-  folderPath: string
+  folderPath: string,
+  options?: ScanOptions,
+  sort?: SortMode
+): Promise<ImageFile[]> {
+  return invoke<ImageFile[]>("get_images_in_folder", {
+    folderPath,
+    options,
+    sort,
+  });
+}
+
+/**
+ * 画像の詳細情報を取得（サイズ・解像度・形式・EXIF）
+ * @param imagePath 画像ファイルパス
+ * @returns 画像詳細情報
+ */
+export async function getImageInfo(imagePath: string): Promise<ImageInfo> {
+  return invoke<ImageInfo>("get_image_info", { imagePath });
+}
+
+/**
+ * フォルダ内のすべての画像の詳細情報を取得
+ * @param folderPath フォルダパス
+ * @param options 走査オプション（省略時は直下のみ）
+ * @param sort ソート方法（省略時はファイル名の自然順）
+ * @returns 画像詳細情報の配列（getImagesInFolderと同じ順序）
+ */
+export async function getImagesInfoInFolder(
+  folderPath: string,
+  options?: ScanOptions,
+  sort?: SortMode
+): Promise<ImageInfo[]> {
+  return invoke<ImageInfo[]>("get_images_info_in_folder", {
+    folderPath,
+    options,
+    sort,
+  });
+}
+
+/**
+ * このビルドで対応している画像形式の一覧を取得
+ * @returns 対応画像形式の配列
+ */
+export async function getSupportedFormats(): Promise<SupportedFormat[]> {
+  return invoke<SupportedFormat[]>("get_supported_formats");
+}
+
+/**
+ * アニメーション情報（フレーム数と各フレームの表示時間）を取得
+ * @param imagePath 画像ファイルパス
+ */
+export async function getAnimationInfo(imagePath: string): Promise<AnimationInfo> {
+  return invoke<AnimationInfo>("get_animation_info", { imagePath });
+}
+
+/**
+ * アニメーション画像の指定したフレームを画像ファイルとして保存
+ * @param imagePath 画像ファイルパス
+ * @param frameIndex フレーム番号（0始まり）
+ * @param outputPath 保存先のパス（形式は拡張子から決定）
+ */
+export async function exportAnimationFrame(
+  imagePath: string,
+  frameIndex: number,
+  outputPath: string
+): Promise<void> {
+  return invoke("export_animation_frame", { imagePath, frameIndex, outputPath });
+}
+
+// ========================================
+// タグ
+// ========================================
+
+/**
+ * タグを作成する（作成したタグは末尾に追加される）
+ * @param color "#rrggbb" 形式の色（null で色なし）
+ * @returns 更新後のプロファイル
+ */
+export async function createTag(
+  profile: ProfileData,
+  name: string,
+  color: string | null = null
+): Promise<ProfileData> {
+  return invoke<ProfileData>("create_tag", { profile, name, color });
+}
+
+/**
+ * タグ名を変更する
+ * @returns 更新後のプロファイル
+ */
+export async function renameTag(
+  profile: ProfileData,
+  tagId: string,
+  name: string
+): Promise<ProfileData> {
+  return invoke<ProfileData>("rename_tag", { profile, tagId, name });
+}
+
+/**
+ * タグの色を変更する
+ * @param color "#rrggbb" 形式の色（null で色なし）
+ * @returns 更新後のプロファイル
+ */
+export async function setTagColor(
+  profile: ProfileData,
+  tagId: string,
+  color: string | null
+): Promise<ProfileData> {
+  return invoke<ProfileData>("set_tag_color", { profile, tagId, color });
+}
+
+/**
+ * タグを削除する（カードへのタグ付けも削除される）
+ * @returns 更新後のプロファイル
+ */
+export async function deleteTag(
+  profile: ProfileData,
+  tagId: string
+): Promise<ProfileData> {
+  return invoke<ProfileData>("delete_tag", { profile, tagId });
+}
+
+/**
+ * 複数のカードに複数のタグを付ける
+ * @returns 更新後のプロファイル
+ */
+export async function attachTags(
+  profile: ProfileData,
+  cardIds: string[],
+  tagIds: string[]
+): Promise<ProfileData> {
+  return invoke<ProfileData>("attach_tags", { profile, cardIds, tagIds });
+}
+
+/**
+ * 複数のカードから複数のタグを外す
+ * @returns 更新後のプロファイル
+ */
+export async function detachTags(
+  profile: ProfileData,
+  cardIds: string[],
+  tagIds: string[]
+): Promise<ProfileData> {
+  return invoke<ProfileData>("detach_tags", { profile, cardIds, tagIds });
+}
+
+/**
+ * タグの条件式に一致するカードを取得
+ * @returns 一致したカード（sortOrder順）
+ */
+export async function findCardsByTags(
+  profile: ProfileData,
+  expression: TagExpression
+): Promise<Card[]> {
+  return invoke<Card[]>("find_cards_by_tags", { profile, expression });
+}
+
+// ========================================
+// 画像の注釈
+// ========================================
+
+/**
+ * 画像の注釈を一括で変更する（何も設定されていない状態になった注釈は削除される）
+ * @param relativePaths カードのルートフォルダからの相対パス（ImageFile.relativePath）
+ * @returns 更新後のプロファイル
+ */
+export async function setImageAnnotations(
+  profile: ProfileData,
+  cardId: string,
+  relativePaths: string[],
+  update: ImageAnnotationUpdate
+): Promise<ProfileData> {
+  return invoke<ProfileData>("set_image_annotations", {
+    profile,
+    cardId,
+    relativePaths,
+    update,
+  });
+}
+
+/**
+ * カードの画像一覧を注釈付きで取得（カードの走査オプション・ソート方法に従う）
+ * @param filter 絞り込み条件（省略時はすべての画像）
+ */
+export async function listAnnotatedImages(
+  profile: ProfileData,
+  cardId: string,
+  filter?: ImageAnnotationFilter
+): Promise<AnnotatedImage[]> {
+  return invoke<AnnotatedImage[]>("list_annotated_images", {
+    profile,
+    cardId,
+    filter,
+  });
+}
+
+// ========================================
+// スマートコレクション
+// ========================================
+
+/**
+ * スマートコレクションを作成する（作成したコレクションは末尾に追加される）
+ * 存在しないカード・タグを参照する検索条件はエラーになる
+ * @returns 更新後のプロファイル
+ */
+export async function createSmartCollection(
+  profile: ProfileData,
+  title: string,
+  query: SmartQuery
+): Promise<ProfileData> {
+  return invoke<ProfileData>("create_smart_collection", {
+    profile,
+    title,
+    query,
+  });
+}
+
+/**
+ * スマートコレクションの名前・検索条件を変更する（省略した項目は変更しない）
+ * @returns 更新後のプロファイル
+ */
+export async function updateSmartCollection(
+  profile: ProfileData,
+  collectionId: string,
+  title?: string,
+  query?: SmartQuery
+): Promise<ProfileData> {
+  return invoke<ProfileData>("update_smart_collection", {
+    profile,
+    collectionId,
+    title,
+    query,
+  });
+}
+
+/**
+ * スマートコレクションを削除する
+ * @returns 更新後のプロファイル
+ */
+export async function deleteSmartCollection(
+  profile: ProfileData,
+  collectionId: string
+): Promise<ProfileData> {
+  return invoke<ProfileData>("delete_smart_collection", {
+    profile,
+    collectionId,
+  });
+}
+
+/**
+ * スマートコレクションの画像一覧を取得（全カードから条件に一致する画像を集める）
+ * @returns 画像一覧（各画像の cardId に画像のカードが設定される）
+ */
+export async function listSmartCollectionImages(
+  profile: ProfileData,
+  collectionId: string
 ): Promise<ImageFile[]> {
-  return invoke<ImageFile[]>("get_images_in_folder", { folderPath });
+  return invoke<ImageFile[]>("list_smart_collection_images", {
+    profile,
+    collectionId,
+  });
+}
+
+/**
+ * 保存前の検索条件に一致する画像一覧を取得（コレクションの編集中のプレビュー用）
+ */
+export async function previewSmartQuery(
+  profile: ProfileData,
+  query: SmartQuery
+): Promise<ImageFile[]> {
+  return invoke<ImageFile[]>("preview_smart_query", { profile, query });
+}
+
+// ========================================
+// アルバム
+// ========================================
+
+/**
+ * アルバムを作成する（作成したアルバムは末尾に追加される）
+ * @returns 更新後のプロファイル
+ */
+export async function createAlbum(
+  profile: ProfileData,
+  title: string
+): Promise<ProfileData> {
+  return invoke<ProfileData>("create_album", { profile, title });
+}
+
+/**
+ * アルバム名を変更する
+ * @returns 更新後のプロファイル
+ */
+export async function renameAlbum(
+  profile: ProfileData,
+  albumId: string,
+  title: string
+): Promise<ProfileData> {
+  return invoke<ProfileData>("rename_album", { profile, albumId, title });
+}
+
+/**
+ * アルバムを削除する（画像ファイルは削除されない）
+ * @returns 更新後のプロファイル
+ */
+export async function deleteAlbum(
+  profile: ProfileData,
+  albumId: string
+): Promise<ProfileData> {
+  return invoke<ProfileData>("delete_album", { profile, albumId });
+}
+
+/**
+ * アルバムに画像を追加する（アルバムにすでにある画像は追加されない）
+ * @param index 追加する位置（省略時は末尾）
+ * @returns 更新後のプロファイル
+ */
+export async function addAlbumImages(
+  profile: ProfileData,
+  albumId: string,
+  paths: string[],
+  index?: number
+): Promise<ProfileData> {
+  return invoke<ProfileData>("add_album_images", {
+    profile,
+    albumId,
+    paths,
+    index,
+  });
+}
+
+/**
+ * アルバムから画像を外す（画像ファイルは削除されない）
+ * @returns 更新後のプロファイル
+ */
+export async function removeAlbumImages(
+  profile: ProfileData,
+  albumId: string,
+  paths: string[]
+): Promise<ProfileData> {
+  return invoke<ProfileData>("remove_album_images", { profile, albumId, paths });
+}
+
+/**
+ * アルバムの画像を並べ替える
+ * @param paths アルバムのすべての画像のパス（新しい順序）
+ * @returns 更新後のプロファイル
+ */
+export async function reorderAlbumImages(
+  profile: ProfileData,
+  albumId: string,
+  paths: string[]
+): Promise<ProfileData> {
+  return invoke<ProfileData>("reorder_album_images", {
+    profile,
+    albumId,
+    paths,
+  });
+}
+
+/**
+ * アルバムの画像一覧を取得（見つからない画像は除く）
+ * @returns 画像一覧（カードのフォルダ内の画像は cardId にカードが設定される）
+ */
+export async function listAlbumImages(
+  profile: ProfileData,
+  albumId: string
+): Promise<ImageFile[]> {
+  return invoke<ImageFile[]>("list_album_images", { profile, albumId });
+}
+
+/**
+ * すべてのアルバムについて、見つからない画像を検証
+ */
+export async function validateAlbums(
+  profile: ProfileData
+): Promise<AlbumStatus[]> {
+  return invoke<AlbumStatus[]>("validate_albums", { profile });
+}
+
+// ========================================
+// サムネイル一括生成
+// ========================================
+
+/**
+ * 指定した画像のサムネイルをバックグラウンドで一括生成
+ * @param imagePaths 画像ファイルパスの配列（プロファイル内のカードサムネイルなど）
+ * @param size サムネイルサイズ
+ * @returns ジョブID
+ */
+export async function startThumbnailJob(
+  imagePaths: string[],
+  size: number = 200
+): Promise<string> {
+  return invoke<string>("start_thumbnail_job", { imagePaths, size });
+}
+
+/**
+ * フォルダ内のすべての画像のサムネイルをバックグラウンドで一括生成
+ * @param folderPath フォルダパス
+ * @param size サムネイルサイズ
+ * @param options 走査オプション（省略時は直下のみ）
+ * @param sort ソート方法（省略時はファイル名の自然順）
+ * @returns ジョブID
+ */
+export async function startFolderThumbnailJob(
+  folderPath: string,
+  size: number = 200,
+  options?: ScanOptions,
+  sort?: SortMode
+): Promise<string> {
+  return invoke<string>("start_folder_thumbnail_job", {
+    folderPath,
+    options,
+    sort,
+    size,
+  });
+}
+
+/**
+ * 表示中の画像を優先的に生成するよう指定
+ * @param jobId ジョブID
+ * @param imagePaths 優先する画像ファイルパス（先頭ほど優先）
+ */
+export async function prioritizeThumbnailJob(
+  jobId: string,
+  imagePaths: string[]
+): Promise<void> {
+  return invoke("prioritize_thumbnail_job", { jobId, imagePaths });
+}
+
+/**
+ * サムネイル一括生成をキャンセル（画面遷移時など）
+ * @param jobId ジョブID
+ */
+export async function cancelThumbnailJob(jobId: string): Promise<void> {
+  return invoke("cancel_thumbnail_job", { jobId });
+}
+
+/**
+ * サムネイル生成の進捗イベントを購読
+ * @returns 購読解除関数
+ */
+export async function onThumbnailProgress(
+  handler: (progress: ThumbnailProgress) => void
+): Promise<UnlistenFn> {
+  return listen<ThumbnailProgress>("thumbnail-progress", (event) =>
+    handler(event.payload)
+  );
+}
+
+/**
+ * サムネイル一括生成の終了イベントを購読
+ * @returns 購読解除関数
+ */
+export async function onThumbnailJobFinished(
+  handler: (finished: ThumbnailJobFinished) => void
+): Promise<UnlistenFn> {
+  return listen<ThumbnailJobFinished>("thumbnail-job-finished", (event) =>
+    handler(event.payload)
+  );
+}
+
+// ========================================
+// サムネイルキャッシュ
+// ========================================
+
+/**
+ * サムネイルキャッシュの統計情報を取得
+ */
+export async function getThumbnailCacheStats(): Promise<ThumbnailCacheStats> {
+  return invoke<ThumbnailCacheStats>("get_thumbnail_cache_stats");
+}
+
+/**
+ * サムネイルキャッシュ（メモリ+ディスク）をすべて削除
+ */
+export async function clearThumbnailCache(): Promise<ThumbnailCacheCleanup> {
+  return invoke<ThumbnailCacheCleanup>("clear_thumbnail_cache");
+}
+
+/**
+ * 元画像が存在しなくなったサムネイルをディスクキャッシュから削除
+ */
+export async function collectThumbnailCacheGarbage(): Promise<ThumbnailCacheCleanup> {
+  return invoke<ThumbnailCacheCleanup>("collect_thumbnail_cache_garbage");
+}
+
+// ========================================
+// フォルダ監視
+// ========================================
+
+/**
+ * 表示中のカードのフォルダの監視を開始（以前の監視は解除される）
+ * @param cardId カードID
+ * @param folderPath フォルダパス（アーカイブファイルも可）
+ * @param options 走査オプション（recursive の場合はサブフォルダも監視）
+ */
+export async function watchCardFolder(
+  cardId: string,
+  folderPath: string,
+  options?: ScanOptions
+): Promise<void> {
+  return invoke("watch_card_folder", { cardId, folderPath, options });
+}
+
+/**
+ * 表示中のカードのフォルダの監視を解除
+ */
+export async function unwatchCardFolder(): Promise<void> {
+  return invoke("unwatch_card_folder");
+}
+
+/**
+ * 全カードのフォルダの有効性の監視を開始（以前の監視は解除される）
+ * @returns 現在の検証結果
+ */
+export async function watchCardStatuses(cards: Card[]): Promise<CardWithStatus[]> {
+  return invoke<CardWithStatus[]>("watch_card_statuses", { cards });
+}
+
+/**
+ * 全カードのフォルダの有効性の監視を解除
+ */
+export async function unwatchCardStatuses(): Promise<void> {
+  return invoke("unwatch_card_statuses");
+}
+
+/**
+ * 画像一覧の変更イベントを購読
+ * @returns 購読解除関数
+ */
+export async function onImagesChanged(
+  handler: (changed: ImagesChanged) => void
+): Promise<UnlistenFn> {
+  return listen<ImagesChanged>("images-changed", (event) => handler(event.payload));
+}
+
+/**
+ * カードの有効性の変更イベントを購読
+ * @returns 購読解除関数
+ */
+export async function onCardStatusChanged(
+  handler: (status: CardWithStatus) => void
+): Promise<UnlistenFn> {
+  return listen<CardWithStatus>("card-status-changed", (event) => handler(event.payload));
 }
 
 // ========================================
@@ -162,6 +933,18 @@ export async function copyImageToClipboard(imagePath: string): Promise<void> {
   return invoke("copy_image_to_clipboard", { imagePath });
 }
 
+/**
+ * アニメーション画像の指定したフレームをクリップボードにコピー
+ * @param imagePath 画像ファイルパス
+ * @param frameIndex フレーム番号（0始まり）
+ */
+export async function copyAnimationFrameToClipboard(
+  imagePath: string,
+  frameIndex: number
+): Promise<void> {
+  return invoke("copy_animation_frame_to_clipboard", { imagePath, frameIndex });
+}
+
 /**
  * テキストをクリップボードにコピー（パスコピー用）
  * @param text コピーするテキスト
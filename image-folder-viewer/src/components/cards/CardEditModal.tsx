@@ -9,7 +9,7 @@ import {
   getThumbnail,
   getFirstImageInFolder,
 } from "../../api/tauri";
-import type { Card } from "../../types";
+import type { Card, ScanOptions, SortKey, SortMode } from "../../types";
 import type { UpdateCardInput } from "../../store/profileStore";
 
 interface CardEditModalProps {
@@ -23,6 +23,32 @@ interface CardEditModalProps {
 // サムネイルプレビューサイズ
 const PREVIEW_SIZE = 150;
 
+// 走査オプションの既定値（走査オプションを持たないカード用、バックエンドの既定値と同じ）
+const DEFAULT_SCAN_OPTIONS: ScanOptions = {
+  recursive: false,
+  maxDepth: null,
+  followSymlinks: false,
+};
+
+// ソート方法の既定値（ソート方法を持たないカード用、バックエンドの既定値と同じ）
+const DEFAULT_SORT_MODE: SortMode = { key: "name", direction: "asc" };
+
+// ソートキーの選択肢
+const SORT_KEY_OPTIONS: { value: SortKey; label: string }[] = [
+  { value: "name", label: "ファイル名" },
+  { value: "modified", label: "更新日時" },
+  { value: "created", label: "作成日時" },
+  { value: "size", label: "ファイルサイズ" },
+  { value: "exifDate", label: "撮影日時（EXIF）" },
+  { value: "dimensions", label: "画像サイズ" },
+];
+
+// 走査オプションが同じかを判定
+const isSameScanOptions = (a: ScanOptions, b: ScanOptions) =>
+  a.recursive === b.recursive &&
+  a.maxDepth === b.maxDepth &&
+  a.followSymlinks === b.followSymlinks;
+
 export const CardEditModal = ({
   isOpen,
   onClose,
@@ -35,6 +61,9 @@ export const CardEditModal = ({
   const [folderPath, setFolderPath] = useState("");
   const [thumbnailPath, setThumbnailPath] = useState<string | null>(null);
   const [thumbnailUrl, setThumbnailUrl] = useState<string | null>(null);
+  const [scanOptions, setScanOptions] =
+    useState<ScanOptions>(DEFAULT_SCAN_OPTIONS);
+  const [sortMode, setSortMode] = useState<SortMode>(DEFAULT_SORT_MODE);
 
   // 編集状態追跡
   const [isFolderChanged, setIsFolderChanged] = useState(false);
@@ -48,6 +77,8 @@ export const CardEditModal = ({
       setTitle(card.title);
       setFolderPath(card.folderPath);
       setThumbnailPath(card.thumbnail);
+      setScanOptions(card.scanOptions ?? DEFAULT_SCAN_OPTIONS);
+      setSortMode(card.sortMode ?? DEFAULT_SORT_MODE);
       setIsFolderChanged(false);
     }
   }, [card, isOpen]);
@@ -95,7 +126,11 @@ export const CardEditModal = ({
         setIsFolderChanged(true);
 
         // フォルダ内最初の画像をサムネイルに設定
-        const firstImage = await getFirstImageInFolder(path);
+        const firstImage = await getFirstImageInFolder(
+          path,
+          scanOptions,
+          sortMode
+        );
         setThumbnailPath(firstImage);
       }
     } catch (e) {
@@ -115,6 +150,15 @@ export const CardEditModal = ({
     }
   };
 
+  // 最大深さの変更（空欄は無制限）
+  const handleMaxDepthChange = (value: string) => {
+    const depth = Number.parseInt(value, 10);
+    setScanOptions((options) => ({
+      ...options,
+      maxDepth: Number.isNaN(depth) ? null : Math.max(0, depth),
+    }));
+  };
+
   // 保存
   const handleSave = () => {
     if (!card || !title.trim()) return;
@@ -133,6 +177,22 @@ export const CardEditModal = ({
       input.thumbnail = thumbnailPath;
     }
 
+    // 走査オプションが変更された場合
+    if (
+      !isSameScanOptions(scanOptions, card.scanOptions ?? DEFAULT_SCAN_OPTIONS)
+    ) {
+      input.scanOptions = scanOptions;
+    }
+
+    // ソート方法が変更された場合
+    const currentSortMode = card.sortMode ?? DEFAULT_SORT_MODE;
+    if (
+      sortMode.key !== currentSortMode.key ||
+      sortMode.direction !== currentSortMode.direction
+    ) {
+      input.sortMode = sortMode;
+    }
+
     onSave(card.id, input);
     onClose();
   };
@@ -222,6 +282,96 @@ export const CardEditModal = ({
           />
         </div>
 
+        {/* フォルダの走査 */}
+        <div>
+          <label className="block text-sm font-medium text-gray-700 mb-1">
+            フォルダの走査
+          </label>
+          <div className="space-y-2 text-sm text-gray-700">
+            <label className="flex items-center gap-2">
+              <input
+                type="checkbox"
+                checked={scanOptions.recursive}
+                onChange={(e) =>
+                  setScanOptions((options) => ({
+                    ...options,
+                    recursive: e.target.checked,
+                  }))
+                }
+              />
+              サブフォルダも表示する
+            </label>
+            <label className="flex items-center gap-2 pl-6">
+              最大の深さ
+              <input
+                type="number"
+                min={0}
+                value={scanOptions.maxDepth ?? ""}
+                onChange={(e) => handleMaxDepthChange(e.target.value)}
+                disabled={!scanOptions.recursive}
+                className="w-20 px-2 py-1 border border-gray-300 rounded disabled:bg-gray-100 disabled:text-gray-400"
+                placeholder="無制限"
+              />
+            </label>
+            <label className="flex items-center gap-2">
+              <input
+                type="checkbox"
+                checked={scanOptions.followSymlinks}
+                onChange={(e) =>
+                  setScanOptions((options) => ({
+                    ...options,
+                    followSymlinks: e.target.checked,
+                  }))
+                }
+              />
+              シンボリックリンクをたどる
+            </label>
+          </div>
+        </div>
+
+        {/* 画像の並び順 */}
+        <div>
+          <label
+            htmlFor="edit-card-sort-key"
+            className="block text-sm font-medium text-gray-700 mb-1"
+          >
+            画像の並び順
+          </label>
+          <div className="flex items-center gap-2">
+            <select
+              id="edit-card-sort-key"
+              value={sortMode.key}
+              onChange={(e) =>
+                setSortMode((mode) => ({
+                  ...mode,
+                  key: e.target.value as SortKey,
+                }))
+              }
+              className="flex-1 px-3 py-2 border border-gray-300 rounded text-sm focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+            >
+              {SORT_KEY_OPTIONS.map((option) => (
+                <option key={option.value} value={option.value}>
+                  {option.label}
+                </option>
+              ))}
+            </select>
+            <select
+              value={sortMode.direction}
+              onChange={(e) =>
+                setSortMode((mode) => ({
+                  ...mode,
+                  direction: e.target.value as SortMode["direction"],
+                }))
+              }
+              className="px-3 py-2 border border-gray-300 rounded text-sm focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+              aria-label="並び順の方向"
+            >
+              <option value="asc">昇順</option>
+              <option value="desc">降順</option>
+            </select>
+          </div>
+        </div>
+
         {/* サムネイル */}
         <div>
           <label className="block text-sm font-medium text-gray-700 mb-1">
@@ -2,7 +2,7 @@
 
 import { useState, useEffect } from "react";
 import { Pencil, Trash2, AlertTriangle, ImageIcon } from "lucide-react";
-import { getThumbnail } from "../../api/tauri";
+import { getThumbnailUrl } from "../../api/tauri";
 import type { Card } from "../../types";
 
 interface CardItemProps {
@@ -32,41 +32,19 @@ export const CardItem = ({
   dragHandleProps,
   isDragging = false,
 }: CardItemProps) => {
-  const [thumbnailUrl, setThumbnailUrl] = useState<string | null>(null);
-  const [isLoadingThumbnail, setIsLoadingThumbnail] = useState(false);
+  // サムネイルは thumb:// プロトコルから直接読み込む
+  const thumbnailUrl =
+    card.thumbnail && isValid
+      ? getThumbnailUrl(card.thumbnail, THUMBNAIL_SIZE)
+      : null;
+  const [isLoadingThumbnail, setIsLoadingThumbnail] = useState(true);
+  const [thumbnailFailed, setThumbnailFailed] = useState(false);
 
-  // サムネイル読み込み
+  // サムネイルURLが変わったら読み込み状態をリセット
   useEffect(() => {
-    if (!card.thumbnail || !isValid) {
-      setThumbnailUrl(null);
-      return;
-    }
-
-    let cancelled = false;
     setIsLoadingThumbnail(true);
-
-    getThumbnail(card.thumbnail, THUMBNAIL_SIZE)
-      .then((url) => {
-        if (!cancelled) {
-          setThumbnailUrl(url);
-        }
-      })
-      .catch((e) => {
-        console.error("サムネイル読み込みエラー:", e);
-        if (!cancelled) {
-          setThumbnailUrl(null);
-        }
-      })
-      .finally(() => {
-        if (!cancelled) {
-          setIsLoadingThumbnail(false);
-        }
-      });
-
-    return () => {
-      cancelled = true;
-    };
-  }, [card.thumbnail, isValid]);
+    setThumbnailFailed(false);
+  }, [thumbnailUrl]);
 
   // カードクリック
   const handleClick = () => {
@@ -108,16 +86,25 @@ export const CardItem = ({
       {/* サムネイル領域 */}
       <div className="relative aspect-square bg-gray-100 flex items-center justify-center overflow-hidden">
         {isValid ? (
-          isLoadingThumbnail ? (
-            // ローディング
-            <div className="animate-pulse bg-gray-200 w-full h-full" />
-          ) : thumbnailUrl ? (
-            // サムネイル画像
-            <img
-              src={thumbnailUrl}
-              alt={card.title}
-              className="w-full h-full object-cover"
-            />
+          thumbnailUrl && !thumbnailFailed ? (
+            <>
+              {/* サムネイル画像 */}
+              <img
+                src={thumbnailUrl}
+                alt={card.title}
+                className="w-full h-full object-cover"
+                onLoad={() => setIsLoadingThumbnail(false)}
+                onError={() => {
+                  console.error("サムネイル読み込みエラー:", card.thumbnail);
+                  setIsLoadingThumbnail(false);
+                  setThumbnailFailed(true);
+                }}
+              />
+              {isLoadingThumbnail && (
+                // ローディング
+                <div className="absolute inset-0 animate-pulse bg-gray-200" />
+              )}
+            </>
           ) : (
             // 画像なし
             <ImageIcon size={48} className="text-gray-300" />
@@ -1,8 +1,8 @@
 // 画像表示コンポーネント
 
 import { useState, useCallback, useRef } from "react";
-import { convertFileSrc } from "@tauri-apps/api/core";
 import { Spinner } from "../common/Spinner";
+import { getImageUrl } from "../../api/tauri";
 
 interface ImageDisplayProps {
   imagePath: string;
@@ -43,8 +43,8 @@ export function ImageDisplay({
     setHasError(true);
   }, []);
 
-  // ローカルファイルパスをWebView表示用URLに変換
-  const imageUrl = convertFileSrc(imagePath);
+  // 画像パスをWebView表示用URLに変換（アーカイブ内の画像にも対応）
+  const imageUrl = getImageUrl(imagePath);
 
   // 表示サイズ: 元画像サイズ × ズーム率
   const displaySize = originalImageSize
@@ -15,7 +15,11 @@ import {
   type AddCardInput,
   type UpdateCardInput,
 } from "../store/profileStore";
-import { validateFolderPath } from "../api/tauri";
+import {
+  onCardStatusChanged,
+  unwatchCardStatuses,
+  watchCardStatuses,
+} from "../api/tauri";
 import { getCurrentWindow, LogicalPosition, LogicalSize, currentMonitor } from "@tauri-apps/api/window";
 import type { Card } from "../types";
 
@@ -115,36 +119,67 @@ export function IndexPage() {
     }
   }, [currentProfile, navigate, updateAppState]);
 
-  // カードのフォルダパス検証
+  // カードのフォルダパス検証（フォルダの作成・削除・リネームを監視して更新）
   useEffect(() => {
-    const validateCards = async () => {
-      const newValidations = new Map<string, CardValidation>();
-
-      for (const card of cards) {
-        try {
-          const isValid = await validateFolderPath(card.folderPath);
-          newValidations.set(card.id, {
-            cardId: card.id,
-            isValid,
-            errorMessage: isValid ? undefined : "フォルダが見つかりません",
-          });
-        } catch {
-          newValidations.set(card.id, {
-            cardId: card.id,
-            isValid: false,
-            errorMessage: "検証エラー",
+    let cancelled = false;
+    let unlisten: (() => void) | undefined;
+
+    const watchCards = async () => {
+      unlisten = await onCardStatusChanged((status) => {
+        setValidations((prev) => {
+          const next = new Map(prev);
+          next.set(status.id, {
+            cardId: status.id,
+            isValid: status.isValid,
+            errorMessage: status.errorMessage ?? undefined,
           });
-        }
+          return next;
+        });
+      });
+      if (cancelled) {
+        unlisten();
+        return;
       }
 
-      setValidations(newValidations);
+      try {
+        const statuses = await watchCardStatuses(cards);
+        if (cancelled) return;
+        setValidations(
+          new Map(
+            statuses.map((status) => [
+              status.id,
+              {
+                cardId: status.id,
+                isValid: status.isValid,
+                errorMessage: status.errorMessage ?? undefined,
+              },
+            ])
+          )
+        );
+      } catch {
+        if (cancelled) return;
+        setValidations(
+          new Map(
+            cards.map((card) => [
+              card.id,
+              { cardId: card.id, isValid: false, errorMessage: "検証エラー" },
+            ])
+          )
+        );
+      }
     };
 
     if (cards.length > 0) {
-      validateCards();
+      watchCards();
     } else {
       setValidations(new Map());
     }
+
+    return () => {
+      cancelled = true;
+      unlisten?.();
+      unwatchCardStatuses().catch(() => {});
+    };
   }, [cards]);
 
   // 選択カードが削除された場合はリセット
@@ -11,7 +11,11 @@ import {
   type ContextMenuItem,
 } from "../components/common/ContextMenu";
 import { useProfileStore } from "../store/profileStore";
-import { saveProfile } from "../api/tauri";
+import {
+  onImagesChanged,
+  unwatchCardFolder,
+  watchCardFolder,
+} from "../api/tauri";
 import {
   useCurrentImage,
   useNavigationState,
@@ -20,6 +24,7 @@ import {
   useViewerStore,
 } from "../store/viewerStore";
 import { copyImageToClipboard, copyTextToClipboard } from "../api/tauri";
+import type { ScanOptions, SortMode } from "../types";
 
 // フィットズーム率を計算
 function calculateFitZoom(displayW: number, displayH: number, imageW: number, imageH: number): number {
@@ -41,7 +46,7 @@ export function ViewerPage() {
   const currentImage = useCurrentImage();
   const { currentIndex, totalImages, actualIndex } = useNavigationState();
   const { hFlipEnabled, shuffleEnabled } = useViewerOptions();
-  const { loadImages, goToNext, goToPrev, toggleHFlip, toggleShuffle, setZoomLevel, setOriginalImageSize, reset } =
+  const { loadImages, refreshImages, goToNext, goToPrev, toggleHFlip, toggleShuffle, setZoomLevel, setOriginalImageSize, reset } =
     useViewerActions();
   const isLoading = useViewerStore((state) => state.isLoading);
   const error = useViewerStore((state) => state.error);
@@ -271,13 +276,10 @@ export function ViewerPage() {
     });
 
     // プロファイルをディスクに保存
-    const { currentProfile: profile, currentProfilePath: path } =
-      useProfileStore.getState();
-    if (profile && path) {
-      saveProfile(path, profile).catch((e) =>
-        console.error("プロファイル保存に失敗:", e)
-      );
-    }
+    useProfileStore
+      .getState()
+      .syncCurrentProfile()
+      .catch((e) => console.error("プロファイル保存に失敗:", e));
   }, [updateAppState]);
 
   // 画像表示・オプション変更時にビューア状態を保存
@@ -299,13 +301,10 @@ export function ViewerPage() {
     updateAppState({ lastPage: "index" });
 
     // 保存実行
-    const { currentProfile: profile, currentProfilePath: path } =
-      useProfileStore.getState();
-    if (profile && path) {
-      saveProfile(path, profile).catch((e) =>
-        console.error("プロファイル保存に失敗:", e)
-      );
-    }
+    useProfileStore
+      .getState()
+      .syncCurrentProfile()
+      .catch((e) => console.error("プロファイル保存に失敗:", e));
 
     reset();
     navigate("/");
@@ -314,6 +313,17 @@ export function ViewerPage() {
   // 画像一覧の読み込み（プリミティブ値を依存配列に使用し、不要な再読み込みを防止）
   const cardTitle = card?.title ?? "";
   const folderPath = card?.folderPath;
+  // 走査オプション・ソート方法は内容が変わった場合のみ再読み込みする
+  const scanOptionsKey = JSON.stringify(card?.scanOptions ?? null);
+  const scanOptions = useMemo<ScanOptions | undefined>(
+    () => JSON.parse(scanOptionsKey) ?? undefined,
+    [scanOptionsKey]
+  );
+  const sortModeKey = JSON.stringify(card?.sortMode ?? null);
+  const sortMode = useMemo<SortMode | undefined>(
+    () => JSON.parse(sortModeKey) ?? undefined,
+    [sortModeKey]
+  );
 
   useEffect(() => {
     if (!cardId || !folderPath) return;
@@ -326,11 +336,43 @@ export function ViewerPage() {
       cardId,
       cardTitle,
       folderPath,
+      scanOptions,
+      sortMode,
       isRestore ? appState.lastImageIndex : 0,
       isRestore ? appState.hFlipEnabled : false,
       isRestore ? appState.shuffleEnabled : false,
     );
-  }, [cardId, cardTitle, folderPath, loadImages]); // currentProfileは意図的に依存配列から除外
+  }, [cardId, cardTitle, folderPath, scanOptions, sortMode, loadImages]); // currentProfileは意図的に依存配列から除外
+
+  // フォルダを監視し、画像の追加・削除・リネーム時に画像一覧を再取得
+  useEffect(() => {
+    if (!cardId || !folderPath) return;
+
+    let cancelled = false;
+    let unlisten: (() => void) | undefined;
+
+    const setup = async () => {
+      unlisten = await onImagesChanged((changed) => {
+        if (changed.cardId === cardId) {
+          refreshImages();
+        }
+      });
+      if (cancelled) {
+        unlisten();
+        return;
+      }
+      watchCardFolder(cardId, folderPath, scanOptions).catch((e) =>
+        console.error("フォルダ監視の開始に失敗:", e)
+      );
+    };
+    setup();
+
+    return () => {
+      cancelled = true;
+      unlisten?.();
+      unwatchCardFolder().catch(() => {});
+    };
+  }, [cardId, folderPath, scanOptions, refreshImages]);
 
   // プロファイルが読み込まれていない場合はStartupPageへ
   useEffect(() => {
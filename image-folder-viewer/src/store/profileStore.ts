@@ -2,7 +2,23 @@
 
 import { create } from "zustand";
 import { useShallow } from "zustand/react/shallow";
-import type { ProfileData, AppConfig, RecentProfile, Card, AppState } from "../types";
+import type {
+  ProfileData,
+  AppConfig,
+  RecentProfile,
+  Card,
+  CardRelink,
+  AppState,
+  MergeConflict,
+  ProfileLockStatus,
+  Tag,
+  ImageAnnotationUpdate,
+  SmartCollection,
+  SmartQuery,
+  Album,
+  ScanOptions,
+  SortMode,
+} from "../types";
 import {
   loadProfile,
   saveProfile,
@@ -12,11 +28,48 @@ import {
   removeRecentProfile,
   selectProfileFile,
   selectProfileSavePath,
+  applyCardRelinks,
+  restoreProfileBackup,
+  acquireProfileLock,
+  releaseProfileLock,
+  mergeProfileChanges,
+  ProfileConflictError,
+  createTag as createTagCommand,
+  renameTag as renameTagCommand,
+  setTagColor as setTagColorCommand,
+  deleteTag as deleteTagCommand,
+  attachTags as attachTagsCommand,
+  detachTags as detachTagsCommand,
+  setImageAnnotations as setImageAnnotationsCommand,
+  createSmartCollection as createSmartCollectionCommand,
+  updateSmartCollection as updateSmartCollectionCommand,
+  deleteSmartCollection as deleteSmartCollectionCommand,
+  createAlbum as createAlbumCommand,
+  renameAlbum as renameAlbumCommand,
+  deleteAlbum as deleteAlbumCommand,
+  addAlbumImages as addAlbumImagesCommand,
+  removeAlbumImages as removeAlbumImagesCommand,
+  reorderAlbumImages as reorderAlbumImagesCommand,
 } from "../api/tauri";
 
 // 空配列の定数（参照の安定性のため）
 const EMPTY_RECENT_PROFILES: RecentProfile[] = [];
 const EMPTY_CARDS: Card[] = [];
+const EMPTY_TAGS: Tag[] = [];
+const EMPTY_SMART_COLLECTIONS: SmartCollection[] = [];
+const EMPTY_ALBUMS: Album[] = [];
+
+// 保存を直列化するためのキュー（競合確認の基準となる更新日時を順に引き継ぐ）
+let saveQueue: Promise<void> = Promise.resolve();
+
+// 開いたプロファイルの状態（競合確認の基準を含む）
+const openedProfileState = (profile: ProfileData, path: string) => ({
+  currentProfile: profile,
+  currentProfilePath: path,
+  baseProfile: profile,
+  diskUpdatedAt: profile.updatedAt,
+  mergeConflicts: [],
+});
 
 // カード追加時の入力データ
 interface AddCardInput {
@@ -25,11 +78,19 @@ interface AddCardInput {
   thumbnail: string | null;
 }
 
+// スマートコレクション更新時の入力データ（部分更新）
+interface UpdateSmartCollectionInput {
+  title?: string;
+  query?: SmartQuery;
+}
+
 // カード更新時の入力データ（部分更新）
 interface UpdateCardInput {
   title?: string;
   folderPath?: string;
   thumbnail?: string | null;
+  scanOptions?: ScanOptions;
+  sortMode?: SortMode;
 }
 
 interface ProfileState {
@@ -37,6 +98,17 @@ interface ProfileState {
   currentProfile: ProfileData | null;
   currentProfilePath: string | null;
 
+  // 読み込み時（前回の保存・統合時）のプロファイルと、ディスク上の更新日時
+  // （保存時の競合確認と3方向の統合に使用）
+  baseProfile: ProfileData | null;
+  diskUpdatedAt: string | null;
+
+  // プロファイルのロック（acquired が false の場合は他のユーザーも開いている）
+  lockStatus: ProfileLockStatus | null;
+
+  // 直近の統合で編集中の値を採用した競合
+  mergeConflicts: MergeConflict[];
+
   // アプリ共通設定
   appConfig: AppConfig | null;
 
@@ -60,23 +132,71 @@ interface ProfileActions {
   openProfileWithDialog: () => Promise<boolean>;
   createProfile: () => Promise<boolean>;
   saveCurrentProfile: () => Promise<void>;
+  // 読み込み表示・エラー表示なしで保存（ビューアやウィンドウ終了時の自動保存用）
+  syncCurrentProfile: () => Promise<void>;
   saveProfileAs: () => Promise<boolean>;
+  restoreBackup: (path: string, backupPath: string) => Promise<boolean>;
   closeProfile: () => void;
+  // 他のユーザーのロックを奪って編集を続ける
+  takeOverLock: () => Promise<void>;
 
   // カード操作
   addCard: (input: AddCardInput) => Card | null;
   updateCard: (cardId: string, input: UpdateCardInput) => Card | null;
   deleteCard: (cardId: string) => boolean;
   reorderCards: (cardIds: string[]) => void;
+  relinkCards: (relinks: CardRelink[]) => Promise<boolean>;
+
+  // タグ操作
+  createTag: (name: string, color?: string | null) => Promise<Tag | null>;
+  renameTag: (tagId: string, name: string) => Promise<boolean>;
+  setTagColor: (tagId: string, color: string | null) => Promise<boolean>;
+  deleteTag: (tagId: string) => Promise<boolean>;
+  attachTags: (cardIds: string[], tagIds: string[]) => Promise<boolean>;
+  detachTags: (cardIds: string[], tagIds: string[]) => Promise<boolean>;
+
+  // 画像の注釈（relativePaths はカードのルートフォルダからの相対パス）
+  setImageAnnotations: (
+    cardId: string,
+    relativePaths: string[],
+    update: ImageAnnotationUpdate
+  ) => Promise<boolean>;
+
+  // スマートコレクション操作
+  addSmartCollection: (
+    title: string,
+    query: SmartQuery
+  ) => Promise<SmartCollection | null>;
+  updateSmartCollection: (
+    collectionId: string,
+    input: UpdateSmartCollectionInput
+  ) => Promise<SmartCollection | null>;
+  deleteSmartCollection: (collectionId: string) => Promise<boolean>;
+
+  // アルバム操作
+  createAlbum: (title: string) => Promise<Album | null>;
+  renameAlbum: (albumId: string, title: string) => Promise<boolean>;
+  deleteAlbum: (albumId: string) => Promise<boolean>;
+  addAlbumImages: (
+    albumId: string,
+    paths: string[],
+    index?: number
+  ) => Promise<boolean>;
+  removeAlbumImages: (albumId: string, paths: string[]) => Promise<boolean>;
+  reorderAlbumImages: (albumId: string, paths: string[]) => Promise<boolean>;
 
   // appState更新
   updateAppState: (partial: Partial<AppState>) => void;
 
+  // パスの保存方法を変更（true: プロファイルファイルのフォルダ以下を相対パスで保存）
+  setRelativePaths: (enabled: boolean) => void;
+
   // 履歴操作
   removeFromHistory: (path: string) => Promise<void>;
 
   // エラークリア
   clearError: () => void;
+  clearMergeConflicts: () => void;
 }
 
 export const useProfileStore = create<ProfileState & ProfileActions>(
@@ -84,6 +204,10 @@ export const useProfileStore = create<ProfileState & ProfileActions>(
     // 初期状態
     currentProfile: null,
     currentProfilePath: null,
+    baseProfile: null,
+    diskUpdatedAt: null,
+    lockStatus: null,
+    mergeConflicts: [],
     appConfig: null,
     isLoading: false,
     error: null,
@@ -109,12 +233,12 @@ export const useProfileStore = create<ProfileState & ProfileActions>(
             await addRecentProfile(path);
             const updatedConfig = await getAppConfig();
             set({
-              currentProfile: profile,
-              currentProfilePath: path,
+              ...openedProfileState(profile, path),
               appConfig: updatedConfig,
               isAutoOpening: false,
               isLoading: false,
             });
+            await lockProfile(path);
           } catch {
             // 自動オープン失敗: エラーなしで StartupPage を表示
             set({ isAutoOpening: false, isLoading: false });
@@ -139,12 +263,13 @@ export const useProfileStore = create<ProfileState & ProfileActions>(
         const profile = await loadProfile(path);
         await addRecentProfile(path);
         const config = await getAppConfig();
+        await unlockProfile(path);
         set({
-          currentProfile: profile,
-          currentProfilePath: path,
+          ...openedProfileState(profile, path),
           appConfig: config,
           isLoading: false,
         });
+        await lockProfile(path);
       } catch (e) {
         set({
           error: `プロファイルを開けませんでした: ${e}`,
@@ -178,12 +303,13 @@ export const useProfileStore = create<ProfileState & ProfileActions>(
           const profile = await createNewProfile(path);
           await addRecentProfile(path);
           const config = await getAppConfig();
+          await unlockProfile(path);
           set({
-            currentProfile: profile,
-            currentProfilePath: path,
+            ...openedProfileState(profile, path),
             appConfig: config,
             isLoading: false,
           });
+          await lockProfile(path);
           return true;
         }
         return false;
@@ -206,7 +332,7 @@ export const useProfileStore = create<ProfileState & ProfileActions>(
 
       set({ isLoading: true, error: null });
       try {
-        await saveProfile(currentProfilePath, currentProfile);
+        await get().syncCurrentProfile();
         set({ isLoading: false });
       } catch (e) {
         set({
@@ -216,6 +342,75 @@ export const useProfileStore = create<ProfileState & ProfileActions>(
       }
     },
 
+    // 現在のプロファイルを保存（他のユーザーの保存と競合した場合は統合して保存し直す）
+    syncCurrentProfile: () => {
+      const save = async () => {
+        const { currentProfile, currentProfilePath, baseProfile, diskUpdatedAt } =
+          get();
+        if (!currentProfile || !currentProfilePath) return;
+
+        try {
+          const updatedAt = await saveProfile(
+            currentProfilePath,
+            currentProfile,
+            diskUpdatedAt ?? undefined
+          );
+          set({
+            baseProfile: { ...currentProfile, updatedAt },
+            diskUpdatedAt: updatedAt,
+          });
+        } catch (e) {
+          if (!(e instanceof ProfileConflictError) || !baseProfile) throw e;
+
+          // ディスク上の変更と統合し、統合結果を保存し直す（再度競合した場合はエラー）
+          const merge = await mergeProfileChanges(
+            currentProfilePath,
+            baseProfile,
+            currentProfile
+          );
+          const updatedAt = await saveProfile(
+            currentProfilePath,
+            merge.profile,
+            merge.diskProfile.updatedAt
+          );
+          set({
+            currentProfile: merge.profile,
+            baseProfile: { ...merge.profile, updatedAt },
+            diskUpdatedAt: updatedAt,
+            mergeConflicts: merge.conflicts,
+          });
+        }
+      };
+
+      const result = saveQueue.then(save);
+      saveQueue = result.catch(() => undefined);
+      return result;
+    },
+
+    // バックアップから復元して開く（壊れて開けないプロファイルにも使用できる）
+    restoreBackup: async (path: string, backupPath: string) => {
+      set({ isLoading: true, error: null });
+      try {
+        const profile = await restoreProfileBackup(path, backupPath);
+        await addRecentProfile(path);
+        const config = await getAppConfig();
+        await unlockProfile(path);
+        set({
+          ...openedProfileState(profile, path),
+          appConfig: config,
+          isLoading: false,
+        });
+        await lockProfile(path);
+        return true;
+      } catch (e) {
+        set({
+          error: `バックアップからの復元に失敗しました: ${e}`,
+          isLoading: false,
+        });
+        return false;
+      }
+    },
+
     // 別名で保存
     saveProfileAs: async () => {
       const { currentProfile } = get();
@@ -228,14 +423,20 @@ export const useProfileStore = create<ProfileState & ProfileActions>(
         const path = await selectProfileSavePath();
         if (path) {
           set({ isLoading: true, error: null });
-          await saveProfile(path, currentProfile);
+          // 別名で保存する場合は保存先の内容によらず上書きする
+          const updatedAt = await saveProfile(path, currentProfile);
           await addRecentProfile(path);
           const config = await getAppConfig();
+          await unlockProfile(path);
           set({
             currentProfilePath: path,
+            baseProfile: { ...currentProfile, updatedAt },
+            diskUpdatedAt: updatedAt,
+            mergeConflicts: [],
             appConfig: config,
             isLoading: false,
           });
+          await lockProfile(path);
           return true;
         }
         return false;
@@ -250,13 +451,30 @@ export const useProfileStore = create<ProfileState & ProfileActions>(
 
     // プロファイルを閉じる
     closeProfile: () => {
+      void unlockProfile();
       set({
         currentProfile: null,
         currentProfilePath: null,
+        baseProfile: null,
+        diskUpdatedAt: null,
+        lockStatus: null,
+        mergeConflicts: [],
         error: null,
       });
     },
 
+    // 他のユーザーのロックを奪う
+    takeOverLock: async () => {
+      const { currentProfilePath } = get();
+      if (!currentProfilePath) return;
+      try {
+        const lockStatus = await acquireProfileLock(currentProfilePath, true);
+        set({ lockStatus });
+      } catch (e) {
+        set({ error: `プロファイルのロックに失敗しました: ${e}` });
+      }
+    },
+
     // カードを追加
     addCard: (input: AddCardInput) => {
       const { currentProfile } = get();
@@ -349,11 +567,17 @@ export const useProfileStore = create<ProfileState & ProfileActions>(
         (ct) => ct.cardId !== cardId
       );
 
+      // カード内の画像の注釈も削除
+      const newImageAnnotations = (currentProfile.imageAnnotations ?? []).filter(
+        (a) => a.cardId !== cardId
+      );
+
       set({
         currentProfile: {
           ...currentProfile,
           cards: newCards,
           cardTags: newCardTags,
+          imageAnnotations: newImageAnnotations,
           updatedAt: now,
         },
       });
@@ -399,6 +623,196 @@ export const useProfileStore = create<ProfileState & ProfileActions>(
       });
     },
 
+    // カードのフォルダを一括で付け替え
+    relinkCards: async (relinks: CardRelink[]) => {
+      const { currentProfile } = get();
+      if (!currentProfile) {
+        set({ error: "プロファイルが開かれていません" });
+        return false;
+      }
+      if (relinks.length === 0) return true;
+
+      try {
+        const updated = await applyCardRelinks(currentProfile, relinks);
+        const latest = get().currentProfile;
+        if (!latest) return false;
+        set({
+          currentProfile: {
+            ...latest,
+            cards: updated.cards,
+            albums: updated.albums,
+            updatedAt: updated.updatedAt,
+          },
+        });
+        return true;
+      } catch (e) {
+        set({ error: `フォルダの付け替えに失敗しました: ${e}` });
+        return false;
+      }
+    },
+
+    // タグを作成
+    createTag: async (name: string, color: string | null = null) => {
+      const updated = await applyProfileCommand(
+        (profile) => createTagCommand(profile, name, color),
+        "タグの作成に失敗しました"
+      );
+      return updated?.tags[updated.tags.length - 1] ?? null;
+    },
+
+    // タグ名を変更
+    renameTag: async (tagId: string, name: string) => {
+      const updated = await applyProfileCommand(
+        (profile) => renameTagCommand(profile, tagId, name),
+        "タグ名の変更に失敗しました"
+      );
+      return updated !== null;
+    },
+
+    // タグの色を変更
+    setTagColor: async (tagId: string, color: string | null) => {
+      const updated = await applyProfileCommand(
+        (profile) => setTagColorCommand(profile, tagId, color),
+        "タグの色の変更に失敗しました"
+      );
+      return updated !== null;
+    },
+
+    // タグを削除（カードへのタグ付けも削除）
+    deleteTag: async (tagId: string) => {
+      const updated = await applyProfileCommand(
+        (profile) => deleteTagCommand(profile, tagId),
+        "タグの削除に失敗しました"
+      );
+      return updated !== null;
+    },
+
+    // 複数のカードにタグを付ける
+    attachTags: async (cardIds: string[], tagIds: string[]) => {
+      const updated = await applyProfileCommand(
+        (profile) => attachTagsCommand(profile, cardIds, tagIds),
+        "タグ付けに失敗しました"
+      );
+      return updated !== null;
+    },
+
+    // 複数のカードからタグを外す
+    detachTags: async (cardIds: string[], tagIds: string[]) => {
+      const updated = await applyProfileCommand(
+        (profile) => detachTagsCommand(profile, cardIds, tagIds),
+        "タグの解除に失敗しました"
+      );
+      return updated !== null;
+    },
+
+    // 画像の注釈を一括で変更
+    setImageAnnotations: async (
+      cardId: string,
+      relativePaths: string[],
+      update: ImageAnnotationUpdate
+    ) => {
+      const updated = await applyProfileCommand(
+        (profile) =>
+          setImageAnnotationsCommand(profile, cardId, relativePaths, update),
+        "画像の注釈の変更に失敗しました"
+      );
+      return updated !== null;
+    },
+
+    // スマートコレクションを追加
+    addSmartCollection: async (title: string, query: SmartQuery) => {
+      const updated = await applyProfileCommand(
+        (profile) => createSmartCollectionCommand(profile, title, query),
+        "コレクションの作成に失敗しました"
+      );
+      const collections = updated?.smartCollections ?? [];
+      return collections[collections.length - 1] ?? null;
+    },
+
+    // スマートコレクションを更新
+    updateSmartCollection: async (
+      collectionId: string,
+      input: UpdateSmartCollectionInput
+    ) => {
+      const updated = await applyProfileCommand(
+        (profile) =>
+          updateSmartCollectionCommand(
+            profile,
+            collectionId,
+            input.title,
+            input.query
+          ),
+        "コレクションの更新に失敗しました"
+      );
+      return (
+        updated?.smartCollections?.find((c) => c.id === collectionId) ?? null
+      );
+    },
+
+    // スマートコレクションを削除
+    deleteSmartCollection: async (collectionId: string) => {
+      const updated = await applyProfileCommand(
+        (profile) => deleteSmartCollectionCommand(profile, collectionId),
+        "コレクションの削除に失敗しました"
+      );
+      return updated !== null;
+    },
+
+    // アルバムを作成
+    createAlbum: async (title: string) => {
+      const updated = await applyProfileCommand(
+        (profile) => createAlbumCommand(profile, title),
+        "アルバムの作成に失敗しました"
+      );
+      const albums = updated?.albums ?? [];
+      return albums[albums.length - 1] ?? null;
+    },
+
+    // アルバム名を変更
+    renameAlbum: async (albumId: string, title: string) => {
+      const updated = await applyProfileCommand(
+        (profile) => renameAlbumCommand(profile, albumId, title),
+        "アルバム名の変更に失敗しました"
+      );
+      return updated !== null;
+    },
+
+    // アルバムを削除
+    deleteAlbum: async (albumId: string) => {
+      const updated = await applyProfileCommand(
+        (profile) => deleteAlbumCommand(profile, albumId),
+        "アルバムの削除に失敗しました"
+      );
+      return updated !== null;
+    },
+
+    // アルバムに画像を追加
+    addAlbumImages: async (albumId: string, paths: string[], index?: number) => {
+      const updated = await applyProfileCommand(
+        (profile) => addAlbumImagesCommand(profile, albumId, paths, index),
+        "アルバムへの追加に失敗しました"
+      );
+      return updated !== null;
+    },
+
+    // アルバムから画像を外す
+    removeAlbumImages: async (albumId: string, paths: string[]) => {
+      const updated = await applyProfileCommand(
+        (profile) => removeAlbumImagesCommand(profile, albumId, paths),
+        "アルバムからの削除に失敗しました"
+      );
+      return updated !== null;
+    },
+
+    // アルバムの画像を並べ替え
+    reorderAlbumImages: async (albumId: string, paths: string[]) => {
+      const updated = await applyProfileCommand(
+        (profile) => reorderAlbumImagesCommand(profile, albumId, paths),
+        "アルバムの並べ替えに失敗しました"
+      );
+      return updated !== null;
+    },
+
     // appState更新
     updateAppState: (partial: Partial<AppState>) => {
       const { currentProfile } = get();
@@ -417,6 +831,20 @@ export const useProfileStore = create<ProfileState & ProfileActions>(
       });
     },
 
+    // パスの保存方法を変更
+    setRelativePaths: (enabled: boolean) => {
+      const { currentProfile } = get();
+      if (!currentProfile) return;
+
+      set({
+        currentProfile: {
+          ...currentProfile,
+          relativePaths: enabled,
+          updatedAt: new Date().toISOString(),
+        },
+      });
+    },
+
     // 履歴から削除
     removeFromHistory: async (path: string) => {
       try {
@@ -432,9 +860,64 @@ export const useProfileStore = create<ProfileState & ProfileActions>(
     clearError: () => {
       set({ error: null });
     },
+
+    clearMergeConflicts: () => {
+      set({ mergeConflicts: [] });
+    },
   })
 );
 
+// タグ・画像の注釈・スマートコレクション・アルバムの編集コマンドを実行し、結果を現在のプロファイルに反映
+// （実行中に変更されたカードなどを失わないよう、タグ・注釈・コレクション・アルバムのみを反映する）
+async function applyProfileCommand(
+  command: (profile: ProfileData) => Promise<ProfileData>,
+  errorMessage: string
+): Promise<ProfileData | null> {
+  const { currentProfile } = useProfileStore.getState();
+  if (!currentProfile) {
+    useProfileStore.setState({ error: "プロファイルが開かれていません" });
+    return null;
+  }
+
+  try {
+    const updated = await command(currentProfile);
+    const latest = useProfileStore.getState().currentProfile;
+    if (!latest) return null;
+    useProfileStore.setState({
+      currentProfile: {
+        ...latest,
+        tags: updated.tags,
+        cardTags: updated.cardTags,
+        imageAnnotations: updated.imageAnnotations,
+        smartCollections: updated.smartCollections,
+        albums: updated.albums,
+        updatedAt: updated.updatedAt,
+      },
+    });
+    return updated;
+  } catch (e) {
+    useProfileStore.setState({ error: `${errorMessage}: ${e}` });
+    return null;
+  }
+}
+
+// プロファイルのロックを取得（ロックは勧告のみのため、失敗しても開く）
+async function lockProfile(path: string): Promise<void> {
+  try {
+    const lockStatus = await acquireProfileLock(path);
+    useProfileStore.setState({ lockStatus });
+  } catch {
+    useProfileStore.setState({ lockStatus: null });
+  }
+}
+
+// 現在のプロファイルのロックを解除（nextPath と同じプロファイルの場合は解除しない）
+async function unlockProfile(nextPath?: string): Promise<void> {
+  const { currentProfilePath } = useProfileStore.getState();
+  if (!currentProfilePath || currentProfilePath === nextPath) return;
+  await releaseProfileLock(currentProfilePath).catch(() => undefined);
+}
+
 // カスタムフック: 最近使用したプロファイル一覧
 export const useRecentProfiles = (): RecentProfile[] => {
   return useProfileStore(
@@ -471,9 +954,52 @@ export const useCardActions = () => {
       updateCard: state.updateCard,
       deleteCard: state.deleteCard,
       reorderCards: state.reorderCards,
+      relinkCards: state.relinkCards,
+    }))
+  );
+};
+
+// カスタムフック: スマートコレクション一覧（sortOrder順にソート済み）
+export const useSmartCollections = (): SmartCollection[] => {
+  return useProfileStore(
+    useShallow((state) => {
+      const collections =
+        state.currentProfile?.smartCollections ?? EMPTY_SMART_COLLECTIONS;
+      return [...collections].sort((a, b) => a.sortOrder - b.sortOrder);
+    })
+  );
+};
+
+// カスタムフック: アルバム一覧（sortOrder順にソート済み）
+export const useAlbums = (): Album[] => {
+  return useProfileStore(
+    useShallow((state) => {
+      const albums = state.currentProfile?.albums ?? EMPTY_ALBUMS;
+      return [...albums].sort((a, b) => a.sortOrder - b.sortOrder);
+    })
+  );
+};
+
+// カスタムフック: タグ一覧
+export const useTags = (): Tag[] => {
+  return useProfileStore(
+    useShallow((state) => state.currentProfile?.tags ?? EMPTY_TAGS)
+  );
+};
+
+// カスタムフック: タグ操作アクション
+export const useTagActions = () => {
+  return useProfileStore(
+    useShallow((state) => ({
+      createTag: state.createTag,
+      renameTag: state.renameTag,
+      setTagColor: state.setTagColor,
+      deleteTag: state.deleteTag,
+      attachTags: state.attachTags,
+      detachTags: state.detachTags,
     }))
   );
 };
 
 // 型のエクスポート
-export type { AddCardInput, UpdateCardInput };
+export type { AddCardInput, UpdateCardInput, UpdateSmartCollectionInput };
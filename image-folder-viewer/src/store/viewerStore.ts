@@ -2,17 +2,28 @@
 
 import { create } from "zustand";
 import { useShallow } from "zustand/react/shallow";
-import type { ImageFile } from "../types";
-import { getImagesInFolder } from "../api/tauri";
+import type { ImageFile, ProfileData, ScanOptions, SortMode } from "../types";
+import {
+  getImagesInFolder,
+  listAlbumImages,
+  listSmartCollectionImages,
+} from "../api/tauri";
 
 // 空配列の定数（参照の安定性のため）
 const EMPTY_IMAGES: ImageFile[] = [];
 
 interface ViewerState {
-  // 表示中のカード情報
+  // 表示中のカード情報（スマートコレクション・アルバムの場合は collectionId・albumId のみ設定し、
+  // cardTitle にはコレクション名・アルバム名）
   cardId: string | null;
   cardTitle: string;
   folderPath: string | null;
+  collectionId: string | null;
+  albumId: string | null;
+
+  // カードの走査オプション・ソート方法（画像一覧の再取得時にも使用）
+  scanOptions: ScanOptions | null;
+  sortMode: SortMode | null;
 
   // 画像一覧
   images: ImageFile[];
@@ -38,11 +49,36 @@ interface ViewerActions {
     cardId: string,
     cardTitle: string,
     folderPath: string,
+    scanOptions?: ScanOptions,
+    sortMode?: SortMode,
+    initialIndex?: number,
+    hFlip?: boolean,
+    shuffle?: boolean
+  ) => Promise<void>;
+
+  // スマートコレクションの画像一覧を読み込む（カードと同様に表示する）
+  loadCollection: (
+    profile: ProfileData,
+    collectionId: string,
+    title: string,
+    initialIndex?: number,
+    hFlip?: boolean,
+    shuffle?: boolean
+  ) => Promise<void>;
+
+  // アルバムの画像一覧を読み込む（カードと同様に表示する）
+  loadAlbum: (
+    profile: ProfileData,
+    albumId: string,
+    title: string,
     initialIndex?: number,
     hFlip?: boolean,
     shuffle?: boolean
   ) => Promise<void>;
 
+  // 画像一覧を再取得（フォルダの変更時、表示中の画像をできるだけ維持）
+  refreshImages: () => Promise<void>;
+
   // ナビゲーション
   goToNext: () => void;
   goToPrev: () => void;
@@ -72,6 +108,10 @@ const initialState: ViewerState = {
   cardId: null,
   cardTitle: "",
   folderPath: null,
+  collectionId: null,
+  albumId: null,
+  scanOptions: null,
+  sortMode: null,
   images: [],
   currentIndex: 0,
   shuffledIndices: null,
@@ -102,57 +142,185 @@ function generateShuffledIndices(length: number, currentIndex: number): number[]
   return indices;
 }
 
+// 読み込み対象（カード・スマートコレクション・アルバム）
+type ImageSource = Pick<
+  ViewerState,
+  | "cardId"
+  | "cardTitle"
+  | "folderPath"
+  | "collectionId"
+  | "albumId"
+  | "scanOptions"
+  | "sortMode"
+>;
+
+// 画像一覧を取得して表示状態を初期化
+async function loadInto(
+  set: (state: Partial<ViewerState>) => void,
+  fetchImages: () => Promise<ImageFile[]>,
+  source: ImageSource,
+  emptyMessage: string,
+  initialIndex: number,
+  hFlip: boolean,
+  shuffle: boolean
+): Promise<void> {
+  set({ isLoading: true, error: null });
+
+  try {
+    const images = await fetchImages();
+
+    if (images.length === 0) {
+      set({
+        ...initialState,
+        ...source,
+        error: emptyMessage,
+        isLoading: false,
+      });
+      return;
+    }
+
+    // 初期インデックスを範囲内に収める
+    const validIndex = Math.max(0, Math.min(initialIndex, images.length - 1));
+
+    // シャッフルが有効な場合はインデックス配列を生成
+    const shuffledIndices = shuffle
+      ? generateShuffledIndices(images.length, validIndex)
+      : null;
+
+    set({
+      ...source,
+      images,
+      currentIndex: shuffle ? 0 : validIndex, // シャッフル時は0から開始（先頭が元の位置）
+      shuffledIndices,
+      hFlipEnabled: hFlip,
+      shuffleEnabled: shuffle,
+      isLoading: false,
+      error: null,
+    });
+  } catch (e) {
+    set({
+      ...initialState,
+      ...source,
+      error: `画像の読み込みに失敗しました: ${e}`,
+      isLoading: false,
+    });
+  }
+}
+
 export const useViewerStore = create<ViewerState & ViewerActions>((set, get) => ({
   ...initialState,
 
   // 画像一覧を読み込む
-  loadImages: async (cardId, cardTitle, folderPath, initialIndex = 0, hFlip = false, shuffle = false) => {
-    set({ isLoading: true, error: null });
+  loadImages: async (
+    cardId,
+    cardTitle,
+    folderPath,
+    scanOptions,
+    sortMode,
+    initialIndex = 0,
+    hFlip = false,
+    shuffle = false
+  ) => {
+    await loadInto(
+      set,
+      () => getImagesInFolder(folderPath, scanOptions, sortMode),
+      {
+        cardId,
+        cardTitle,
+        folderPath,
+        collectionId: null,
+        albumId: null,
+        scanOptions: scanOptions ?? null,
+        sortMode: sortMode ?? null,
+      },
+      "フォルダ内に画像がありません",
+      initialIndex,
+      hFlip,
+      shuffle
+    );
+  },
+
+  // スマートコレクションの画像一覧を読み込む
+  loadCollection: async (profile, collectionId, title, initialIndex = 0, hFlip = false, shuffle = false) => {
+    await loadInto(
+      set,
+      () => listSmartCollectionImages(profile, collectionId),
+      {
+        cardId: null,
+        cardTitle: title,
+        folderPath: null,
+        collectionId,
+        albumId: null,
+        scanOptions: null,
+        sortMode: null,
+      },
+      "条件に一致する画像がありません",
+      initialIndex,
+      hFlip,
+      shuffle
+    );
+  },
+
+  // アルバムの画像一覧を読み込む
+  loadAlbum: async (profile, albumId, title, initialIndex = 0, hFlip = false, shuffle = false) => {
+    await loadInto(
+      set,
+      () => listAlbumImages(profile, albumId),
+      {
+        cardId: null,
+        cardTitle: title,
+        folderPath: null,
+        collectionId: null,
+        albumId,
+        scanOptions: null,
+        sortMode: null,
+      },
+      "アルバムに画像がありません",
+      initialIndex,
+      hFlip,
+      shuffle
+    );
+  },
+
+  // 画像一覧を再取得
+  refreshImages: async () => {
+    const {
+      folderPath,
+      scanOptions,
+      sortMode,
+      images,
+      currentIndex,
+      shuffledIndices,
+      shuffleEnabled,
+    } = get();
+    if (!folderPath) return;
 
     try {
-      const images = await getImagesInFolder(folderPath);
-
-      if (images.length === 0) {
-        set({
-          ...initialState,
-          cardId,
-          cardTitle,
-          folderPath,
-          error: "フォルダ内に画像がありません",
-          isLoading: false,
-        });
-        return;
-      }
-
-      // 初期インデックスを範囲内に収める
-      const validIndex = Math.max(0, Math.min(initialIndex, images.length - 1));
-
-      // シャッフルが有効な場合はインデックス配列を生成
-      const shuffledIndices = shuffle
-        ? generateShuffledIndices(images.length, validIndex)
-        : null;
+      const newImages = await getImagesInFolder(
+        folderPath,
+        scanOptions ?? undefined,
+        sortMode ?? undefined
+      );
+      // 取得中に別のカードへ移動した場合は破棄
+      if (get().folderPath !== folderPath) return;
+
+      const actualIndex = shuffledIndices ? shuffledIndices[currentIndex] : currentIndex;
+      const currentPath = images[actualIndex]?.path;
+      const foundIndex = newImages.findIndex((image) => image.path === currentPath);
+      // 表示中の画像が削除された場合は同じ位置（範囲外なら末尾）の画像を表示
+      const newIndex =
+        foundIndex !== -1 ? foundIndex : Math.max(0, Math.min(actualIndex, newImages.length - 1));
 
       set({
-        cardId,
-        cardTitle,
-        folderPath,
-        images,
-        currentIndex: shuffle ? 0 : validIndex, // シャッフル時は0から開始（先頭が元の位置）
-        shuffledIndices,
-        hFlipEnabled: hFlip,
-        shuffleEnabled: shuffle,
-        isLoading: false,
-        error: null,
+        images: newImages,
+        currentIndex: shuffleEnabled ? 0 : newIndex,
+        shuffledIndices: shuffleEnabled
+          ? generateShuffledIndices(newImages.length, newIndex)
+          : null,
+        error: newImages.length === 0 ? "フォルダ内に画像がありません" : null,
       });
     } catch (e) {
-      set({
-        ...initialState,
-        cardId,
-        cardTitle,
-        folderPath,
-        error: `画像の読み込みに失敗しました: ${e}`,
-        isLoading: false,
-      });
+      console.error("画像一覧の再取得に失敗:", e);
     }
   },
 
@@ -326,6 +494,9 @@ export const useViewerActions = () => {
   return useViewerStore(
     useShallow((state) => ({
       loadImages: state.loadImages,
+      loadCollection: state.loadCollection,
+      loadAlbum: state.loadAlbum,
+      refreshImages: state.refreshImages,
       goToNext: state.goToNext,
       goToPrev: state.goToPrev,
       goToIndex: state.goToIndex,
@@ -1,5 +1,30 @@
 // 型定義
 
+// フォルダ走査オプション（カードごとに保存）
+export interface ScanOptions {
+  /** サブフォルダを再帰的に走査する */
+  recursive: boolean;
+  /** 再帰走査の最大深さ（null は無制限、0 は直下のみ） */
+  maxDepth: number | null;
+  /** シンボリックリンクをたどる */
+  followSymlinks: boolean;
+}
+
+// 画像一覧のソートキー
+export type SortKey =
+  | "name" // ファイル名（自然順）
+  | "modified" // 更新日時
+  | "created" // 作成日時
+  | "size" // ファイルサイズ
+  | "exifDate" // EXIF撮影日時
+  | "dimensions"; // 画像サイズ（ピクセル数）
+
+// 画像一覧のソート方法（カードごとに保存）
+export interface SortMode {
+  key: SortKey;
+  direction: "asc" | "desc";
+}
+
 // カード
 export interface Card {
   id: string;
@@ -9,19 +34,59 @@ export interface Card {
   sortOrder: number;
   createdAt: string;
   updatedAt: string;
+  /** フォルダ走査オプション（新規作成したカードでは省略） */
+  scanOptions?: ScanOptions;
+  /** 画像一覧のソート方法（新規作成したカードでは省略） */
+  sortMode?: SortMode;
 }
 
+// カードのフォルダの状態
+export type CardStatus =
+  | "ok"
+  | "missing"
+  | "notADirectory"
+  | "permissionDenied"
+  | "noImages"
+  | "unreadable";
+
 // カード（検証結果付き）
 export interface CardWithStatus extends Card {
   isValid: boolean;
-  errorMessage?: string;
+  errorMessage?: string | null;
+  status: CardStatus;
+  imageCount?: number | null; // フォルダを走査していない場合は null
+  thumbnailMissing: boolean; // サムネイルに指定した画像が見つからない
+}
+
+// カードの移動先の候補
+export interface RelinkCandidate {
+  folderPath: string;
+  score: number; // 一致度（0〜1）
+  nameMatched: boolean; // フォルダ名（アーカイブはファイル名）が一致した
+  matchedFiles: number; // 以前の画像とファイル名が一致した画像数
+  imageCount: number;
+}
+
+// 見つからないカードの移動先の提案
+export interface RelinkProposal {
+  cardId: string;
+  oldFolderPath: string;
+  knownFiles: number; // ファイル名がわかっている以前の画像数
+  candidates: RelinkCandidate[]; // 一致度の高い順
+  suggestedFolderPath?: string | null; // 推奨する移動先（一意に決まらない場合は null）
+}
+
+// カードの移動先の指定
+export interface CardRelink {
+  cardId: string;
+  folderPath: string;
 }
 
 // タグ情報
 export interface Tag {
   id: string;
   name: string;
-  color?: string;
+  color?: string | null; // "#rrggbb" 形式
 }
 
 // カードとタグの関連
@@ -30,6 +95,96 @@ export interface CardTag {
   tagId: string;
 }
 
+// タグの条件式
+export type TagExpression =
+  | { op: "tag"; tagId: string }
+  | { op: "and"; operands: TagExpression[] } // 空の場合はすべてのカード
+  | { op: "or"; operands: TagExpression[] } // 空の場合は該当なし
+  | { op: "not"; operand: TagExpression };
+
+// 画像の色ラベル
+export type ColorLabel =
+  | "red"
+  | "orange"
+  | "yellow"
+  | "green"
+  | "blue"
+  | "purple"
+  | "gray";
+
+// 画像ごとの注釈（カードIDと相対パスで画像を識別）
+export interface ImageAnnotation {
+  cardId: string;
+  relativePath: string; // カードのルートフォルダからの相対パス（区切り文字は "/"）
+  rating: number; // 0〜5（0 は未評価）
+  favorite: boolean;
+  colorLabel?: ColorLabel | null;
+  tagIds: string[]; // プロファイルのタグのID
+  updatedAt: string;
+}
+
+// 画像の注釈の変更内容（省略した項目は変更しない）
+export interface ImageAnnotationUpdate {
+  rating?: number;
+  favorite?: boolean;
+  colorLabel?: ColorLabel;
+  clearColorLabel?: boolean; // 色ラベルを解除（colorLabel より優先）
+  addTagIds?: string[];
+  removeTagIds?: string[];
+}
+
+// 画像一覧の注釈による絞り込み条件（省略した条件は判定しない）
+export interface ImageAnnotationFilter {
+  minRating?: number;
+  favorite?: boolean;
+  colorLabels?: ColorLabel[]; // いずれかの色ラベルが付いている
+  tags?: TagExpression; // 画像のタグの条件式
+}
+
+// スマートコレクションの検索条件（省略した条件は判定しない）
+export interface SmartQuery {
+  cardIds?: string[] | null; // 対象のカード（省略時はすべてのカード）
+  cardTags?: TagExpression | null; // カードのタグの条件式
+  annotations?: ImageAnnotationFilter; // 画像の注釈の条件
+  modifiedAfter?: string | null; // 更新日時の下限（ISO 8601形式）
+  modifiedWithinDays?: number | null; // 直近の日数以内に更新された
+  sort?: SortMode | null; // 省略時はカードの並び順に、カードごとのソート方法で並べる
+  limit?: number | null;
+}
+
+// アルバムの画像
+export interface AlbumItem {
+  path: string; // アーカイブ内の画像は "<アーカイブ>!/<エントリ名>"
+  addedAt: string;
+}
+
+// アルバム（複数のフォルダから選んだ画像を手動で並べたもの）
+export interface Album {
+  id: string;
+  title: string;
+  items: AlbumItem[]; // 表示順
+  sortOrder: number;
+  createdAt: string;
+  updatedAt: string;
+}
+
+// アルバムの検証結果
+export interface AlbumStatus {
+  albumId: string;
+  itemCount: number; // 見つからない画像を含む
+  missingPaths: string[]; // 見つからない画像のパス（アルバムの表示順）
+}
+
+// スマートコレクション（全カードから条件に一致する画像を集める保存済みの検索）
+export interface SmartCollection {
+  id: string;
+  title: string;
+  query: SmartQuery;
+  sortOrder: number;
+  createdAt: string;
+  updatedAt: string;
+}
+
 // ウィンドウ状態
 export interface WindowState {
   x: number | null;
@@ -56,6 +211,59 @@ export interface ProfileData {
   tags: Tag[];
   cardTags: CardTag[];
   appState: AppState;
+  relativePaths?: boolean; // プロファイルファイルのフォルダ以下のパスを相対パスで保存する
+  imageAnnotations?: ImageAnnotation[];
+  smartCollections?: SmartCollection[];
+  albums?: Album[];
+}
+
+// プロファイルのバックアップ
+export interface ProfileBackup {
+  path: string;
+  createdAt: string; // ISO 8601形式
+  size: number; // バイト
+  cardCount?: number | null; // 解析できない場合は null
+}
+
+// プロファイルのロックの所有者
+export interface ProfileLockInfo {
+  sessionId: string;
+  user: string;
+  host: string;
+  pid: number;
+  acquiredAt: string; // ISO 8601形式
+  refreshedAt: string; // ISO 8601形式
+}
+
+// プロファイルのロックの取得結果
+export interface ProfileLockStatus {
+  acquired: boolean; // false の場合は他のユーザーが開いている
+  owner: ProfileLockInfo;
+}
+
+// プロファイルの保存時の競合（読み込み後に他のユーザーが保存していた）
+export interface ProfileConflict {
+  kind: "conflict";
+  message: string;
+  diskUpdatedAt: string;
+  expectedUpdatedAt: string;
+  lockedBy?: ProfileLockInfo | null;
+}
+
+// プロファイルの統合で解決できなかった競合（編集中の値を採用）
+export interface MergeConflict {
+  item: "card" | "tag" | "cardTag" | "imageAnnotation" | "smartCollection" | "album";
+  id: string; // カードとタグの関連付けは "<カードID>:<タグID>"、画像の注釈は "<カードID>:<相対パス>"
+  field?: string | null; // 削除と変更の競合は null
+  localValue?: unknown;
+  diskValue?: unknown;
+}
+
+// プロファイルの統合結果
+export interface ProfileMerge {
+  profile: ProfileData;
+  diskProfile: ProfileData;
+  conflicts: MergeConflict[];
 }
 
 // 最近使用したプロファイル
@@ -73,22 +281,144 @@ export interface AppConfig {
   theme: "light" | "dark" | "system";
   /** 起動時にウィンドウを最前面に表示する（Linux のフォーカス盗み防止対策、デフォルト: true） */
   focusOnStartup: boolean;
+  /** サムネイルのディスクキャッシュの上限サイズ（MB、0 は無制限、デフォルト: 512） */
+  thumbnailCacheMaxMb: number;
+  /** サムネイルのディスクキャッシュの保持期間（最終アクセスからの日数、0 は無期限、デフォルト: 90） */
+  thumbnailCacheMaxAgeDays: number;
+  /** サムネイルのメモリキャッシュの上限サイズ（MB、デフォルト: 64） */
+  thumbnailMemoryCacheMb: number;
+  /** JPEGサムネイルの品質（1〜100、デフォルト: 85。透過画像はPNGになるため影響しない） */
+  thumbnailJpegQuality: number;
 }
 
 // 画像ファイル情報（軽量版：ビューア用）
 export interface ImageFile {
   path: string;
   filename: string;
+  /** カードのルートフォルダからの相対パス（区切り文字は "/"） */
+  relativePath: string;
+  /** 画像のカードのID（スマートコレクション・アルバムなど、複数のカードから集めた一覧の場合のみ） */
+  cardId?: string;
+}
+
+// 注釈付きの画像
+export interface AnnotatedImage {
+  image: ImageFile;
+  annotation: ImageAnnotation | null;
 }
 
-// 画像詳細情報（将来の画像情報表示機能用）
+// 対応画像形式の情報
+export interface SupportedFormat {
+  /** 表示名（"JPEG" など） */
+  name: string;
+  mimeType: string;
+  /** 拡張子一覧（小文字、ドットなし） */
+  extensions: string[];
+}
+
+// EXIF情報の概要
+export interface ExifSummary {
+  make: string | null;
+  model: string | null;
+  lensModel: string | null;
+  /** 露出時間（"1/125 s" など） */
+  exposureTime: string | null;
+  /** F値（"f/2.8" など） */
+  fNumber: string | null;
+  iso: number | null;
+  /** 焦点距離（"50 mm" など） */
+  focalLength: string | null;
+  /** 撮影日時（EXIF形式 "YYYY:MM:DD HH:MM:SS"） */
+  dateTaken: string | null;
+  /** 緯度（十進度、南緯は負） */
+  gpsLatitude: number | null;
+  /** 経度（十進度、西経は負） */
+  gpsLongitude: number | null;
+}
+
+// 画像詳細情報（画像情報表示用）
 export interface ImageInfo {
   path: string;
   filename: string;
-  width: number;
-  height: number;
+  /** ファイルサイズ（バイト） */
+  size: number;
+  modifiedAt: string | null;
+  /** EXIFの向きを適用した表示上のサイズ */
+  width: number | null;
+  height: number | null;
+  /** 画像形式の表示名（"JPEG" など） */
+  format: string | null;
+  colorType: string | null;
+  /** アニメーションのフレーム数（静止画は null） */
+  frameCount: number | null;
+  exif: ExifSummary | null;
+}
+
+// 画像一覧の変更（"images-changed" イベント）
+export interface ImagesChanged {
+  cardId: string;
+  folderPath: string;
+  /** 追加・削除・変更されたファイルやフォルダのパス */
+  changedPaths: string[];
+}
+
+// アニメーション情報
+export interface AnimationInfo {
+  /** アニメーション画像かどうか（静止画は false） */
+  animated: boolean;
+  /** フレーム数（静止画は 1） */
+  frameCount: number;
+  /** 各フレームの表示時間（ミリ秒、静止画は空） */
+  frameDelays: number[];
+  /** 1周の再生時間（ミリ秒） */
+  totalDuration: number;
+}
+
+// サムネイル一括生成の進捗（"thumbnail-progress" イベント）
+export interface ThumbnailProgress {
+  jobId: string;
+  imagePath: string;
   size: number;
-  modifiedAt: string;
+  completed: number;
+  total: number;
+  /** 生成に失敗した場合のエラーメッセージ */
+  error: string | null;
+}
+
+// サムネイル一括生成の終了（"thumbnail-job-finished" イベント）
+export interface ThumbnailJobFinished {
+  jobId: string;
+  completed: number;
+  total: number;
+  cancelled: boolean;
+}
+
+// サムネイルキャッシュの統計情報
+export interface ThumbnailCacheStats {
+  /** ディスクキャッシュのサムネイル数 */
+  fileCount: number;
+  /** ディスクキャッシュの合計サイズ（バイト） */
+  totalBytes: number;
+  /** ディスクキャッシュの上限サイズ（バイト、0 は無制限） */
+  maxBytes: number;
+  /** メモリキャッシュのサムネイル数 */
+  memoryEntries: number;
+  /** メモリキャッシュの合計サイズ（バイト） */
+  memoryBytes: number;
+  /** メモリキャッシュの上限サイズ（バイト） */
+  memoryMaxBytes: number;
+  /** 以下は起動後の累計 */
+  memoryHits: number;
+  diskHits: number;
+  misses: number;
+  /** ヒット率（0.0〜1.0） */
+  hitRatio: number;
+}
+
+// サムネイルキャッシュ整理の結果
+export interface ThumbnailCacheCleanup {
+  removedFiles: number;
+  freedBytes: number;
 }
 
 // ビューア状態（ランタイム用）